@@ -1,27 +1,93 @@
 use std::path::PathBuf;
 
+use serde::{Deserialize, Serialize};
+
+use crate::{connect::Output, util};
+
+/// Exit code when the app is left without choosing, e.g. with `Esc`
+pub const EXIT_KEEP: i32 = 1;
+
+/// Serialized as `{"action":"change","path":"/foo/bar"}`
+#[derive(Debug, Serialize)]
+#[serde(tag = "action", content = "path", rename_all = "lowercase")]
 pub enum Action {
+  /// `Enter` `c` `;`, the shell changes to the path
   Change(PathBuf),
+  /// `Esc` `q` `Ctrl+c`, the shell stays in the original directory
   Keep,
+  /// `p`, the path is printed and the shell stays in the original directory
   Print(PathBuf),
 }
 
+/// What `Enter`, or another key bound to `confirm`, finishes with
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EnterAction {
+  /// `Change` to the current directory
+  #[default]
+  Change,
+  /// `Print` the current directory
+  Print,
+  /// `Print` the selected file, or `Change` to the current directory when a directory is selected
+  ChangeOrPrintForFiles,
+}
+
+impl EnterAction {
+  pub fn name(&self) -> &'static str {
+    match self {
+      EnterAction::Change => "change",
+      EnterAction::Print => "print",
+      EnterAction::ChangeOrPrintForFiles => "change_or_print_for_files",
+    }
+  }
+}
+
 impl Action {
-  pub fn execute(&self) -> PathBuf {
-    let current = PathBuf::from(".");
+  /// The path of `Print` goes to `output`, the path returned is the one to change to
+  pub fn execute(&self, output: &Output) -> anyhow::Result<PathBuf> {
+    if let Action::Print(print_path) = self {
+      output.write(&print_path.to_string_lossy())?;
+    }
+    Ok(self.cd_path())
+  }
+  pub fn cd_path(&self) -> PathBuf {
     match self {
       Action::Change(cd_path) => cd_path.into(),
-      Action::Keep => current,
-      Action::Print(print_path) => {
-        println!("{}", print_path.display());
-        current
-      }
+      Action::Keep | Action::Print(_) => PathBuf::from("."),
     }
   }
+  /// Redundant separators and `.` are removed from the path to change to, and with `canonicalize` `..` and symbolic
+  /// links are resolved too, keeping the path as it is when that fails, e.g. it was deleted
+  pub fn normalize(self, canonicalize: bool) -> Self {
+    match self {
+      Action::Change(path) if canonicalize => Action::Change(util::canonicalize(&path).unwrap_or(path)),
+      Action::Change(path) => Action::Change(path.components().collect()),
+      action => action,
+    }
+  }
+  /// `Change` becomes `Print` of the same path, for `--print-only`
+  pub fn print_only(self) -> Self {
+    match self {
+      Action::Change(path) => Action::Print(path),
+      action => action,
+    }
+  }
+  /// `0` for `Change` and `Print`, `EXIT_KEEP` for `Keep`
+  pub fn exit_code(&self) -> i32 {
+    match self {
+      Action::Change(_) | Action::Print(_) => exitcode::OK,
+      Action::Keep => EXIT_KEEP,
+    }
+  }
+  pub fn to_json(&self) -> anyhow::Result<String> {
+    Ok(serde_json::to_string(self)?)
+  }
 }
 
 #[cfg(test)]
 mod tests {
+  use std::path::Path;
+
   use super::*;
 
   #[test]
@@ -29,10 +95,39 @@ mod tests {
     let current = PathBuf::from(".");
     let cd_path = PathBuf::from("/tmp");
     let action = Action::Change(cd_path.clone());
-    assert_eq!(action.execute(), cd_path);
+    assert_eq!(action.execute(&Output::Stdout).unwrap(), cd_path);
     let action = Action::Keep;
-    assert_eq!(action.execute(), current);
+    assert_eq!(action.execute(&Output::Stdout).unwrap(), current);
     let action = Action::Print(cd_path);
-    assert_eq!(action.execute(), current);
+    assert_eq!(action.execute(&Output::Stdout).unwrap(), current);
+    assert_eq!(action.exit_code(), 0);
+    assert_eq!(Action::Keep.exit_code(), EXIT_KEEP);
+  }
+
+  #[test]
+  fn test_normalize() {
+    let dir = std::env::temp_dir();
+    let path = |action| match action {
+      Action::Change(path) => path,
+      _ => unreachable!(),
+    };
+    assert_eq!(path(Action::Change(dir.join("./a//")).normalize(false)), dir.join("a"));
+    let missing = dir.join("_easychangedirectory_missing/..");
+    assert_eq!(path(Action::Change(missing.clone()).normalize(true)), missing);
+    assert_eq!(path(Action::Change(dir.join(".")).normalize(true)), util::canonicalize(&dir).unwrap());
+    assert!(matches!(Action::Keep.normalize(true), Action::Keep));
+  }
+
+  #[test]
+  fn test_print_only() {
+    assert!(matches!(Action::Change("/tmp".into()).print_only(), Action::Print(path) if path == Path::new("/tmp")));
+    assert!(matches!(Action::Keep.print_only(), Action::Keep));
+  }
+
+  #[test]
+  fn test_action_to_json() {
+    assert_eq!(Action::Change("/foo/bar".into()).to_json().unwrap(), r#"{"action":"change","path":"/foo/bar"}"#);
+    assert_eq!(Action::Keep.to_json().unwrap(), r#"{"action":"keep"}"#);
+    assert_eq!(Action::Print("/foo".into()).to_json().unwrap(), r#"{"action":"print","path":"/foo"}"#);
   }
 }
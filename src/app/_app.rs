@@ -1,19 +1,44 @@
 use std::{
-  env, io, mem,
+  collections::{HashMap, HashSet},
+  env, fs,
+  io::{self, IsTerminal},
+  mem, panic,
   path::{Path, PathBuf},
+  sync::Arc,
+  thread,
+  time::{Duration, Instant},
   vec,
 };
 
-use anyhow::bail;
 use crossterm::{
   event::{DisableMouseCapture, EnableMouseCapture},
   execute,
   terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use tui::{backend::CrosstermBackend, Terminal};
+use log::warn;
+use tui::{
+  backend::{Backend, CrosstermBackend},
+  Terminal,
+};
+
+use super::{
+  disk_space, expand_home, read_list, Areas, CaseMode, Clipboard, ColumnRatios, DirSizes, DirWatcher, FileCategory,
+  FileColors, Filter, History, InitialSelection, Input, InputKind, Item, ItemInfo, Jump, KeyMap, Loader, Messages,
+  Only, Operation, PreviewCommands, PreviewTrigger, QuickSelect, ReadOptions, Register, RegisterKind, Removal, Search,
+  State, StatefulList, Status, SymlinkNavigation, Theme, TreeEntry, PREVIEW_COMMAND_TIMEOUT,
+};
+use crate::{
+  action::Action,
+  config::{Bookmarks, LastDir, SavedColumnRatios},
+  error::PrintError,
+  util::{canonicalize, DateFormat},
+  Config,
+};
 
-use super::{Item, ItemInfo, Search, State, StatefulList};
-use crate::{action::Action, Config};
+/// Directories read faster than this are shown without the loading indicator
+const LOAD_WAIT: Duration = Duration::from_millis(30);
+/// Milliseconds the selection has to stay before the child block is read
+pub const DEFAULT_PREVIEW_DELAY: u64 = 80;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppMode {
@@ -25,19 +50,191 @@ pub enum AppMode {
 pub struct App {
   pub mode: AppMode,
   pub child_items: StatefulList,
+  clipboard: Clipboard,
+  /// Paths that could not be copied, printed to stderr after the terminal is restored
+  pub uncopied: Vec<PathBuf>,
+  /// The tree of the directory in the child block and its path, shown instead of `child_items`
+  child_tree: Option<(PathBuf, Vec<TreeEntry>)>,
   pub items: StatefulList,
   pub parent_items: StatefulList,
   pub grandparent_items: StatefulList,
   pub wd: PathBuf,
   grandparent_path: PathBuf,
+  home: Option<PathBuf>,
   pub search: Search,
+  pub filter: Option<Filter>,
+  /// Paths read with `--stdin`, shown in the working block instead of the entries of the working directory
+  pub list: Option<Vec<ItemInfo>>,
+  pub case: CaseMode,
+  pub jump: Option<Jump>,
+  pub quick_select: QuickSelect,
+  pub input: Option<Input>,
+  pub read_options: ReadOptions,
+  pub bookmarks: Bookmarks,
+  pub marked: HashSet<PathBuf>,
+  pub register: Option<Register>,
+  pub last_operation: Option<Operation>,
+  last_selected: HashMap<PathBuf, (PathBuf, usize)>,
+  pub messages: Messages,
+  pub status: Status,
+  pub disk_space: Option<String>,
+  pub list_height: usize,
+  pub areas: Areas,
+  /// Whether the child block is shown, child items are not read while hidden
+  pub show_preview: bool,
+  pub read_only: bool,
+  pub show_grandparent: bool,
+  /// Whether only the working block is shown, `None` to decide by the width against `_ED_COMPACT_WIDTH`
+  pub compact: Option<bool>,
+  pub wrap: bool,
+  /// Scroll offset of the file contents in the child block and the path it belongs to
+  preview_scroll: (Option<PathBuf>, usize),
+  /// When the child block is read after moving the selection
+  preview_due: Option<Instant>,
+  /// The child block was left empty with `_ED_PREVIEW_TRIGGER=manual` and is read before entering the selection
+  preview_skipped: bool,
+  pub loader: Loader,
+  pub dir_sizes: DirSizes,
+  pub watcher: DirWatcher,
+  pub keymap: KeyMap,
+  history: History,
+  pub show_help: bool,
+  pub preview_commands: PreviewCommands,
+  pub theme: Theme,
+  pub columns: ColumnRatios,
+  /// The border between blocks being dragged with the mouse, counted from the left among the blocks shown
+  pub dragged_border: Option<usize>,
+  /// The other pane of the split view, which is not focused
+  pub split: Option<Box<App>>,
+  pub is_right_pane: bool,
   pub config: Config,
 }
 
-const JUMP: usize = 4;
 impl App {
+  pub fn cancel_jump(&mut self) -> anyhow::Result<()> {
+    let origin = if let Some(jump) = self.jump.take() { jump.origin() } else { return Ok(()) };
+    match self.judge_mode() {
+      AppMode::Normal => self.items.select(origin),
+      AppMode::Search => self.search.select(origin),
+    }
+    self.follow_selection(origin)
+  }
+  pub fn change_sort(&mut self) -> anyhow::Result<()> {
+    self.read_options.sort = self.read_options.sort.next();
+    self.sort_all()
+  }
+  fn sort_all(&mut self) -> anyhow::Result<()> {
+    self.grandparent_items.sort_items(&self.read_options);
+    self.parent_items.sort_items(&self.read_options);
+    self.items.sort_items(&self.read_options);
+    self.child_items.sort_items(&self.read_options);
+    self.update_search_effect()
+  }
+  pub fn commit_jump(&mut self) -> anyhow::Result<()> {
+    self.jump = None;
+    let now_i = match self.judge_mode() {
+      AppMode::Normal => self.get_current_index(),
+      AppMode::Search => self.get_search_index(),
+    };
+    self.follow_selection(now_i)
+  }
+  /// The working directory, or in the list the selected directory or the directory of the selected file
+  pub fn confirm_path(&self) -> PathBuf {
+    match self.get_selected_path().filter(|_| self.list.is_some()) {
+      Some(path) if path.is_dir() => path,
+      Some(path) => Self::generate_parent_path(path),
+      None => self.wd.clone(),
+    }
+  }
+  fn create_dir(&mut self, name: &str) -> anyhow::Result<()> {
+    if name.is_empty() {
+      return Ok(());
+    }
+    if self.is_contents_in_working_block() || self.list.is_some() {
+      self.messages.warn("Cannot create a directory here");
+      return Ok(());
+    }
+    let path = self.wd.join(name);
+    if let Err(e) = fs::create_dir(&path) {
+      self.messages.error(format!("Failed to create {}: {}", name, e));
+      return Ok(());
+    }
+    self.last_operation = Some(Operation::CreateDir(name.into()));
+    self.reload_items()?;
+    self.select_path(&path)
+  }
+  /// The selected path, or the current directory while viewing file contents or an empty directory
+  pub fn copy_path(&mut self) {
+    let path = self.get_selected_path().unwrap_or_else(|| self.wd.clone());
+    match self.clipboard.copy(&path.to_string_lossy()) {
+      Ok(()) => self.messages.info(format!("Copied! {}", self.config.display_path(&path))),
+      Err(e) => {
+        self.uncopied.push(path);
+        self.messages.error(format!("Could not copy, printed on exit instead: {}", e));
+      }
+    }
+  }
+  pub fn cut(&mut self) {
+    if self.deny_in_read_only("Cut") {
+      return;
+    }
+    self.set_register(RegisterKind::Cut);
+  }
+  fn deny_in_read_only(&mut self, operation: &str) -> bool {
+    if self.read_only {
+      self.messages.warn(format!("{} is disabled in read-only mode", operation));
+    }
+    self.read_only
+  }
+  /// Read the child block once the selection has stayed for the preview delay
+  fn defer_child_items(&mut self, index: usize) -> anyhow::Result<()> {
+    if self.config.preview_trigger() == PreviewTrigger::Manual && self.show_preview {
+      self.skip_child_items();
+      return Ok(());
+    }
+    let delay = self.config.preview_delay();
+    if delay.is_zero() || !self.show_preview {
+      return self.update_child_items(index);
+    }
+    self.loader.cancel();
+    self.child_items = StatefulList::with_items_option(vec![], self.child_items.state.selected());
+    self.preview_due = Some(Instant::now() + delay);
+    Ok(())
+  }
+  /// Entries that cannot be moved to the trash are asked again to be deleted permanently
+  fn delete(&mut self, paths: &[PathBuf], removal: Removal) -> anyhow::Result<()> {
+    self.last_operation = Some(Operation::Delete);
+    for (i, path) in paths.iter().enumerate() {
+      match super::file_op::delete(path, &removal) {
+        Ok(()) => {}
+        Err(e) if removal == Removal::Trash => {
+          warn!("Cannot move {} to the trash: {}", path.display(), e);
+          self.input = Some(Input::new(InputKind::DeletePermanently(paths[i..].to_vec())));
+          break;
+        }
+        Err(e) => {
+          self.messages.error(format!("Failed to delete {}: {}", self.config.display_path(path), e));
+          break;
+        }
+      }
+      self.messages.info(match paths[..=i] {
+        [_] => format!("{} {}", removal.verb(), path.file_name().unwrap_or_default().to_string_lossy()),
+        _ => format!("{} {} items", removal.verb(), i + 1),
+      });
+    }
+    self.marked.clear();
+    self.reload_items()
+  }
+  /// `_ED_TRASH_DIR` if set, otherwise the trash of the system unless `_ED_USE_TRASH` is `0`
+  fn removal(&self) -> Removal {
+    match self.config.trash_dir() {
+      Some(trash_dir) => Removal::MoveInto(trash_dir.into()),
+      None if self.config.is_use_trash() => Removal::Trash,
+      None => Removal::Permanent,
+    }
+  }
   fn generate_index<P: AsRef<Path>>(items: &[ItemInfo], path: P) -> usize {
-    let generate_item = items.iter().enumerate().find(|(_, item)| item.get_path().unwrap() == path.as_ref());
+    let generate_item = items.iter().enumerate().find(|(_, item)| item.get_path().as_deref() == Some(path.as_ref()));
     if let Some((i, _)) = generate_item {
       i
     } else {
@@ -47,47 +244,74 @@ impl App {
   fn generate_parent_path<P: AsRef<Path>>(path: P) -> PathBuf {
     path.as_ref().parent().unwrap_or_else(|| Path::new("")).into()
   }
+  /// The home directory is abbreviated to `~` unless `_ED_NO_TILDE` is set
   pub fn generate_wd_str(&self) -> String {
-    self.wd.to_string_lossy().into()
+    self.config.display_path(&self.wd)
   }
   fn get_child_index(&self) -> usize {
     self.child_items.state.selected().unwrap_or(0)
   }
-  pub fn get_child_items(&self) -> Vec<ItemInfo> {
-    self.child_items.items.clone()
+  pub fn get_child_items(&self) -> &[ItemInfo] {
+    &self.child_items.items
+  }
+  pub fn get_child_tree(&self) -> Option<&[TreeEntry]> {
+    let (path, tree) = self.child_tree.as_ref()?;
+    (self.get_selected_path().as_ref() == Some(path)).then_some(tree.as_slice())
   }
   fn get_current_index(&self) -> usize {
     self.items.state.selected().unwrap_or(0)
   }
-  pub fn get_items(&self) -> Vec<ItemInfo> {
-    self.items.items.clone()
+  pub fn get_items(&self) -> &[ItemInfo] {
+    &self.items.items
   }
-  pub fn get_parent_items(&self) -> Vec<ItemInfo> {
-    self.parent_items.items.clone()
+  pub fn get_parent_items(&self) -> &[ItemInfo] {
+    &self.parent_items.items
   }
   fn get_search_index(&self) -> usize {
     self.search.state.selected().unwrap_or(0)
   }
-  fn get_search_list(&self) -> Vec<ItemInfo> {
-    self.search.list.clone()
+  fn get_search_list(&self) -> &[ItemInfo] {
+    &self.search.list
   }
-  fn get_selected_item(&self) -> ItemInfo {
+  fn get_selected_item(&self) -> &ItemInfo {
     match self.judge_mode() {
-      AppMode::Normal => self.items.items[self.items.selected()].clone(),
-      AppMode::Search => self.search.list[self.search.state.selected().unwrap()].clone(),
+      AppMode::Normal => &self.items.items[self.items.selected()],
+      AppMode::Search => &self.search.list[self.search.state.selected().unwrap()],
     }
   }
-  pub fn get_selected_filepath(&self) -> PathBuf {
-    self.get_selected_item().get_path().unwrap()
+  /// `None` for empty blocks and file contents
+  pub fn get_selected_path(&self) -> Option<PathBuf> {
+    let item = match self.judge_mode() {
+      AppMode::Normal => self.items.items.get(self.items.state.selected()?)?,
+      AppMode::Search => self.search.list.get(self.search.state.selected()?)?,
+    };
+    item.get_path().filter(|path| !path.as_os_str().is_empty())
+  }
+  /// Marked entries if any, otherwise the selected one
+  fn get_target_paths(&self) -> Vec<PathBuf> {
+    if self.marked.is_empty() {
+      return self.get_selected_path().into_iter().collect();
+    }
+    let mut paths: Vec<_> = self.marked.iter().cloned().collect();
+    paths.sort();
+    paths
+  }
+  fn keep_filter(&mut self) {
+    if !self.config.is_sticky_filter() {
+      self.filter = None;
+    } else if let Some(filter) = &self.filter {
+      self.items.map_items(|items| filter.apply(items));
+    }
+  }
+  pub fn invert_marks(&mut self) {
+    let marked = mem::take(&mut self.marked);
+    self.marked = self.markable_paths().into_iter().filter(|path| !marked.contains(path)).collect();
   }
   /// If the working block is "content" `true`
   fn is_contents_in_working_block(&self) -> bool {
     let i = self.parent_items.selected();
     self.get_parent_items()[i].is_file()
   }
-  fn is_child_empty(&self) -> bool {
-    self.child_items.items.is_empty()
-  }
   fn is_empty_in_working_block(&self) -> bool {
     match self.judge_mode() {
       AppMode::Normal => self.items.items.is_empty(),
@@ -101,20 +325,168 @@ impl App {
       AppMode::Search
     }
   }
-  pub fn make_items<P: AsRef<Path>>(path: P) -> anyhow::Result<Vec<ItemInfo>> {
-    Ok(if path.as_ref().to_string_lossy().is_empty() { vec![ItemInfo::default()] } else { super::read_items(path)? })
+  /// Read the child block even while the preview is hidden
+  fn load_child_items(&mut self, index: usize) -> anyhow::Result<()> {
+    if self.is_empty_in_working_block() {
+      self.child_items = StatefulList::with_items_option(vec![], None);
+      return Ok(());
+    }
+
+    let ci = self.child_items.state.selected();
+
+    let items = match self.judge_mode() {
+      AppMode::Normal => self.get_items(),
+      AppMode::Search => self.get_search_list(),
+    };
+
+    let item = if let Some(item) = items.get(index) { item.clone() } else { return Ok(()) };
+    if item.is_dir() {
+      self.child_items = StatefulList::with_items_option(vec![], ci);
+      self.loader.request(item.get_path().unwrap(), self.read_options);
+      return self.receive_items(LOAD_WAIT);
+    }
+    if let Some(command) =
+      item.get_path().filter(|_| item.can_read()).and_then(|path| self.preview_commands.find(&path))
+    {
+      let command = command.clone();
+      self.child_items = StatefulList::with_items_option(vec![], None);
+      self.loader.request_preview(item, command, self.read_options);
+      return self.receive_items(LOAD_WAIT);
+    }
+    self.child_items = StatefulList::with_items_option(item.generate_child_items(&self.read_options)?, ci);
+    if item.is_file() {
+      self.child_items.unselect();
+    }
+
+    Ok(())
+  }
+  /// The empty path is above the root
+  pub fn make_items<P: AsRef<Path>>(path: P, options: &ReadOptions) -> anyhow::Result<Vec<ItemInfo>> {
+    Ok(if path.as_ref().to_string_lossy().is_empty() {
+      Self::make_root_items()
+    } else {
+      super::read_items(path, options)?
+    })
+  }
+  /// The drives, so that another drive can be chosen from the parent block of a drive root
+  #[cfg(windows)]
+  fn make_root_items() -> Vec<ItemInfo> {
+    crate::util::drive_roots()
+      .into_iter()
+      .enumerate()
+      .map(|(i, root)| ItemInfo { item: Item::Path(super::ItemPath::Dir(root)), index: Some(i) })
+      .collect()
+  }
+  #[cfg(not(windows))]
+  fn make_root_items() -> Vec<ItemInfo> {
+    vec![ItemInfo::default()]
+  }
+  pub fn mark_all(&mut self) {
+    self.marked = self.markable_paths().into_iter().collect();
+  }
+  fn markable_paths(&self) -> Vec<PathBuf> {
+    if self.is_contents_in_working_block() {
+      return vec![];
+    }
+    self.items.items.iter().filter_map(|item| item.get_path()).filter(|path| !path.as_os_str().is_empty()).collect()
+  }
+  pub fn measure_selected(&mut self) {
+    match self.get_selected_path() {
+      Some(path) if path.is_dir() => self.dir_sizes.request(path),
+      _ => {}
+    }
+  }
+  pub fn move_bookmark(&mut self, key: char) -> anyhow::Result<()> {
+    match self.bookmarks.get(key) {
+      Some(path) if path.is_dir() => self.move_to(path.clone())?,
+      Some(path) => {
+        self.messages.warn(format!("Bookmark '{}' no longer exists: {}", key, self.config.display_path(path)))
+      }
+      None => self.messages.warn(format!("Bookmark '{}' is not set", key)),
+    }
+    Ok(())
+  }
+  pub fn move_back(&mut self) -> anyhow::Result<()> {
+    let path = self.history.back().map(Path::to_path_buf);
+    self.move_in_history(path, "No previous directory")
+  }
+  pub fn move_forward(&mut self) -> anyhow::Result<()> {
+    let path = self.history.forward().map(Path::to_path_buf);
+    self.move_in_history(path, "No next directory")
+  }
+  fn move_in_history(&mut self, path: Option<PathBuf>, none: &str) -> anyhow::Result<()> {
+    match path {
+      Some(path) if path.is_dir() => self.move_to(path),
+      Some(path) => {
+        self.messages.warn(format!("No longer exists: {}", self.config.display_path(&path)));
+        Ok(())
+      }
+      None => {
+        self.messages.warn(none);
+        Ok(())
+      }
+    }
+  }
+  /// `$HOME`, or `%USERPROFILE%` on Windows
+  pub fn move_home_dir(&mut self) -> anyhow::Result<()> {
+    match self.home.clone().filter(|home| home.is_dir()) {
+      Some(home) => self.move_to(home),
+      None => {
+        self.messages.warn("The home directory is not found");
+        Ok(())
+      }
+    }
   }
   pub fn move_child(&mut self) -> anyhow::Result<()> {
-    if self.is_empty_in_working_block() || self.is_child_empty() {
+    if !self.is_empty_in_working_block() && self.get_selected_item().is_parent() {
+      return self.move_parent();
+    }
+    let is_unread = !self.show_preview || self.preview_due.take().is_some() || mem::take(&mut self.preview_skipped);
+    if is_unread && !self.is_empty_in_working_block() {
+      let now_i = match self.judge_mode() {
+        AppMode::Normal => self.get_current_index(),
+        AppMode::Search => self.get_search_index(),
+      };
+      self.load_child_items(now_i)?;
+    }
+    // Empty directories can be entered, the working block is empty then
+    if self.is_empty_in_working_block() {
       return Ok(());
     }
 
     let selected_item = self.get_selected_item();
-    let new_wd = if selected_item.is_dir() {
-      selected_item.get_path().unwrap()
-    } else if selected_item.is_file() && self.config.is_view_file_contents() {
-      self.move_content(selected_item)?;
-      return Ok(());
+    let (is_dir, is_file, is_symlink) = (selected_item.is_dir(), selected_item.is_file(), selected_item.is_symlink());
+    let path = if let Some(path) = selected_item.get_path() { path } else { return Ok(()) };
+    // The list is left for the directory, or for the directory of the file with the file selected
+    if self.list.is_some() && is_dir {
+      return self.move_to(path);
+    } else if self.list.is_some() {
+      return match path.parent().filter(|parent| parent.is_dir()) {
+        Some(parent) => {
+          self.move_to(parent)?;
+          self.select_path(&path)
+        }
+        None => Ok(()),
+      };
+    }
+    // Physical continues from the real location so that `h` goes back to the parent of the target
+    if is_dir && is_symlink && self.config.symlink_navigation() == SymlinkNavigation::Physical {
+      return match canonicalize(&path) {
+        Ok(target) => self.move_to(target),
+        Err(e) => {
+          self.messages.error(format!("Failed to resolve the link: {}", e));
+          Ok(())
+        }
+      };
+    }
+    let new_wd = if is_dir {
+      path
+    } else if is_file && self.config.is_view_file_contents() {
+      // The output of the preview command is still being read
+      if self.loader.pending() == Some(path.as_path()) {
+        self.receive_items(PREVIEW_COMMAND_TIMEOUT + LOAD_WAIT)?;
+      }
+      return self.move_content(path);
     } else {
       return Ok(());
     };
@@ -122,11 +494,7 @@ impl App {
     let selected_ci = self.get_child_index();
 
     // The index of `items` is set to the index of `child_items` if it is selected. If not, it is set to `0`.
-    let (new_child_items, new_i) = if let Some(items) = self.get_child_items().get(selected_ci) {
-      (items.generate_child_items()?, self.get_child_index())
-    } else {
-      (self.get_child_items().get(0).unwrap_or(&ItemInfo::default()).generate_child_items()?, 0)
-    };
+    let new_i = if self.get_child_items().get(selected_ci).is_some() { selected_ci } else { 0 };
 
     let new_pi = match self.judge_mode() {
       AppMode::Normal => Some(self.get_current_index()),
@@ -134,42 +502,49 @@ impl App {
     };
 
     let new_grandparent_path = Self::generate_parent_path(&self.wd);
+    self.remember_selection();
 
     self.wd = new_wd;
+    self.history.push(&self.wd);
+    self.marked.clear();
     self.grandparent_path = new_grandparent_path;
     self.search = Search::new();
     self.grandparent_items = mem::replace(
       &mut self.parent_items,
-      mem::replace(
-        &mut self.items,
-        mem::replace(&mut self.child_items, StatefulList::with_items_option(new_child_items, None)),
-      ),
+      mem::replace(&mut self.items, mem::replace(&mut self.child_items, StatefulList::with_items_option(vec![], None))),
     );
+    let new_i = self.remembered_index(&self.wd, &self.items.items).unwrap_or(new_i);
     self.items.state.select(Some(new_i));
     self.parent_items.state.select(new_pi);
+    self.keep_filter();
 
-    Ok(())
+    // Still being read if entered right after selecting it, then the child block follows when it arrives
+    if self.loader.pending() == Some(self.wd.as_path()) {
+      return Ok(());
+    }
+    self.follow_selection(self.get_current_index())
   }
-  pub fn move_content(&mut self, selected_item: ItemInfo) -> anyhow::Result<()> {
+  /// Enter the file at `path`, whose contents are in the child block
+  pub fn move_content(&mut self, path: PathBuf) -> anyhow::Result<()> {
     let new_pi = match self.judge_mode() {
       AppMode::Normal => Some(self.get_current_index()),
       AppMode::Search => self.get_search_list()[self.get_search_index()].index,
     };
     let new_grandparent_path = Self::generate_parent_path(&self.wd);
 
-    self.wd = selected_item.get_path().unwrap();
+    self.wd = path;
+    self.marked.clear();
     self.grandparent_path = new_grandparent_path;
     self.search = Search::new();
     self.grandparent_items = mem::replace(
       &mut self.parent_items,
-      mem::replace(
-        &mut self.items,
-        mem::replace(&mut self.child_items, StatefulList::with_items(vec![ItemInfo::default()])),
-      ),
+      mem::replace(&mut self.items, mem::replace(&mut self.child_items, StatefulList::with_items_option(vec![], None))),
     );
     self.items.state.select(Some(0));
     self.parent_items.state.select(new_pi);
 
+    self.keep_filter();
+
     Ok(())
   }
   pub fn move_end(&mut self) -> anyhow::Result<()> {
@@ -185,7 +560,7 @@ impl App {
       AppMode::Normal => self.items.select(last_i),
       AppMode::Search => self.search.select(last_i),
     };
-    self.update_child_items(last_i)?;
+    self.defer_child_items(last_i)?;
     Ok(())
   }
   pub fn move_home(&mut self) -> anyhow::Result<()> {
@@ -198,19 +573,34 @@ impl App {
       AppMode::Normal => self.items.select(top_i),
       AppMode::Search => self.search.select(top_i),
     }
-    self.update_child_items(top_i)?;
+    self.defer_child_items(top_i)?;
     Ok(())
   }
+  pub fn move_index(&mut self, index: usize) -> anyhow::Result<()> {
+    if self.is_empty_in_working_block() {
+      return Ok(());
+    }
+
+    let new_i = match self.judge_mode() {
+      AppMode::Normal => index.min(self.items.items.len() - 1),
+      AppMode::Search => index.min(self.search.list.len() - 1),
+    };
+    match self.judge_mode() {
+      AppMode::Normal => self.items.select(new_i),
+      AppMode::Search => self.search.select(new_i),
+    }
+    self.defer_child_items(new_i)
+  }
   pub fn move_next(&mut self) -> anyhow::Result<()> {
     if self.is_empty_in_working_block() {
       return Ok(());
     }
 
     let new_i = match self.judge_mode() {
-      AppMode::Normal => self.items.next(),
-      AppMode::Search => self.search.next(),
+      AppMode::Normal => self.items.next(self.wrap),
+      AppMode::Search => self.search.next(self.wrap),
     };
-    self.update_child_items(new_i)?;
+    self.defer_child_items(new_i)?;
     Ok(())
   }
   pub fn move_page_down(&mut self) -> anyhow::Result<()> {
@@ -218,22 +608,12 @@ impl App {
       return Ok(());
     }
 
-    let (last_i, old_i) = match self.judge_mode() {
-      AppMode::Normal => (self.items.items.len() - 1, self.get_current_index()),
-      AppMode::Search => (self.search.list.len() - 1, self.get_search_index()),
-    };
-    let new_i = if <isize as std::convert::TryInto<usize>>::try_into(last_i as isize - JUMP as isize).is_ok()
-      && old_i.le(&(last_i - JUMP))
-    {
-      old_i + JUMP
-    } else {
-      last_i
+    let page = self.page_size();
+    let new_i = match self.judge_mode() {
+      AppMode::Normal => self.items.page_down(page, self.wrap),
+      AppMode::Search => self.search.page_down(page, self.wrap),
     };
-    match self.judge_mode() {
-      AppMode::Normal => self.items.select(new_i),
-      AppMode::Search => self.search.select(new_i),
-    }
-    self.update_child_items(new_i)?;
+    self.defer_child_items(new_i)?;
     Ok(())
   }
   pub fn move_page_up(&mut self) -> anyhow::Result<()> {
@@ -241,27 +621,44 @@ impl App {
       return Ok(());
     }
 
-    let old_i = match self.judge_mode() {
-      AppMode::Normal => self.get_current_index(),
-      AppMode::Search => self.get_search_index(),
-    };
-    let new_i = if old_i < JUMP { 0 } else { old_i - JUMP };
-    match self.judge_mode() {
-      AppMode::Normal => self.items.select(new_i),
-      AppMode::Search => self.search.select(new_i),
+    let page = self.page_size();
+    let new_i = match self.judge_mode() {
+      AppMode::Normal => self.items.page_up(page, self.wrap),
+      AppMode::Search => self.search.page_up(page, self.wrap),
     };
-    self.update_child_items(new_i)?;
+    self.defer_child_items(new_i)?;
     Ok(())
   }
   pub fn move_parent(&mut self) -> anyhow::Result<()> {
-    let new_wd = if let Some(wd) = self.wd.parent() {
+    // Physical goes up from the real location of a path reached through a link
+    if self.config.symlink_navigation() == SymlinkNavigation::Physical && self.list.is_none() {
+      match canonicalize(&self.wd) {
+        Ok(real) if real != self.wd => {
+          return match real.parent() {
+            Some(parent) => {
+              self.move_to(parent)?;
+              self.select_path(&real)
+            }
+            None => self.move_to(real),
+          };
+        }
+        _ => {}
+      }
+    }
+    let new_wd: PathBuf = if let Some(wd) = self.wd.parent() {
       wd.into()
     } else {
       return Ok(());
     };
+    // The working block is not the child of the parent block
+    if self.list.is_some() {
+      let wd = self.wd.clone();
+      self.move_to(new_wd)?;
+      return self.select_path(&wd);
+    }
 
     let new_grandparent_path = Self::generate_parent_path(&self.grandparent_path);
-    let new_grandparent_items = Self::make_items(&new_grandparent_path)?;
+    let new_grandparent_items = Self::make_items(&new_grandparent_path, &self.read_options)?;
 
     let new_ci = if self.is_contents_in_working_block() {
       None
@@ -278,8 +675,13 @@ impl App {
       }
     };
     let new_gi = Self::generate_index(&new_grandparent_items, &self.grandparent_path);
+    // The working block becomes the child block as it is
+    self.preview_due = None;
+    self.remember_selection();
 
     self.wd = new_wd;
+    self.history.push(&self.wd);
+    self.marked.clear();
     self.grandparent_path = new_grandparent_path;
     self.search = Search::new();
     self.child_items = mem::replace(
@@ -290,6 +692,7 @@ impl App {
       ),
     );
     self.child_items.state.select(new_ci);
+    self.keep_filter();
 
     Ok(())
   }
@@ -299,64 +702,461 @@ impl App {
     }
 
     let new_i = match self.judge_mode() {
-      AppMode::Normal => self.items.previous(),
-      AppMode::Search => self.search.previous(),
+      AppMode::Normal => self.items.previous(self.wrap),
+      AppMode::Search => self.search.previous(self.wrap),
     };
-    self.update_child_items(new_i)?;
+    self.defer_child_items(new_i)?;
     Ok(())
   }
-  fn new() -> anyhow::Result<App> {
-    let wd = env::current_dir()?;
-    let items = super::read_items(&wd)?;
+  pub fn move_to<P: Into<PathBuf>>(&mut self, wd: P) -> anyhow::Result<()> {
+    let wd = wd.into();
+    self.loader.cancel();
+    let items = super::read_items(&wd, &self.read_options)?;
 
-    // Initial selection is 0
-    let child_path = match items.get(0) {
-      Some(item) => {
-        if item.is_dir() {
-          item.get_path().unwrap()
-        } else {
-          PathBuf::new()
-        }
-      }
-      None => PathBuf::new(),
-    };
     let parent_path = Self::generate_parent_path(&wd);
     let grandparent_path = Self::generate_parent_path(&parent_path);
-    let parent_items = Self::make_items(&parent_path)?;
-    let grandparent_items = Self::make_items(&grandparent_path)?;
+    let parent_items = Self::make_items(&parent_path, &self.read_options)?;
+    let grandparent_items = Self::make_items(&grandparent_path, &self.read_options)?;
     let pi = Self::generate_index(&parent_items, &wd);
     let gi = Self::generate_index(&grandparent_items, &parent_path);
+    let i = self.remembered_index(&wd, &items).unwrap_or(0);
+    self.remember_selection();
+
+    self.child_items = StatefulList::with_items_option(vec![], None);
+    self.items = StatefulList::with_items_select(items, i);
+    self.parent_items = StatefulList::with_items_select(parent_items, pi);
+    self.grandparent_items = StatefulList::with_items_select(grandparent_items, gi);
+    self.wd = wd;
+    self.history.push(&self.wd);
+    self.marked.clear();
+    self.grandparent_path = grandparent_path;
+    self.search = Search::new();
+    self.jump = None;
+    self.list = None;
+    self.keep_filter();
+    self.disk_space = self.config.is_disk_space().then(|| disk_space(&self.wd, self.config.size_unit())).flatten();
+
+    self.follow_selection(self.get_current_index())
+  }
+  /// `start` is a directory to start in, or a file to select in its directory, which takes precedence over `select`
+  fn new(
+    start: Option<&Path>,
+    select: Option<InitialSelection>,
+    ignore_last_dir: bool,
+    read_only: bool,
+  ) -> anyhow::Result<App> {
+    Self::with_config(Config::new()?, start, select, ignore_last_dir, read_only)
+  }
+  /// `select` overrides `_ED_INITIAL_SELECTION`
+  pub fn with_config(
+    config: Config,
+    start: Option<&Path>,
+    select: Option<InitialSelection>,
+    ignore_last_dir: bool,
+    read_only: bool,
+  ) -> anyhow::Result<App> {
+    let default_wd = match LastDir::read() {
+      Some(last_dir) if config.is_restore_last_dir() && !ignore_last_dir => last_dir,
+      _ => env::current_dir()?,
+    };
+    let (wd, selected, start_error) = match start.map(Self::resolve_start) {
+      Some(Ok((wd, selected))) => (wd, selected, None),
+      Some(Err(e)) => (default_wd, None, Some(e)),
+      None => (default_wd, None, None),
+    };
+    let read_options = ReadOptions::from_config(&config);
+    let preview_commands = PreviewCommands::new(config.preview_commands())
+      .map_err(|e| anyhow::anyhow!("Invalid _ED_PREVIEW_COMMANDS: {}", e))?;
+    let keymap = KeyMap::new(config.keymap()).map_err(|e| anyhow::anyhow!("Invalid _ED_KEYMAP: {}", e))?;
+    let date_format =
+      DateFormat::new(config.date_format()).map_err(|e| anyhow::anyhow!("Invalid _ED_DATE_FORMAT: {}", e))?;
+    let mut theme = Theme::new(config.theme()).map_err(|e| anyhow::anyhow!("Invalid _ED_THEME: {}", e))?;
+    theme.files = config.is_file_colors().then(|| FileColors::new(env::var("LS_COLORS").ok().as_deref()));
+    // Only the layout is affected, so start with the defaults
+    let saved_columns = config.is_save_column_ratios().then(SavedColumnRatios::read).flatten();
+    let mut messages = Messages::default();
+    let columns = ColumnRatios::new(saved_columns.as_deref().or(config.column_ratios())).unwrap_or_else(|e| {
+      messages.warn(format!("Invalid _ED_COLUMN_RATIOS, using the defaults: {}", e));
+      ColumnRatios::default()
+    });
+    if let Some(start_error) = start_error {
+      messages.warn(start_error);
+    }
 
     let mut app = App {
       mode: AppMode::Normal,
-      child_items: StatefulList::with_items_option(Self::make_items(child_path)?, None),
-      items: StatefulList::with_items(items),
-      parent_items: StatefulList::with_items(parent_items),
-      grandparent_items: StatefulList::with_items(grandparent_items),
-      wd,
-      grandparent_path,
+      child_items: StatefulList::with_items_option(vec![], None),
+      child_tree: None,
+      clipboard: Clipboard::default(),
+      uncopied: vec![],
+      items: StatefulList::with_items(vec![]),
+      parent_items: StatefulList::with_items(vec![]),
+      grandparent_items: StatefulList::with_items(vec![]),
+      wd: PathBuf::new(),
+      grandparent_path: PathBuf::new(),
       search: Search::new(),
-      config: Config::new()?,
+      filter: None,
+      list: None,
+      case: config.case_mode(),
+      jump: None,
+      quick_select: QuickSelect::default(),
+      input: None,
+      read_options,
+      bookmarks: Bookmarks::load(),
+      marked: HashSet::new(),
+      register: None,
+      last_operation: None,
+      last_selected: HashMap::new(),
+      home: home::home_dir(),
+      messages,
+      status: Status::new(config.size_unit(), date_format),
+      disk_space: None,
+      list_height: 0,
+      areas: Areas::default(),
+      // The preview of directories is mostly redundant when only directories are listed
+      show_preview: !config.is_dirs_only(),
+      show_grandparent: !config.is_hide_grandparent(),
+      read_only: read_only || config.is_read_only(),
+      compact: None,
+      wrap: !config.is_no_wrap(),
+      preview_scroll: (None, 0),
+      preview_due: None,
+      preview_skipped: false,
+      loader: Loader::default(),
+      dir_sizes: DirSizes::new(config.is_follow_symlinks()),
+      watcher: DirWatcher::default(),
+      keymap,
+      history: History::default(),
+      show_help: false,
+      preview_commands,
+      theme,
+      columns,
+      dragged_border: None,
+      split: None,
+      is_right_pane: false,
+      config,
     };
-
-    app.parent_items.select(pi);
-    app.grandparent_items.select(gi);
+    app.move_to(wd)?;
+    if let Some(selected) = selected {
+      app.select_path(&selected)?;
+    } else if let Some(i) = select.unwrap_or_else(|| app.config.initial_selection()).index(&app.items.items) {
+      app.move_index(i)?;
+    }
 
     Ok(app)
   }
+  /// One line overlaps with the previous page
+  fn page_size(&self) -> usize {
+    self.list_height.saturating_sub(1).max(1)
+  }
+  /// `true` when the opener was started
+  pub fn open_selected(&mut self) -> bool {
+    let path = if let Some(path) = self.get_selected_path() { path } else { return false };
+    match super::opener::open(&path) {
+      Ok(()) => true,
+      Err(e) => {
+        self.messages.error(format!("Failed to open {}: {}", self.config.display_path(&path), e));
+        false
+      }
+    }
+  }
+  pub fn reveal_selected(&mut self) {
+    let selected = self.get_selected_path().filter(|path| !path.as_os_str().is_empty());
+    if let Err(e) = super::opener::reveal(&self.wd, selected.as_deref()) {
+      self.messages.error(format!("Failed to open the file manager: {}", e));
+    }
+  }
+  /// 1-based position of the selection and the number of entries in the working block, `None` for file contents
+  pub fn position(&self) -> Option<(usize, usize)> {
+    let (i, items) = match self.judge_mode() {
+      AppMode::Normal => (self.get_current_index(), &self.items.items),
+      AppMode::Search => (self.get_search_index(), &self.search.list),
+    };
+    // `..` is not counted, and the placeholder above the root is not an entry either
+    let start = usize::from(items.first().is_some_and(ItemInfo::is_parent));
+    let entries = &items[start..];
+    if entries.is_empty() || entries.iter().any(|item| item.get_path().is_none_or(|path| path.as_os_str().is_empty())) {
+      return None;
+    }
+    Some((i.checked_sub(start)? + 1, entries.len()))
+  }
+  pub fn paste(&mut self) -> anyhow::Result<()> {
+    if self.deny_in_read_only("Paste") {
+      return Ok(());
+    }
+    let register = if let Some(register) = self.register.take() { register } else { return Ok(()) };
+    if self.is_contents_in_working_block() || self.list.is_some() {
+      self.messages.warn("Cannot paste here");
+      self.register = Some(register);
+      return Ok(());
+    }
+    let cut = register.kind == RegisterKind::Cut;
+    let to = self.wd.clone();
+    let last = self.paste_paths(&register.paths, &to, cut);
+    // The cut sources are gone, while copies can be pasted again
+    if !cut {
+      self.register = Some(register);
+    }
+    self.reload_items()?;
+    match last {
+      Some(path) => self.select_path(&path),
+      None => Ok(()),
+    }
+  }
+  /// Stops at the first failure, returning the last path pasted
+  fn paste_paths(&mut self, paths: &[PathBuf], to: &Path, cut: bool) -> Option<PathBuf> {
+    self.last_operation = Some(Operation::Paste { to: to.into(), cut });
+    let mut last = None;
+    for from in paths {
+      match super::file_op::paste(from, to, cut) {
+        Ok(path) => last = Some(path),
+        Err(e) => {
+          self.messages.error(format!("Failed to paste {}: {}", self.config.display_path(from), e));
+          break;
+        }
+      }
+    }
+    last
+  }
+  pub fn preview_offset(&mut self) -> usize {
+    let path = self.get_selected_path();
+    if self.preview_scroll.0 != path {
+      self.preview_scroll = (path, 0);
+    }
+    self.preview_scroll.1
+  }
+  fn replace_working_items(&mut self, items: Vec<ItemInfo>) {
+    let items = match &self.filter {
+      Some(filter) => filter.apply(items),
+      None => items,
+    };
+    self.items.replace_items(items);
+  }
+  fn reload_items(&mut self) -> anyhow::Result<()> {
+    self.loader.cancel();
+    let is_contents = self.is_contents_in_working_block();
+    let parent_path = Self::generate_parent_path(&self.wd);
+
+    let grandparent_items = Self::make_items(&self.grandparent_path, &self.read_options)?;
+    let gi = Self::generate_index(&grandparent_items, &parent_path);
+    self.grandparent_items = StatefulList::with_items_select(grandparent_items, gi);
+
+    let parent_items = Self::make_items(&parent_path, &self.read_options)?;
+    let pi = Self::generate_index(&parent_items, &self.wd);
+    self.parent_items = StatefulList::with_items_select(parent_items, pi);
+
+    if let Some(list) = &self.list {
+      // Entries deleted or renamed are dropped
+      let items = list.iter().filter(|item| item.get_path().is_some_and(|path| path.exists())).cloned().collect();
+      self.replace_working_items(items);
+    } else if !is_contents {
+      let items = Self::make_items(&self.wd, &self.read_options)?;
+      self.replace_working_items(items);
+    }
+    self.status.invalidate();
+    self.dir_sizes.invalidate();
+    self.update_search_effect()
+  }
+  pub fn preview_wait(&self) -> Option<Duration> {
+    self.preview_due.map(|due| due.saturating_duration_since(Instant::now()))
+  }
+  pub fn receive_preview(&mut self) -> anyhow::Result<()> {
+    if self.preview_due.is_some_and(|due| due <= Instant::now()) {
+      let now_i = match self.judge_mode() {
+        AppMode::Normal => self.get_current_index(),
+        AppMode::Search => self.get_search_index(),
+      };
+      self.update_child_items(now_i)?;
+    }
+    Ok(())
+  }
+  /// Read the working directory again when its entries have changed on disk, the selection stays on the same entry
+  pub fn receive_changes(&mut self) -> anyhow::Result<()> {
+    if self.is_contents_in_working_block() || self.list.is_some() {
+      return Ok(());
+    }
+    // Follows the working directory wherever it has moved
+    self.watcher.watch(&self.wd);
+    if self.watcher.changed() {
+      self.reload_items()?;
+    }
+    Ok(())
+  }
+  /// Put the directory read in the background into the block it belongs to, stale results are dropped
+  pub fn receive_items(&mut self, timeout: Duration) -> anyhow::Result<()> {
+    let (path, items, tree) = if let Some(loaded) = self.loader.receive(timeout) { loaded } else { return Ok(()) };
+    if path == self.wd {
+      let items = items?;
+      // Entered before it was read
+      if self.items.items.is_empty() {
+        if let Some(i) = self.remembered_index(&path, &items) {
+          self.items.state.select(Some(i));
+        }
+      }
+      self.replace_working_items(items);
+      return self.update_search_effect();
+    }
+    if self.get_selected_path().as_ref() == Some(&path) {
+      let items = items?;
+      // Output of preview commands has no selection
+      let ci = self.remembered_index(&path, &items).or(self.child_items.state.selected()).filter(|_| path.is_dir());
+      self.child_items = StatefulList::with_items_option(items, ci);
+      self.child_tree = tree.map(|tree| (path, tree));
+    }
+    Ok(())
+  }
+  /// Where the selection was when `dir` was left, the index is clamped if the entry has gone
+  fn remembered_index(&self, dir: &Path, items: &[ItemInfo]) -> Option<usize> {
+    let (path, i) = self.last_selected.get(dir)?;
+    let position = items.iter().position(|item| item.get_path().as_ref() == Some(path));
+    Some(position.unwrap_or_else(|| (*i).min(items.len().saturating_sub(1))))
+  }
+  /// File contents have no paths and are not remembered
+  fn remember_selection(&mut self) {
+    if let Some(path) =
+      self.items.state.selected().and_then(|i| self.items.items.get(i)).and_then(|item| item.get_path())
+    {
+      self.last_selected.insert(self.wd.clone(), (path, self.get_current_index()));
+    }
+  }
+  /// Repeat the last file operation on the marked or selected entries, deleting is asked again
+  pub fn repeat_operation(&mut self) -> anyhow::Result<()> {
+    match self.last_operation.clone() {
+      Some(Operation::CreateDir(name)) => self.create_dir(&name),
+      Some(Operation::Delete) => {
+        self.start_delete();
+        Ok(())
+      }
+      Some(Operation::Paste { to, .. }) if !to.is_dir() => {
+        self.messages.warn(format!("No longer exists: {}", self.config.display_path(&to)));
+        Ok(())
+      }
+      Some(Operation::Paste { to, cut }) => {
+        let paths = self.get_target_paths();
+        if paths.is_empty() {
+          return Ok(());
+        }
+        if self.paste_paths(&paths, &to, cut).is_some() && self.messages.latest().is_none() {
+          let verb = if cut { "Moved" } else { "Copied" };
+          self.messages.info(format!("{} {} to {}", verb, paths.len(), self.config.display_path(&to)));
+        }
+        self.marked.clear();
+        self.reload_items()
+      }
+      None => {
+        self.messages.warn("No operation to repeat");
+        Ok(())
+      }
+    }
+  }
+  pub fn toggle_split(&mut self) -> anyhow::Result<()> {
+    if self.split.take().is_some() {
+      self.is_right_pane = false;
+      return Ok(());
+    }
+    let mut other = App::with_config(self.config.clone(), Some(&self.wd), None, true, self.read_only)?;
+    other.is_right_pane = !self.is_right_pane;
+    self.split = Some(Box::new(other));
+    Ok(())
+  }
+  /// Focus the other pane, the register and the last operation go along,
+  /// so that entries yanked or cut in one pane can be pasted in the other
+  pub fn toggle_focus(&mut self) -> anyhow::Result<()> {
+    let mut other = if let Some(other) = self.split.take() { other } else { return Ok(()) };
+    mem::swap(self, &mut other);
+    mem::swap(&mut self.register, &mut other.register);
+    mem::swap(&mut self.last_operation, &mut other.last_operation);
+    mem::swap(&mut self.uncopied, &mut other.uncopied);
+    mem::swap(&mut self.bookmarks, &mut other.bookmarks);
+    self.split = Some(other);
+    // Files may have been pasted or deleted from the other pane
+    self.reload_items()
+  }
+  /// Select the next entry whose name starts with `c`, or with the letters typed just before and `c`
+  pub fn quick_select(&mut self, c: char) -> anyhow::Result<()> {
+    let (items, current) = match self.judge_mode() {
+      AppMode::Normal => (&self.items.items, self.get_current_index()),
+      AppMode::Search => (&self.search.list, self.get_search_index()),
+    };
+    match self.quick_select.find(c, items, current, self.case, Instant::now()) {
+      Some(i) => self.move_index(i),
+      None => {
+        self.messages.warn(format!("No entry starts with {}", self.quick_select.text()));
+        Ok(())
+      }
+    }
+  }
+  fn rename(&mut self, from: &Path, name: &str) -> anyhow::Result<()> {
+    if name.is_empty() || name.contains(std::path::is_separator) {
+      self.messages.warn(format!("Invalid name: {}", name));
+      return Ok(());
+    }
+    let to = from.with_file_name(name);
+    if to == from {
+      return Ok(());
+    }
+    if to.symlink_metadata().is_ok() {
+      self.messages.warn(format!("{} already exists", name));
+      return Ok(());
+    }
+    if let Err(e) = fs::rename(from, &to) {
+      self.messages.error(format!("Failed to rename: {}", e));
+      return Ok(());
+    }
+    self.reload_items()?;
+    self.select_path(&to)
+  }
+  pub fn save_bookmark(&mut self, key: char) {
+    self.bookmarks.insert(key, &self.wd);
+    match self.bookmarks.save() {
+      Ok(()) => self.messages.info(format!("Bookmark '{}' set: {}", key, self.config.display_path(&self.wd))),
+      Err(e) => self.messages.error(format!("Failed to save bookmarks: {}", e)),
+    }
+  }
+  /// The directory to start in and the file to select, symbolic links to files are selected as they are
+  fn resolve_start(start: &Path) -> Result<(PathBuf, Option<PathBuf>), String> {
+    let error = |e: io::Error| format!("Cannot start in {}: {}", start.display(), e);
+    let path = canonicalize(start).map_err(error)?;
+    if path.is_dir() {
+      return Ok((path, None));
+    }
+    let (dir, name) = match (start.parent(), start.file_name()) {
+      (Some(dir), Some(name)) => (if dir.as_os_str().is_empty() { Path::new(".") } else { dir }, name),
+      _ => return Err(format!("Cannot start in {}: not a directory", start.display())),
+    };
+    let dir = canonicalize(dir).map_err(error)?;
+    Ok((dir.clone(), Some(dir.join(name))))
+  }
+  /// Keep the file contents filling the child block of `height` rows, which may have grown since they were scrolled
+  pub fn clamp_preview_scroll(&mut self, height: u16) {
+    let max = self.child_items.items.len().saturating_sub(height as usize);
+    self.preview_scroll.1 = self.preview_offset().min(max);
+  }
+  pub fn scroll_preview(&mut self, delta: isize) {
+    if !matches!(self.child_items.items.first().map(|item| &item.item), Some(Item::Content(_))) {
+      return;
+    }
+    let max = self.child_items.items.len().saturating_sub(self.list_height);
+    self.preview_scroll.1 = self.preview_offset().saturating_add_signed(delta).min(max);
+  }
   pub fn search_sort_to_vec(&self) -> Vec<ItemInfo> {
+    if self.search.text.is_empty() {
+      return vec![];
+    }
+    // The whole path in the list
+    let base = self.list.as_ref().map(|_| self.wd.as_path());
     self
       .items
       .items
       .iter()
       .filter_map(|item| -> Option<ItemInfo> {
         if let Item::Content(s) = &item.item {
-          if s.contains(&self.search.text) {
+          if self.case.contains(s, &self.search.text) {
             Some(item.clone())
           } else {
             None
           }
-        } else if item.get_path()?.file_name()?.to_string_lossy().to_string().contains(&self.search.text) {
+        } else if self.case.contains(&item.generate_name(base)?, &self.search.text) {
           Some(item.clone())
         } else {
           None
@@ -364,66 +1164,796 @@ impl App {
       })
       .collect()
   }
+  /// Show `items` in the working block until another directory is entered, relative paths are under the working
+  /// directory
+  pub fn show_list(&mut self, items: Vec<ItemInfo>) -> anyhow::Result<()> {
+    self.loader.cancel();
+    self.list = Some(items.clone());
+    self.search = Search::new();
+    self.items = StatefulList::with_items_select(items, 0);
+    self.follow_selection(0)
+  }
+  pub fn select_path(&mut self, path: &Path) -> anyhow::Result<()> {
+    self.search = Search::new();
+    let i = Self::generate_index(&self.items.items, path);
+    self.items.select(i);
+    self.follow_selection(i)
+  }
+  fn set_filter(&mut self, text: &str) -> anyhow::Result<()> {
+    self.filter = if text.is_empty() {
+      None
+    } else {
+      match Filter::new(text, self.case) {
+        Ok(filter) => Some(filter),
+        Err(e) => {
+          self.messages.warn(format!("Invalid filter: {}", e));
+          return Ok(());
+        }
+      }
+    };
+    self.reload_items()
+  }
+  fn set_register(&mut self, kind: RegisterKind) {
+    if self.is_contents_in_working_block() {
+      return;
+    }
+    let paths = self.get_target_paths();
+    if !paths.is_empty() {
+      let register = Register::new(kind, paths);
+      self.messages.info(register.describe());
+      self.register = Some(register);
+      self.marked.clear();
+    }
+  }
+  pub fn start_create_dir(&mut self) {
+    if !self.deny_in_read_only("Creating a directory") {
+      self.start_input(InputKind::CreateDir);
+    }
+  }
+  pub fn start_delete(&mut self) {
+    if self.is_contents_in_working_block() || self.deny_in_read_only("Delete") {
+      return;
+    }
+    let paths = self.get_target_paths();
+    if !paths.is_empty() {
+      self.input = Some(Input::new(InputKind::Delete(paths)));
+    }
+  }
+  pub fn start_filter(&mut self) {
+    let text = self.filter.as_ref().map(|filter| filter.text.clone()).unwrap_or_default();
+    self.input = Some(Input::with_text(InputKind::Filter(self.case), text));
+  }
+  pub fn start_input(&mut self, kind: InputKind) {
+    self.input = Some(Input::new(kind));
+  }
+  pub fn start_jump(&mut self) {
+    let origin = match self.judge_mode() {
+      AppMode::Normal => self.get_current_index(),
+      AppMode::Search => self.get_search_index(),
+    };
+    self.jump = Some(Jump::new(origin));
+  }
+  pub fn start_path_input(&mut self, text: String) {
+    self.input = Some(Input::with_text(InputKind::Path, text));
+  }
+  pub fn start_rename(&mut self) {
+    let path = if let Some(path) = self.get_selected_path() { path } else { return };
+    if self.is_contents_in_working_block() || self.deny_in_read_only("Rename") {
+      return;
+    }
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    self.input = Some(Input::with_text(InputKind::Rename(path), name));
+  }
+  pub fn submit_input(&mut self) -> anyhow::Result<()> {
+    let mut input = if let Some(input) = self.input.take() { input } else { return Ok(()) };
+    if input.kind == InputKind::Path {
+      // The prompt stays until a directory is given
+      return match canonicalize(&self.wd.join(expand_home(input.text.trim()))) {
+        Ok(path) if path.is_dir() => self.move_to(path),
+        Ok(_) => {
+          input.error = Some("Not a directory".into());
+          self.input = Some(input);
+          Ok(())
+        }
+        Err(e) => {
+          input.error = Some(e.to_string());
+          self.input = Some(input);
+          Ok(())
+        }
+      };
+    }
+    match input.kind {
+      InputKind::CreateDir => self.create_dir(&input.text),
+      InputKind::Delete(paths) => {
+        let removal = self.removal();
+        self.delete(&paths, removal)
+      }
+      InputKind::DeletePermanently(paths) => self.delete(&paths, Removal::Permanent),
+      InputKind::Filter(_) => self.set_filter(&input.text),
+      // Handled where the app finishes
+      InputKind::Path | InputKind::Quit => Ok(()),
+      InputKind::Rename(from) => self.rename(&from, &input.text),
+    }
+  }
+  pub fn toggle_case(&mut self) -> anyhow::Result<()> {
+    self.case = self.case.next();
+    self.messages.info(format!("Case: {}", self.case.name()));
+    if let Some(filter) = &self.filter {
+      let text = filter.text.clone();
+      self.set_filter(&text)?;
+    }
+    self.update_jump_effect();
+    self.update_search_effect()
+  }
+  pub fn toggle_dirs_first(&mut self) -> anyhow::Result<()> {
+    self.read_options.dirs_first = !self.read_options.dirs_first;
+    self.messages.info(if self.read_options.dirs_first { "Directories first" } else { "Directories mixed" });
+    self.sort_all()
+  }
+  pub fn toggle_gitignore(&mut self) -> anyhow::Result<()> {
+    self.read_options.respect_gitignore = !self.read_options.respect_gitignore;
+    self.reload_items()
+  }
+  /// The preview is hidden while only directories are shown, and shown again after
+  pub fn toggle_dirs_only(&mut self) -> anyhow::Result<()> {
+    self.read_options.dirs_only = !self.read_options.dirs_only;
+    self.show_preview = !self.read_options.dirs_only;
+    self.messages.info(if self.read_options.dirs_only { "Directories only" } else { "All entries" });
+    self.reload_items()
+  }
+  pub fn cycle_category(&mut self) -> anyhow::Result<()> {
+    self.read_options.category = FileCategory::cycle(self.read_options.category);
+    self.messages.info(match self.read_options.category {
+      Some(category) => format!("Only {}", category.name()),
+      None => "All entries".into(),
+    });
+    self.reload_items()
+  }
+  pub fn show_only(&mut self, only: Only) -> anyhow::Result<()> {
+    match only.category() {
+      Some(category) => self.read_options.category = Some(category),
+      None => {
+        self.read_options.dirs_only = true;
+        self.show_preview = false;
+      }
+    }
+    self.reload_items()
+  }
+  pub fn toggle_hidden(&mut self) -> anyhow::Result<()> {
+    self.read_options.show_hidden = !self.read_options.show_hidden;
+    self.reload_items()
+  }
+  pub fn toggle_mark(&mut self) -> anyhow::Result<()> {
+    if self.is_contents_in_working_block() {
+      return Ok(());
+    }
+    let path = if let Some(path) = self.get_selected_path() { path } else { return Ok(()) };
+    if !self.marked.remove(&path) {
+      self.marked.insert(path);
+    }
+    self.move_next()
+  }
+  pub fn toggle_grandparent(&mut self) {
+    self.show_grandparent = !self.show_grandparent;
+  }
+  /// Whether the pane shows only the working block, as toggled or by its width when last rendered
+  pub fn is_compact(&self) -> bool {
+    self.compact.unwrap_or(self.areas.pane.width < self.config.compact_width())
+  }
+  pub fn toggle_compact(&mut self) {
+    self.compact = Some(!self.is_compact());
+  }
+  pub fn toggle_preview(&mut self) -> anyhow::Result<()> {
+    self.show_preview = !self.show_preview;
+    let now_i = match self.judge_mode() {
+      AppMode::Normal => self.get_current_index(),
+      AppMode::Search => self.get_search_index(),
+    };
+    self.update_child_items(now_i)
+  }
+  fn follow_selection(&mut self, index: usize) -> anyhow::Result<()> {
+    match self.config.preview_trigger() {
+      PreviewTrigger::Auto => self.update_child_items(index),
+      PreviewTrigger::Debounced | PreviewTrigger::Manual => self.defer_child_items(index),
+    }
+  }
+  /// Leave the child block empty until the `preview` command
+  fn skip_child_items(&mut self) {
+    self.loader.cancel();
+    self.preview_due = None;
+    self.preview_skipped = true;
+    self.child_items = StatefulList::with_items_option(vec![], None);
+  }
+  pub fn preview_now(&mut self) -> anyhow::Result<()> {
+    let now_i = match self.judge_mode() {
+      AppMode::Normal => self.get_current_index(),
+      AppMode::Search => self.get_search_index(),
+    };
+    self.update_child_items(now_i)
+  }
   fn update_child_items(&mut self, index: usize) -> anyhow::Result<()> {
-    if self.is_empty_in_working_block() {
+    self.preview_due = None;
+    self.preview_skipped = false;
+    if !self.show_preview {
       self.child_items = StatefulList::with_items_option(vec![], None);
       return Ok(());
     }
-
-    let ci = self.child_items.state.selected();
-
-    let items = match self.judge_mode() {
-      AppMode::Normal => self.get_items(),
-      AppMode::Search => self.get_search_list(),
-    };
-
-    self.child_items =
-      StatefulList::with_items_option(items.get(index).unwrap_or(&ItemInfo::default()).generate_child_items()?, ci);
-    if items[index].is_file() {
-      self.child_items.unselect();
+    self.load_child_items(index)
+  }
+  pub fn update_jump_effect(&mut self) {
+    let jump = if let Some(jump) = &self.jump { jump } else { return };
+    match self.judge_mode() {
+      AppMode::Normal => {
+        if let Some(i) = jump.find(&self.items.items, self.case) {
+          self.items.select(i);
+        }
+      }
+      AppMode::Search => {
+        if let Some(i) = jump.find(&self.search.list, self.case) {
+          self.search.select(i);
+        }
+      }
     }
-
-    Ok(())
   }
   pub fn update_search_effect(&mut self) -> anyhow::Result<()> {
     self.search.list = self.search_sort_to_vec();
+    if self.get_search_index() >= self.search.list.len() {
+      self.search.select(self.search.list.len().saturating_sub(1));
+    }
 
     let now_i = match self.judge_mode() {
       AppMode::Normal => self.get_current_index(),
       AppMode::Search => self.get_search_index(),
     };
 
-    self.update_child_items(now_i)?;
+    self.follow_selection(now_i)?;
 
     Ok(())
   }
+  pub fn yank(&mut self) {
+    self.set_register(RegisterKind::Copy);
+  }
 }
 
-pub fn app() -> anyhow::Result<Action> {
-  // setup terminal
-  enable_raw_mode()?;
-  let mut stdout = io::stdout();
-  execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-  let backend = CrosstermBackend::new(stdout);
-  let mut terminal = Terminal::new(backend)?;
-
-  let app = App::new()?;
-  let action = match super::run(&mut terminal, app) {
-    Ok(action) => action,
-    Err(e) => {
+pub fn app(
+  start: Option<&Path>,
+  select: Option<InitialSelection>,
+  ignore_last_dir: bool,
+  read_only: bool,
+  stdin: bool,
+  only: Option<Only>,
+) -> anyhow::Result<Action> {
+  // Before the terminal setup so that errors are readable
+  let mut app = App::new(start, select, ignore_last_dir || stdin, read_only)?;
+  if let Some(only) = only {
+    app.show_only(only)?;
+  }
+  if stdin {
+    let items = read_list(io::stdin().lock(), &app.wd)?;
+    if items.is_empty() {
+      anyhow::bail!("No paths were read from stdin");
+    }
+    app.show_list(items)?;
+  }
+  let is_restore_last_dir = app.config.is_restore_last_dir();
+
+  // Keep stdout clean when it is captured, e.g. `ed --json | jq`
+  let action =
+    if io::stdout().is_terminal() { run_terminal(io::stdout, &mut app)? } else { run_terminal(io::stderr, &mut app)? };
+  for path in &app.uncopied {
+    eprintln!("{}", path.display());
+  }
+
+  if let Action::Change(path) = &action {
+    if is_restore_last_dir {
+      if let Err(e) = LastDir::write(path) {
+        e.eprintln();
+      }
+    }
+  }
+
+  Ok(action)
+}
+
+/// Choose a directory starting in `start` or the current directory, the terminal is set up and restored here
+pub fn pick_directory(start: Option<PathBuf>, config: &Config) -> anyhow::Result<Action> {
+  let mut app = App::with_config(config.clone(), start.as_deref(), None, true, false)?;
+  run_terminal(io::stdout, &mut app)
+}
+
+/// Same as `pick_directory` in a terminal already in raw mode and the alternate screen, which are left as they are
+pub fn pick_directory_in<B: Backend + io::Write>(
+  terminal: &mut Terminal<B>,
+  start: Option<PathBuf>,
+  config: &Config,
+) -> anyhow::Result<Action> {
+  let mut app = App::with_config(config.clone(), start.as_deref(), None, true, false)?;
+  super::run(terminal, &mut app)
+}
+
+/// The terminal is drawn on the stream `writer` returns, which is opened again to restore it on a panic
+fn run_terminal<W: io::Write + 'static>(writer: fn() -> W, app: &mut App) -> anyhow::Result<Action> {
+  restore_on_panic(
+    move || {
+      let _ = disable_raw_mode();
+      let _ = execute!(writer(), LeaveAlternateScreen, DisableMouseCapture, crossterm::cursor::Show);
+    },
+    || {
+      // setup terminal
+      enable_raw_mode()?;
+      execute!(writer(), EnterAlternateScreen, EnableMouseCapture)?;
+      let backend = CrosstermBackend::new(writer());
+      let mut terminal = Terminal::new(backend)?;
+
+      let result = super::run(&mut terminal, app);
+
       // restore terminal
       disable_raw_mode()?;
       execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
       terminal.show_cursor()?;
 
-      bail!(e)
+      result
+    },
+  )
+}
+
+/// Call `restore` before the panic message while `f` runs, so that the message is readable and the shell usable
+fn restore_on_panic<T>(restore: impl Fn() + Send + Sync + 'static, f: impl FnOnce() -> T) -> T {
+  let previous: Arc<dyn Fn(&panic::PanicHookInfo) + Send + Sync> = panic::take_hook().into();
+  let hook = Arc::clone(&previous);
+  let id = thread::current().id();
+  panic::set_hook(Box::new(move |info| {
+    // Panics of the loader and watcher threads do not end the session
+    if thread::current().id() == id {
+      restore();
     }
-  };
+    hook(info);
+  }));
+  let result = panic::catch_unwind(panic::AssertUnwindSafe(f));
+  panic::set_hook(Box::new(move |info| previous(info)));
+  result.unwrap_or_else(|e| panic::resume_unwind(e))
+}
 
-  // restore terminal
-  disable_raw_mode()?;
-  execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
-  terminal.show_cursor()?;
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::util::{config, TestDir};
 
-  Ok(action)
+  #[test]
+  fn test_restore_on_panic() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static RESTORED: AtomicUsize = AtomicUsize::new(0);
+    let restore = || {
+      RESTORED.fetch_add(1, Ordering::SeqCst);
+    };
+    assert_eq!(restore_on_panic(restore, || 1), 1);
+    assert_eq!(RESTORED.load(Ordering::SeqCst), 0);
+    // Nor for a panic of another thread, e.g. of the tests running meanwhile
+    assert!(restore_on_panic(restore, || thread::spawn(|| panic!("forced elsewhere")).join().is_err()));
+    assert_eq!(RESTORED.load(Ordering::SeqCst), 0);
+    let result = panic::catch_unwind(|| restore_on_panic(restore, || panic!("forced")));
+    assert!(result.is_err());
+    assert_eq!(RESTORED.load(Ordering::SeqCst), 1);
+    // The previous hook is back
+    let _ = panic::catch_unwind(|| panic!("forced again"));
+    assert_eq!(RESTORED.load(Ordering::SeqCst), 1);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_move_at_root() {
+    let mut app = App::with_config(config(&[]), Some(Path::new("/")), None, true, false).unwrap();
+    assert_eq!(app.wd, Path::new("/"));
+    app.move_parent().unwrap();
+    assert_eq!(app.wd, Path::new("/"));
+    app.move_next().unwrap();
+    app.move_previous().unwrap();
+    app.move_page_down().unwrap();
+    app.move_page_up().unwrap();
+    app.move_index(0).unwrap();
+    app.move_parent().unwrap();
+    assert_eq!(app.wd, Path::new("/"));
+
+    // Back to the root from a directory under it
+    let dir = app.get_items().iter().filter_map(|item| item.get_path()).find(|path| path.is_dir()).unwrap();
+    app.move_to(&dir).unwrap();
+    app.move_parent().unwrap();
+    app.move_parent().unwrap();
+    assert_eq!(app.wd, Path::new("/"));
+    assert_eq!(app.grandparent_path, Path::new(""));
+  }
+
+  #[test]
+  fn test_move_home_dir() {
+    let dir = TestDir::new("test_move_home_dir");
+    fs::create_dir_all(dir.join("home")).unwrap();
+    let mut app = App::with_config(config(&[]), Some(&dir), None, true, false).unwrap();
+    app.home = Some(dir.join("home"));
+    app.move_home_dir().unwrap();
+    assert_eq!(app.wd, dir.join("home"));
+    let parent = app.get_parent_items()[app.parent_items.selected()].get_path();
+    assert_eq!(parent, Some(app.wd.clone()));
+
+    app.home = Some(dir.join("missing"));
+    app.move_home_dir().unwrap();
+    assert_eq!(app.wd, dir.join("home"));
+    assert_eq!(app.messages.text(), Some("The home directory is not found"));
+  }
+
+  #[test]
+  fn test_history() {
+    let dir = TestDir::new("test_history");
+    fs::create_dir_all(dir.join("a").join("b")).unwrap();
+    let mut app = App::with_config(config(&[]), Some(&dir), None, true, false).unwrap();
+    app.move_child().unwrap();
+    app.move_to(dir.join("a").join("b")).unwrap();
+    app.move_back().unwrap();
+    assert_eq!(app.wd, dir.join("a"));
+    app.move_back().unwrap();
+    assert_eq!(app.wd, *dir);
+    app.move_back().unwrap();
+    assert_eq!(app.messages.text(), Some("No previous directory"));
+    app.move_forward().unwrap();
+    app.move_forward().unwrap();
+    assert_eq!(app.wd, dir.join("a").join("b"));
+
+    app.move_back().unwrap();
+    app.move_parent().unwrap();
+    app.move_forward().unwrap();
+    assert_eq!(app.wd, *dir);
+    assert_eq!(app.messages.text(), Some("No next directory"));
+  }
+
+  #[test]
+  fn test_toggle_dirs_only() {
+    let dir = TestDir::new("test_dirs_only");
+    fs::create_dir_all(dir.join("a")).unwrap();
+    fs::create_dir_all(dir.join("b")).unwrap();
+    for file in ["c", "d", "e"] {
+      fs::write(dir.join(file), "").unwrap();
+    }
+    let mut app = App::with_config(config(&[]), Some(&dir), None, true, false).unwrap();
+    app.move_end().unwrap();
+    app.toggle_dirs_only().unwrap();
+    assert_eq!(app.items.items.len(), 2);
+    assert_eq!(app.get_selected_path(), Some(dir.join("b")));
+    assert!(!app.show_preview);
+
+    app.toggle_dirs_only().unwrap();
+    assert_eq!(app.items.items.len(), 5);
+    assert_eq!(app.get_selected_path(), Some(dir.join("b")));
+    assert!(app.show_preview);
+  }
+
+  #[test]
+  fn test_show_parent_entry() {
+    let dir = TestDir::new("test_show_parent_entry");
+    fs::create_dir_all(dir.join("a")).unwrap();
+    fs::write(dir.join("b"), "").unwrap();
+    let mut app = App::with_config(config(&[("_ED_SHOW_PARENT_ENTRY", "1")]), Some(&dir), None, true, false).unwrap();
+    assert!(app.items.items[0].is_parent());
+    assert_eq!(app.get_selected_path(), None);
+    assert_eq!(app.position(), None);
+
+    app.change_sort().unwrap();
+    assert!(app.items.items[0].is_parent());
+    app.set_filter("b").unwrap();
+    assert_eq!(app.items.items.len(), 2);
+    app.move_end().unwrap();
+    assert_eq!(app.position(), Some((1, 1)));
+    app.set_filter("").unwrap();
+    app.mark_all();
+    assert_eq!(app.marked.len(), 2);
+
+    app.move_home().unwrap();
+    app.move_child().unwrap();
+    assert_eq!(app.wd, dir.parent().unwrap());
+    assert_eq!(app.get_selected_path(), Some(dir.to_path_buf()));
+    assert!(app.get_child_items()[0].is_parent());
+  }
+
+  #[test]
+  fn test_preview_trigger() {
+    let dir = TestDir::new("test_preview_trigger");
+    fs::create_dir_all(dir.join("a/x")).unwrap();
+    fs::create_dir_all(dir.join("b/y")).unwrap();
+    let app = |trigger: &str| {
+      App::with_config(config(&[("_ED_PREVIEW_TRIGGER", trigger)]), Some(&dir), None, true, false).unwrap()
+    };
+    let child_paths = |app: &App| app.get_child_items().iter().filter_map(|item| item.get_path()).collect::<Vec<_>>();
+
+    let mut manual = app("manual");
+    assert!(manual.get_child_items().is_empty());
+    manual.preview_now().unwrap();
+    assert_eq!(child_paths(&manual), [dir.join("a/x")]);
+    manual.move_next().unwrap();
+    assert!(manual.get_child_items().is_empty());
+    assert_eq!(manual.preview_wait(), None);
+    manual.move_child().unwrap();
+    assert_eq!(manual.wd, dir.join("b"));
+    assert_eq!(manual.get_selected_path(), Some(dir.join("b/y")));
+    assert!(manual.get_child_items().is_empty());
+
+    let mut debounced = app("debounced");
+    assert!(debounced.preview_wait().is_some());
+    debounced.move_child().unwrap();
+    assert_eq!(debounced.wd, dir.join("a"));
+    assert!(debounced.preview_wait().is_some());
+    assert_eq!(child_paths(&app("auto")), [dir.join("a/x")]);
+  }
+
+  #[test]
+  fn test_category() {
+    let dir = TestDir::new("test_category");
+    fs::create_dir_all(dir.join("a")).unwrap();
+    for file in ["b.png", "c.rs", "d.jpg", ".e.png"] {
+      fs::write(dir.join(file), "").unwrap();
+    }
+    let mut app = App::with_config(config(&[]), Some(&dir), None, true, false).unwrap();
+    app.show_only(Only::Images).unwrap();
+    let names = |app: &App| app.items.items.iter().filter_map(|item| item.get_path()).collect::<Vec<_>>();
+    assert_eq!(names(&app), vec![dir.join("a"), dir.join("b.png"), dir.join("d.jpg")]);
+    app.toggle_hidden().unwrap();
+    assert_eq!(app.items.items.len(), 4);
+
+    app.cycle_category().unwrap();
+    assert_eq!(app.messages.text(), Some("Only audio"));
+    assert_eq!(names(&app), vec![dir.join("a")]);
+    app.cycle_category().unwrap();
+    app.cycle_category().unwrap();
+    app.cycle_category().unwrap();
+    assert_eq!(app.messages.text(), Some("All entries"));
+    assert_eq!(app.items.items.len(), 5);
+
+    app.show_only(Only::Dirs).unwrap();
+    assert_eq!(names(&app), vec![dir.join("a")]);
+    assert!(!app.show_preview);
+  }
+
+  #[test]
+  fn test_toggle_dirs_first() {
+    let dir = TestDir::new("test_dirs_first");
+    fs::create_dir_all(dir.join("b")).unwrap();
+    for file in ["a", "c"] {
+      fs::write(dir.join(file), "").unwrap();
+    }
+    let mut app = App::with_config(config(&[]), Some(&dir), None, true, false).unwrap();
+    app.move_end().unwrap();
+    app.toggle_dirs_first().unwrap();
+    let names = |app: &App| app.get_items().iter().filter_map(ItemInfo::generate_filename).collect::<Vec<_>>();
+    assert_eq!(names(&app), ["b", "a", "c"]);
+    assert_eq!(app.get_selected_path(), Some(dir.join("c")));
+    app.toggle_dirs_first().unwrap();
+    assert_eq!(names(&app), ["a", "b", "c"]);
+  }
+
+  #[test]
+  fn test_show_list() {
+    let dir = TestDir::new("test_show_list");
+    fs::create_dir_all(dir.join("src/app")).unwrap();
+    fs::write(dir.join("src/main.rs"), "").unwrap();
+    let mut app = App::with_config(config(&[]), Some(&dir), None, true, false).unwrap();
+    let items = read_list(io::Cursor::new("src/main.rs\nsrc/app\n"), &dir).unwrap();
+    app.show_list(items).unwrap();
+    assert_eq!(app.get_selected_path(), Some(dir.join("src/main.rs")));
+    assert_eq!(app.confirm_path(), dir.join("src"));
+
+    app.search.text = "app".into();
+    app.update_search_effect().unwrap();
+    assert_eq!(app.get_selected_path(), Some(dir.join("src/app")));
+    assert_eq!(app.confirm_path(), dir.join("src/app"));
+    app.move_child().unwrap();
+    assert!(app.list.is_none());
+    assert_eq!(app.wd, dir.join("src/app"));
+    assert_eq!(app.confirm_path(), dir.join("src/app"));
+
+    let items = read_list(io::Cursor::new("src/main.rs"), &dir).unwrap();
+    app.show_list(items).unwrap();
+    app.move_child().unwrap();
+    assert_eq!(app.wd, dir.join("src"));
+    assert_eq!(app.get_selected_path(), Some(dir.join("src/main.rs")));
+  }
+
+  #[test]
+  fn test_repeat_operation() {
+    let dir = TestDir::new("test_repeat_operation");
+    fs::create_dir_all(dir.join("archive")).unwrap();
+    for file in ["a", "b"] {
+      fs::write(dir.join(file), "").unwrap();
+    }
+    // Deleted for good rather than into the trash of the user
+    let mut app = App::with_config(config(&[("_ED_USE_TRASH", "0")]), Some(&dir), None, true, false).unwrap();
+    app.repeat_operation().unwrap();
+    assert_eq!(app.messages.text(), Some("No operation to repeat"));
+
+    app.select_path(&dir.join("a")).unwrap();
+    app.cut();
+    app.move_to(dir.join("archive")).unwrap();
+    app.paste().unwrap();
+    app.move_parent().unwrap();
+    app.select_path(&dir.join("b")).unwrap();
+    app.repeat_operation().unwrap();
+    assert!(dir.join("archive/a").exists() && dir.join("archive/b").exists() && !dir.join("b").exists());
+
+    app.start_input(InputKind::CreateDir);
+    app.input.as_mut().unwrap().text = "new".into();
+    app.submit_input().unwrap();
+    // Failing as it exists, so not repeated
+    app.start_input(InputKind::CreateDir);
+    app.input.as_mut().unwrap().text = "archive".into();
+    app.submit_input().unwrap();
+    app.move_to(dir.join("archive")).unwrap();
+    app.repeat_operation().unwrap();
+    assert!(dir.join("archive/new").is_dir());
+
+    app.start_delete();
+    app.submit_input().unwrap();
+    assert!(app.messages.text().is_some_and(|message| message.starts_with("Deleted ")));
+    app.repeat_operation().unwrap();
+    assert!(matches!(app.input.as_ref().map(|input| &input.kind), Some(InputKind::Delete(_))));
+  }
+
+  #[test]
+  fn test_split() {
+    let dir = TestDir::new("test_split");
+    fs::create_dir_all(dir.join("left")).unwrap();
+    fs::create_dir_all(dir.join("right")).unwrap();
+    fs::write(dir.join("left/a"), "").unwrap();
+    let mut app = App::with_config(config(&[]), Some(&dir.join("left")), None, true, false).unwrap();
+    app.toggle_focus().unwrap();
+    assert!(app.split.is_none());
+
+    app.toggle_split().unwrap();
+    app.toggle_focus().unwrap();
+    assert!(app.is_right_pane);
+    app.move_to(dir.join("right")).unwrap();
+    app.toggle_focus().unwrap();
+    assert_eq!((app.wd.as_path(), app.is_right_pane), (dir.join("left").as_path(), false));
+    assert_eq!(app.split.as_ref().unwrap().wd, dir.join("right"));
+
+    app.select_path(&dir.join("left/a")).unwrap();
+    app.yank();
+    app.toggle_focus().unwrap();
+    app.paste().unwrap();
+    assert!(dir.join("right/a").exists());
+    assert!(app.split.as_ref().unwrap().register.is_none());
+
+    app.toggle_split().unwrap();
+    assert_eq!((app.wd.as_path(), app.is_right_pane), (dir.join("right").as_path(), false));
+    assert!(app.split.is_none());
+  }
+
+  #[test]
+  fn test_selection_width() {
+    use tui::{backend::TestBackend, style::Color};
+
+    let dir = TestDir::new("test_selection_width");
+    for file in ["a", "b"] {
+      fs::write(dir.join(file), "").unwrap();
+    }
+    let mut app = App::with_config(config(&[]), Some(&dir), None, true, false).unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(80, 10)).unwrap();
+    terminal.draw(|f| super::super::ui(f, &mut app)).unwrap();
+    let area = app.areas.current;
+    let buffer = terminal.backend().buffer();
+    // Up to the border on the right
+    assert!((area.x..area.right() - 1).all(|x| buffer.get(x, area.y).bg == Color::DarkGray));
+    assert_ne!(buffer.get(area.x, area.y + 1).bg, Color::DarkGray);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_symlink_navigation() {
+    let test_dir = TestDir::new("test_symlink_navigation");
+    let dir = canonicalize(&test_dir).unwrap();
+    fs::create_dir_all(dir.join("real/sub")).unwrap();
+    fs::create_dir_all(dir.join("links")).unwrap();
+    std::os::unix::fs::symlink(dir.join("real/sub"), dir.join("links/link")).unwrap();
+    let app = |navigation: &str| {
+      App::with_config(config(&[("_ED_SYMLINK_NAVIGATION", navigation)]), Some(&dir.join("links")), None, true, false)
+        .unwrap()
+    };
+    let enter_and_leave = |mut app: App| {
+      app.select_path(&dir.join("links/link")).unwrap();
+      app.move_child().unwrap();
+      let wd = app.wd.clone();
+      app.move_parent().unwrap();
+      (wd, app.wd.clone(), app.get_selected_path().unwrap())
+    };
+    assert_eq!(enter_and_leave(app("logical")), (dir.join("links/link"), dir.join("links"), dir.join("links/link")));
+    assert_eq!(enter_and_leave(app("physical")), (dir.join("real/sub"), dir.join("real"), dir.join("real/sub")));
+
+    // Reached through the link some other way
+    let mut app = app("physical");
+    app.move_to(dir.join("links/link")).unwrap();
+    app.move_parent().unwrap();
+    assert_eq!(app.wd, dir.join("real"));
+  }
+
+  #[test]
+  fn test_resize() {
+    use tui::backend::TestBackend;
+
+    let dir = TestDir::new("test_resize");
+    for i in 0..30 {
+      fs::write(dir.join(format!("{:02}", i)), (0..40).map(|i| format!("{}\n", i)).collect::<String>()).unwrap();
+    }
+    let mut app = App::with_config(config(&[("_ED_PREVIEW_DELAY", "0")]), Some(&dir), None, true, false).unwrap();
+    let draw = |app: &mut App, height| {
+      let mut terminal = Terminal::new(TestBackend::new(100, height)).unwrap();
+      terminal.draw(|f| super::super::ui(f, app)).unwrap();
+    };
+    draw(&mut app, 40);
+    app.move_index(25).unwrap();
+    draw(&mut app, 40);
+    assert_eq!(app.items.offset, 0);
+
+    // Shrunk so that the selection would be below the block
+    draw(&mut app, 12);
+    let height = app.areas.current.height as usize;
+    assert!((app.items.offset..app.items.offset + height).contains(&25));
+
+    app.scroll_preview(100);
+    let scrolled = app.preview_offset();
+    assert_eq!(scrolled, 40 - height);
+    // Grown so that the scrolled contents would leave rows empty
+    draw(&mut app, 40);
+    let height = app.areas.child.unwrap().height as usize;
+    assert_eq!(app.preview_offset(), 40 - height);
+  }
+
+  #[test]
+  fn test_message_on_short_terminal() {
+    use tui::backend::TestBackend;
+
+    let dir = TestDir::new("test_message_on_short_terminal");
+    let mut app = App::with_config(config(&[]), Some(&dir), None, true, false).unwrap();
+    app.messages.info("Copied!");
+    let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+    terminal.draw(|f| super::super::ui(f, &mut app)).unwrap();
+    let text = terminal.backend().buffer().content().iter().map(|cell| cell.symbol.as_str()).collect::<String>();
+    assert!(text.contains("Copied!"));
+  }
+
+  #[test]
+  fn test_compact() {
+    use tui::backend::TestBackend;
+
+    let dir = TestDir::new("test_compact");
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    let mut app = App::with_config(config(&[]), Some(&dir), None, true, false).unwrap();
+    let draw = |app: &mut App, width| {
+      let mut terminal = Terminal::new(TestBackend::new(width, 10)).unwrap();
+      terminal.draw(|f| super::super::ui(f, app)).unwrap();
+    };
+    draw(&mut app, super::super::DEFAULT_COMPACT_WIDTH - 1);
+    assert!(app.is_compact());
+    assert_eq!((app.areas.parent.width, app.areas.current.width), (0, super::super::DEFAULT_COMPACT_WIDTH - 1));
+    assert!(app.areas.child.is_none());
+    draw(&mut app, super::super::DEFAULT_COMPACT_WIDTH);
+    assert!(!app.is_compact() && app.areas.parent.width > 0);
+
+    app.toggle_compact();
+    draw(&mut app, 100);
+    assert_eq!(app.areas.current.width, 100);
+    app.toggle_compact();
+    draw(&mut app, 40);
+    assert!(app.areas.parent.width > 0);
+  }
+
+  #[test]
+  fn test_initial_selection() {
+    let dir = TestDir::new("test_initial_selection");
+    fs::create_dir_all(dir.join("b")).unwrap();
+    for file in ["a", "c"] {
+      fs::write(dir.join(file), "").unwrap();
+    }
+    let selected = |select| App::with_config(config(&[]), Some(&dir), select, true, false).unwrap().get_selected_path();
+    assert_eq!(selected(None), Some(dir.join("a")));
+    assert_eq!(selected(Some(InitialSelection::FirstDir)), Some(dir.join("b")));
+    assert_eq!(selected(Some(InitialSelection::Last)), Some(dir.join("c")));
+    assert_eq!(selected(Some(InitialSelection::Name("c".into()))), Some(dir.join("c")));
+    assert_eq!(selected(Some(InitialSelection::Name("d".into()))), Some(dir.join("a")));
+  }
 }
@@ -0,0 +1,166 @@
+use tui::{
+  style::{Color, Modifier, Style},
+  text::Span,
+};
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+
+/// Pieces of text and the SGR parameters between them, other escape sequences are dropped
+fn segments(text: &str) -> Vec<(&str, Option<&str>)> {
+  let mut segments = vec![];
+  let mut rest = text;
+  while let Some(start) = rest.find(ESC) {
+    let (before, sequence) = rest.split_at(start);
+    let sequence = &sequence[1..];
+    let (sgr, after) = match sequence.chars().next() {
+      // CSI, the final byte is in `@`..=`~`
+      Some('[') => match sequence[1..].find(|c| ('@'..='~').contains(&c)) {
+        Some(end) if sequence.as_bytes()[end + 1] == b'm' => (Some(&sequence[1..end + 1]), &sequence[end + 2..]),
+        Some(end) => (None, &sequence[end + 2..]),
+        None => (None, ""),
+      },
+      // OSC, ended by BEL or ESC `\`
+      Some(']') => match sequence.find([BEL, ESC]) {
+        Some(end) if sequence[end..].starts_with(ESC) => (None, sequence[end + 1..].strip_prefix('\\').unwrap_or("")),
+        Some(end) => (None, &sequence[end + 1..]),
+        None => (None, ""),
+      },
+      // Intermediate bytes and the final byte, e.g. `(B`
+      Some(_) => match sequence.find(|c| !(' '..='/').contains(&c)) {
+        Some(end) => (None, &sequence[end + sequence[end..].chars().next().map_or(0, char::len_utf8)..]),
+        None => (None, ""),
+      },
+      None => (None, ""),
+    };
+    segments.push((before, sgr));
+    rest = after;
+  }
+  segments.push((rest, None));
+  segments
+}
+
+/// `text` without escape sequences
+pub fn strip(text: &str) -> String {
+  segments(text).into_iter().map(|(text, _)| text).collect()
+}
+
+/// `text` styled by its SGR sequences on top of `base`
+pub fn spans(text: &str, base: Style) -> Vec<Span<'static>> {
+  let mut style = base;
+  let mut spans = vec![];
+  for (text, sgr) in segments(text) {
+    if !text.is_empty() {
+      spans.push(Span::styled(text.to_string(), style));
+    }
+    if let Some(sgr) = sgr {
+      // Sequences that are not understood are ignored
+      style = apply_sgr(style, base, sgr).unwrap_or(style);
+    }
+  }
+  spans
+}
+
+/// SGR parameters such as `01;38;5;208`, `None` when one is not understood
+pub fn parse_sgr(codes: &str) -> Option<Style> {
+  apply_sgr(Style::default(), Style::default(), codes)
+}
+
+/// `codes` applied to `style`, `reset` is the style `0` goes back to
+fn apply_sgr(mut style: Style, reset: Style, codes: &str) -> Option<Style> {
+  let mut codes = codes.split(';').map(|code| if code.is_empty() { Some(0) } else { code.parse::<u8>().ok() });
+  while let Some(code) = codes.next() {
+    style = match code? {
+      0 => reset,
+      1 => style.add_modifier(Modifier::BOLD),
+      2 => style.add_modifier(Modifier::DIM),
+      3 => style.add_modifier(Modifier::ITALIC),
+      4 => style.add_modifier(Modifier::UNDERLINED),
+      5 => style.add_modifier(Modifier::SLOW_BLINK),
+      7 => style.add_modifier(Modifier::REVERSED),
+      9 => style.add_modifier(Modifier::CROSSED_OUT),
+      22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+      23 => style.remove_modifier(Modifier::ITALIC),
+      24 => style.remove_modifier(Modifier::UNDERLINED),
+      27 => style.remove_modifier(Modifier::REVERSED),
+      code @ 30..=37 => style.fg(ansi_color(code - 30)),
+      code @ 40..=47 => style.bg(ansi_color(code - 40)),
+      code @ 90..=97 => style.fg(ansi_color(code - 90 + 8)),
+      code @ 100..=107 => style.bg(ansi_color(code - 100 + 8)),
+      38 => style.fg(extended_color(&mut codes)?),
+      48 => style.bg(extended_color(&mut codes)?),
+      39 => style.fg(reset.fg.unwrap_or(Color::Reset)),
+      49 => style.bg(reset.bg.unwrap_or(Color::Reset)),
+      _ => return None,
+    };
+  }
+  Some(style)
+}
+
+/// The rest of `38;5;n` or `38;2;r;g;b`
+fn extended_color(codes: &mut impl Iterator<Item = Option<u8>>) -> Option<Color> {
+  let mut next = || codes.next().flatten();
+  match next()? {
+    5 => Some(Color::Indexed(next()?)),
+    2 => Some(Color::Rgb(next()?, next()?, next()?)),
+    _ => None,
+  }
+}
+
+/// One of the 16 colors of the terminal
+fn ansi_color(n: u8) -> Color {
+  match n {
+    0 => Color::Black,
+    1 => Color::Red,
+    2 => Color::Green,
+    3 => Color::Yellow,
+    4 => Color::Blue,
+    5 => Color::Magenta,
+    6 => Color::Cyan,
+    7 => Color::Gray,
+    8 => Color::DarkGray,
+    9 => Color::LightRed,
+    10 => Color::LightGreen,
+    11 => Color::LightYellow,
+    12 => Color::LightBlue,
+    13 => Color::LightMagenta,
+    14 => Color::LightCyan,
+    _ => Color::White,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_sgr() {
+    assert_eq!(parse_sgr("01;31"), Some(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)));
+    assert_eq!(parse_sgr("38;5;208"), Some(Style::default().fg(Color::Indexed(208))));
+    assert_eq!(
+      parse_sgr("38;2;255;128;0;48;5;0"),
+      Some(Style::default().fg(Color::Rgb(255, 128, 0)).bg(Color::Indexed(0)))
+    );
+    assert_eq!(parse_sgr("94"), Some(Style::default().fg(Color::LightBlue)));
+    assert_eq!(parse_sgr("38;5"), None);
+    assert_eq!(parse_sgr("a"), None);
+  }
+
+  #[test]
+  fn test_strip() {
+    assert_eq!(strip("\x1b[1;31mfn\x1b[0m main"), "fn main");
+    assert_eq!(strip("a\x1b[2Kb\x1b]0;title\x07c\x1b]8;;x\x1b\\d\x1b(Be"), "abcde");
+    assert_eq!(strip("plain"), "plain");
+    assert_eq!(strip("cut\x1b["), "cut");
+  }
+
+  #[test]
+  fn test_spans() {
+    let base = Style::default().fg(Color::Gray);
+    assert_eq!(
+      spans("\x1b[31mfn\x1b[m main\x1b[1m", base),
+      [Span::styled("fn", base.fg(Color::Red)), Span::styled(" main", base)]
+    );
+    assert_eq!(spans("a\x1b[99mb", base), [Span::styled("a", base), Span::styled("b", base)]);
+  }
+}
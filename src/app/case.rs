@@ -0,0 +1,79 @@
+use serde::Deserialize;
+
+/// How search, jump and filter compare letters
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseMode {
+  /// Ignore case unless the query contains an uppercase letter
+  #[default]
+  Smart,
+  Sensitive,
+  Insensitive,
+}
+
+impl CaseMode {
+  pub fn name(&self) -> &'static str {
+    match self {
+      CaseMode::Smart => "smart",
+      CaseMode::Sensitive => "sensitive",
+      CaseMode::Insensitive => "insensitive",
+    }
+  }
+  pub fn next(&self) -> Self {
+    match self {
+      CaseMode::Smart => CaseMode::Sensitive,
+      CaseMode::Sensitive => CaseMode::Insensitive,
+      CaseMode::Insensitive => CaseMode::Smart,
+    }
+  }
+  pub fn is_ignore_case(&self, query: &str) -> bool {
+    match self {
+      CaseMode::Smart => !query.chars().any(char::is_uppercase),
+      CaseMode::Sensitive => false,
+      CaseMode::Insensitive => true,
+    }
+  }
+  pub fn contains(&self, text: &str, query: &str) -> bool {
+    if self.is_ignore_case(query) {
+      text.to_lowercase().contains(&query.to_lowercase())
+    } else {
+      text.contains(query)
+    }
+  }
+  pub fn starts_with(&self, text: &str, query: &str) -> bool {
+    if self.is_ignore_case(query) {
+      text.to_lowercase().starts_with(&query.to_lowercase())
+    } else {
+      text.starts_with(query)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_contains() {
+    assert!(CaseMode::Smart.contains("README.md", "readme"));
+    assert!(!CaseMode::Smart.contains("readme.md", "README"));
+    assert!(!CaseMode::Sensitive.contains("README.md", "readme"));
+    assert!(CaseMode::Insensitive.contains("readme.md", "README"));
+  }
+
+  #[test]
+  fn test_starts_with() {
+    assert!(CaseMode::Smart.starts_with("README.md", "read"));
+    assert!(!CaseMode::Smart.starts_with("README.md", "me"));
+    assert!(!CaseMode::Sensitive.starts_with("README.md", "r"));
+  }
+
+  #[test]
+  fn test_next() {
+    let mut mode = CaseMode::Smart;
+    for _ in 0..3 {
+      mode = mode.next();
+    }
+    assert_eq!(mode, CaseMode::Smart);
+  }
+}
@@ -0,0 +1,23 @@
+use std::fmt;
+
+/// The system clipboard, opened on the first copy and kept open since the text is served by this process on X11
+#[derive(Default)]
+pub struct Clipboard(Option<arboard::Clipboard>);
+
+impl fmt::Debug for Clipboard {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_tuple("Clipboard").field(&self.0.is_some()).finish()
+  }
+}
+
+impl Clipboard {
+  /// Fails without a clipboard, e.g. over SSH without a display
+  pub fn copy(&mut self, text: &str) -> anyhow::Result<()> {
+    let clipboard = match &mut self.0 {
+      Some(clipboard) => clipboard,
+      None => self.0.insert(arboard::Clipboard::new()?),
+    };
+    clipboard.set_text(text)?;
+    Ok(())
+  }
+}
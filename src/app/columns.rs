@@ -0,0 +1,134 @@
+use std::fmt;
+
+use anyhow::{bail, Context};
+use tui::layout::Constraint;
+
+const DEFAULT_RATIOS: [u32; 4] = [2, 2, 3, 3];
+/// Ratios are scaled up to at least this total before resizing, so that a border can move by a single cell
+const RESIZE_TOTAL: u32 = 1000;
+/// Blocks are not made narrower than this by dragging a border
+pub const MIN_COLUMN_WIDTH: u16 = 4;
+
+/// Widths of the grandparent, parent, working and child blocks relative to each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnRatios([u32; 4]);
+
+impl Default for ColumnRatios {
+  fn default() -> Self {
+    ColumnRatios(DEFAULT_RATIOS)
+  }
+}
+
+impl ColumnRatios {
+  /// `spec` is like `1,1,4,3`
+  pub fn new(spec: Option<&str>) -> anyhow::Result<Self> {
+    let spec = if let Some(spec) = spec { spec } else { return Ok(Self::default()) };
+    let ratios = spec
+      .split(',')
+      .map(|n| n.trim().parse::<u32>().with_context(|| format!("Not a number: {}", n.trim())))
+      .collect::<anyhow::Result<Vec<_>>>()?;
+    let ratios: [u32; 4] =
+      ratios.try_into().map_err(|ratios: Vec<_>| anyhow::anyhow!("Expected 4 numbers, got {}", ratios.len()))?;
+    if ratios.contains(&0) {
+      bail!("Ratios must be positive: {}", spec);
+    }
+    Ok(ColumnRatios(ratios))
+  }
+  /// Only for the blocks shown, the working block takes the share of the child block while the preview is hidden
+  pub fn constraints(&self, show_grandparent: bool, show_preview: bool) -> Vec<Constraint> {
+    let [grandparent, parent, current, child] = self.0;
+    let mut ratios = vec![];
+    if show_grandparent {
+      ratios.push(grandparent);
+    }
+    ratios.push(parent);
+    if show_preview {
+      ratios.extend([current, child]);
+    } else {
+      ratios.push(current + child);
+    }
+    let total = ratios.iter().sum();
+    ratios.into_iter().map(|ratio| Constraint::Ratio(ratio, total)).collect()
+  }
+  /// Move the border after the `border`th block shown so that the block before it gets `left` of the `total` cells
+  /// of the two blocks, the other blocks keep their widths
+  pub fn resize(&mut self, show_grandparent: bool, show_preview: bool, border: usize, left: u16, total: u16) {
+    let blocks = [0, 1, 2, 3].into_iter().filter(|&i| (i != 0 || show_grandparent) && (i != 3 || show_preview));
+    let (l, r) = match blocks.skip(border).take(2).collect::<Vec<_>>()[..] {
+      [l, r] if total >= MIN_COLUMN_WIDTH * 2 => (l, r),
+      _ => return,
+    };
+    let left = left.clamp(MIN_COLUMN_WIDTH, total - MIN_COLUMN_WIDTH) as u64;
+    let scale = RESIZE_TOTAL.div_ceil(self.0.iter().sum());
+    let mut ratios = self.0.map(|ratio| ratio * scale);
+    // The working block also has the share of the hidden child block, which is kept
+    let hidden = if r == 2 && !show_preview { ratios[3] } else { 0 };
+    let pair = ratios[l] + ratios[r] + hidden;
+    let new_left = ((pair as u64 * left + total as u64 / 2) / total as u64) as u32;
+    ratios[l] = new_left.clamp(1, pair - hidden - 1);
+    ratios[r] = pair - hidden - ratios[l];
+    let divisor = ratios.into_iter().fold(0, gcd);
+    self.0 = ratios.map(|ratio| ratio / divisor);
+  }
+}
+
+/// In the format of `new`
+impl fmt::Display for ColumnRatios {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let [grandparent, parent, current, child] = self.0;
+    write!(f, "{},{},{},{}", grandparent, parent, current, child)
+  }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+  if b == 0 {
+    a
+  } else {
+    gcd(b, a % b)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_column_ratios() {
+    assert_eq!(ColumnRatios::new(None).unwrap(), ColumnRatios::default());
+    let ratios = ColumnRatios::new(Some("1, 1, 4, 2")).unwrap();
+    assert_eq!(
+      ratios.constraints(true, true),
+      [Constraint::Ratio(1, 8), Constraint::Ratio(1, 8), Constraint::Ratio(4, 8), Constraint::Ratio(2, 8)]
+    );
+    assert_eq!(
+      ratios.constraints(true, false),
+      [Constraint::Ratio(1, 8), Constraint::Ratio(1, 8), Constraint::Ratio(6, 8)]
+    );
+    assert_eq!(ratios.constraints(false, false), [Constraint::Ratio(1, 7), Constraint::Ratio(6, 7)]);
+
+    assert!(ColumnRatios::new(Some("1,1,4")).is_err());
+    assert!(ColumnRatios::new(Some("1,0,4,2")).is_err());
+    assert!(ColumnRatios::new(Some("1,a,4,2")).is_err());
+  }
+
+  #[test]
+  fn test_resize() {
+    let mut ratios = ColumnRatios::default();
+    ratios.resize(true, true, 1, 10, 20);
+    assert_eq!(ratios, ColumnRatios([4, 5, 5, 6]));
+    assert_eq!(ColumnRatios::new(Some(&ratios.to_string())).unwrap(), ratios);
+
+    // The border between the parent and the working block with the share of the hidden child block
+    let mut ratios = ColumnRatios::default();
+    ratios.resize(false, false, 0, 10, 20);
+    assert_eq!(ratios, ColumnRatios([2, 4, 1, 3]));
+
+    let mut ratios = ColumnRatios::default();
+    ratios.resize(true, true, 0, 0, 20);
+    assert_eq!(ratios, ColumnRatios([4, 16, 15, 15]));
+    ratios.resize(true, true, 3, 10, 20);
+    assert_eq!(ratios, ColumnRatios([4, 16, 15, 15]));
+    ratios.resize(true, true, 0, 3, 7);
+    assert_eq!(ratios, ColumnRatios([4, 16, 15, 15]));
+  }
+}
@@ -0,0 +1,144 @@
+use std::{
+  collections::HashMap,
+  fs,
+  path::{Path, PathBuf},
+  sync::mpsc::{self, Receiver, Sender},
+  thread,
+  time::{Duration, Instant, SystemTime},
+};
+
+use super::walk;
+
+/// Interval of the progress sent while walking
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Total {
+  pub bytes: u64,
+  pub files: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirSize {
+  /// Counted so far
+  Walking(Total),
+  /// `at` is when the walk started
+  Done { total: Total, at: SystemTime },
+}
+
+/// Id of the walk, the path, what has been counted, and when it started once it has finished
+type Report = (u64, PathBuf, Total, Option<SystemTime>);
+
+/// Recursive sizes of directories, walked on background threads and cached by path
+#[derive(Debug)]
+pub struct DirSizes {
+  sender: Sender<Report>,
+  receiver: Receiver<Report>,
+  sizes: HashMap<PathBuf, (u64, DirSize)>,
+  next_id: u64,
+  /// Symbolic links to directories are walked into, each directory once
+  follow_symlinks: bool,
+}
+
+impl Default for DirSizes {
+  fn default() -> Self {
+    Self::new(false)
+  }
+}
+
+impl DirSizes {
+  pub fn new(follow_symlinks: bool) -> Self {
+    let (sender, receiver) = mpsc::channel();
+    DirSizes { sender, receiver, sizes: HashMap::new(), next_id: 0, follow_symlinks }
+  }
+  /// Walked again only when the directory has been modified since the cached result
+  pub fn request(&mut self, path: PathBuf) {
+    match self.get(&path) {
+      Some(DirSize::Walking(_)) => return,
+      Some(DirSize::Done { at, .. }) if fs::metadata(&path).and_then(|m| m.modified()).is_ok_and(|m| m <= at) => return,
+      _ => {}
+    }
+    let id = self.next_id;
+    self.next_id += 1;
+    self.sizes.insert(path.clone(), (id, DirSize::Walking(Total::default())));
+    let sender = self.sender.clone();
+    let at = SystemTime::now();
+    let follow_symlinks = self.follow_symlinks;
+    thread::spawn(move || {
+      let mut reported = Instant::now();
+      let total = count(&path, follow_symlinks, |total| {
+        if reported.elapsed() >= REPORT_INTERVAL {
+          reported = Instant::now();
+          let _ = sender.send((id, path.clone(), *total, None));
+        }
+      });
+      // The receiver is gone when the app has finished
+      let _ = sender.send((id, path, total, Some(at)));
+    });
+  }
+  /// Take the reports that have arrived without waiting
+  pub fn receive(&mut self) {
+    while let Ok((id, path, total, at)) = self.receiver.try_recv() {
+      // Reports of walks dropped by `invalidate` are ignored
+      if let Some((_, size)) = self.sizes.get_mut(&path).filter(|(current, _)| *current == id) {
+        *size = match at {
+          Some(at) => DirSize::Done { total, at },
+          None => DirSize::Walking(total),
+        };
+      }
+    }
+  }
+  pub fn get(&self, path: &Path) -> Option<DirSize> {
+    self.sizes.get(path).map(|(_, size)| *size)
+  }
+  pub fn is_walking(&self) -> bool {
+    self.sizes.values().any(|(_, size)| matches!(size, DirSize::Walking(_)))
+  }
+  pub fn invalidate(&mut self) {
+    self.sizes.clear();
+  }
+}
+
+/// Files and symbolic links that are not followed are counted, entries that cannot be read are skipped
+fn count(dir: &Path, follow_symlinks: bool, mut report: impl FnMut(&Total)) -> Total {
+  let mut total = Total::default();
+  walk(dir, follow_symlinks, |_, metadata| {
+    total.files += 1;
+    total.bytes += metadata.len();
+    report(&total);
+  });
+  total
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::util::TestDir;
+
+  #[test]
+  fn test_walk() {
+    let dir = TestDir::new("test_dir_size");
+    fs::create_dir_all(dir.join("a/b")).unwrap();
+    fs::write(dir.join("x"), [0; 10]).unwrap();
+    fs::write(dir.join("a/b/y"), [0; 20]).unwrap();
+    assert_eq!(count(&dir, false, |_| {}), Total { bytes: 30, files: 2 });
+    assert_eq!(count(&dir.join("not_exist"), false, |_| {}), Total::default());
+    #[cfg(unix)]
+    {
+      let _ = std::os::unix::fs::symlink(&dir, dir.join("a/loop"));
+      assert_eq!(count(&dir, true, |_| {}), Total { bytes: 30, files: 2 });
+      fs::remove_file(dir.join("a/loop")).unwrap();
+    }
+
+    let mut sizes = DirSizes::default();
+    sizes.request(dir.to_path_buf());
+    assert!(sizes.is_walking());
+    let started = Instant::now();
+    while sizes.is_walking() && started.elapsed() < Duration::from_secs(5) {
+      sizes.receive();
+    }
+    assert!(matches!(sizes.get(&dir), Some(DirSize::Done { total: Total { bytes: 30, files: 2 }, .. })));
+    sizes.invalidate();
+    assert_eq!(sizes.get(&dir), None);
+  }
+}
@@ -0,0 +1,42 @@
+use std::env;
+
+const FALLBACKS: [&str; 2] = ["vi", "nano"];
+
+/// `$EDITOR`, `$VISUAL`, or the first fallback found in `PATH`, split into the program and its arguments
+pub fn command() -> Option<Vec<String>> {
+  for name in ["EDITOR", "VISUAL"] {
+    if let Some(command) = env::var(name).ok().map(|value| split(&value)).filter(|command| !command.is_empty()) {
+      return Some(command);
+    }
+  }
+  FALLBACKS.into_iter().find(|name| in_path(name)).map(|name| vec![name.into()])
+}
+
+/// e.g. `code --wait`
+fn split(value: &str) -> Vec<String> {
+  value.split_whitespace().map(String::from).collect()
+}
+
+/// Whether `name` is an executable in a directory of `PATH`
+pub fn in_path(name: &str) -> bool {
+  let paths = if let Some(paths) = env::var_os("PATH") { paths } else { return false };
+  env::split_paths(&paths).any(|dir| dir.join(name).is_file() || dir.join(format!("{}.exe", name)).is_file())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_split() {
+    assert_eq!(split("code --wait"), ["code", "--wait"]);
+    assert!(split("  ").is_empty());
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_in_path() {
+    assert!(in_path("sh"));
+    assert!(!in_path("_easychangedirectory_not_exist"));
+  }
+}
@@ -0,0 +1,208 @@
+use std::{collections::HashMap, path::Path};
+
+use tui::style::{Color, Modifier, Style};
+
+use super::ansi::parse_sgr;
+
+const ARCHIVE: Color = Color::Red;
+const IMAGE: Color = Color::Magenta;
+const AUDIO: Color = Color::Cyan;
+const CODE: Color = Color::Yellow;
+const EXECUTABLE: Color = Color::Green;
+
+/// Types of files told by the extension, colored when `LS_COLORS` is not set and shown alone with `--only` and `T`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileCategory {
+  Archive,
+  Image,
+  Audio,
+  Code,
+  /// Also files with the executable permission
+  Executable,
+}
+
+impl FileCategory {
+  const ALL: [FileCategory; 5] =
+    [FileCategory::Archive, FileCategory::Image, FileCategory::Audio, FileCategory::Code, FileCategory::Executable];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      FileCategory::Archive => "archives",
+      FileCategory::Image => "images",
+      FileCategory::Audio => "audio",
+      FileCategory::Code => "code",
+      FileCategory::Executable => "executables",
+    }
+  }
+  fn color(&self) -> Color {
+    match self {
+      FileCategory::Archive => ARCHIVE,
+      FileCategory::Image => IMAGE,
+      FileCategory::Audio => AUDIO,
+      FileCategory::Code => CODE,
+      FileCategory::Executable => EXECUTABLE,
+    }
+  }
+  /// Lowercase
+  fn extensions(&self) -> &'static [&'static str] {
+    match self {
+      FileCategory::Archive => {
+        &["7z", "bz2", "deb", "gz", "jar", "rar", "rpm", "tar", "tbz2", "tgz", "txz", "xz", "zip", "zst"]
+      }
+      FileCategory::Image => &["bmp", "gif", "ico", "jpeg", "jpg", "png", "svg", "tif", "tiff", "webp"],
+      FileCategory::Audio => &["aac", "flac", "m4a", "mid", "mp3", "ogg", "opus", "wav", "wma"],
+      FileCategory::Code => &[
+        "c", "cc", "cpp", "cs", "css", "go", "h", "hpp", "html", "java", "js", "jsx", "kt", "lua", "php", "py", "rb",
+        "rs", "scala", "sh", "swift", "ts", "tsx", "zig",
+      ],
+      FileCategory::Executable => &["bat", "cmd", "com", "exe", "msi"],
+    }
+  }
+  pub fn contains(&self, path: &Path) -> bool {
+    if *self == FileCategory::Executable && is_executable(path) {
+      return true;
+    }
+    let ext = path.extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default();
+    self.extensions().contains(&ext.as_str())
+  }
+  /// The next category, none after the last one and the first one after none
+  pub fn cycle(category: Option<Self>) -> Option<Self> {
+    match category {
+      Some(category) => Self::ALL.into_iter().skip_while(|c| *c != category).nth(1),
+      None => Some(Self::ALL[0]),
+    }
+  }
+}
+
+/// What `--only` shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Only {
+  Dirs,
+  Archives,
+  Images,
+  Audio,
+  Code,
+  Executables,
+}
+
+impl Only {
+  /// `None` for directories, which are shown with `dirs_only`
+  pub fn category(&self) -> Option<FileCategory> {
+    match self {
+      Only::Dirs => None,
+      Only::Archives => Some(FileCategory::Archive),
+      Only::Images => Some(FileCategory::Image),
+      Only::Audio => Some(FileCategory::Audio),
+      Only::Code => Some(FileCategory::Code),
+      Only::Executables => Some(FileCategory::Executable),
+    }
+  }
+}
+
+/// Styles of files by their type, patched onto the style of files of the theme
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileColors {
+  /// By lowercase extension
+  extensions: HashMap<String, Style>,
+  /// Files with the executable permission
+  executable: Option<Style>,
+}
+
+impl Default for FileColors {
+  fn default() -> Self {
+    let extensions = FileCategory::ALL
+      .iter()
+      .flat_map(|category| {
+        category.extensions().iter().map(|ext| (ext.to_string(), Style::default().fg(category.color())))
+      })
+      .collect();
+    FileColors { extensions, executable: Some(Style::default().fg(EXECUTABLE).add_modifier(Modifier::BOLD)) }
+  }
+}
+
+impl FileColors {
+  /// From `ls_colors` in the format of `LS_COLORS`, e.g. `ex=01;32:*.tar=01;31`, the built-in palette when `None`
+  pub fn new(ls_colors: Option<&str>) -> Self {
+    let ls_colors =
+      if let Some(ls_colors) = ls_colors.filter(|s| !s.is_empty()) { ls_colors } else { return Self::default() };
+    let mut colors = FileColors { extensions: HashMap::new(), executable: None };
+    // Entries that cannot be read are skipped as `ls` does
+    for (key, codes) in ls_colors.split(':').filter_map(|entry| entry.split_once('=')) {
+      let style = if let Some(style) = parse_sgr(codes) { style } else { continue };
+      if let Some(ext) = key.strip_prefix("*.") {
+        colors.extensions.insert(ext.to_lowercase(), style);
+      } else if key == "ex" {
+        colors.executable = Some(style);
+      }
+    }
+    colors
+  }
+
+  /// `None` when the file has no particular type, executables take precedence over the extension
+  pub fn style(&self, path: &Path) -> Option<Style> {
+    if let Some(style) = self.executable.filter(|_| is_executable(path)) {
+      return Some(style);
+    }
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    self.extensions.get(&ext).copied()
+  }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+  use std::os::unix::fs::PermissionsExt;
+  path.metadata().is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+/// Executables are told by the extension on Windows
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+  false
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::util::TestDir;
+
+  #[test]
+  fn test_file_colors() {
+    let colors = FileColors::new(None);
+    assert_eq!(colors.style(Path::new("a.TAR")), Some(Style::default().fg(ARCHIVE)));
+    assert_eq!(colors.style(Path::new("a.png")), Some(Style::default().fg(IMAGE)));
+    assert_eq!(colors.style(Path::new("a.txt")), None);
+    assert_eq!(colors.style(Path::new("Makefile")), None);
+
+    let colors = FileColors::new(Some("rs=0:di=01;34:*.rs=38;5;208:*.bad=xx:ex=01;32"));
+    assert_eq!(colors.style(Path::new("main.rs")), Some(Style::default().fg(Color::Indexed(208))));
+    assert_eq!(colors.style(Path::new("a.tar")), None);
+    assert_eq!(colors.style(Path::new("a.bad")), None);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_executable() {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    let dir = TestDir::new("test_file_colors");
+    let path = dir.join("run.sh");
+    fs::write(&path, "").unwrap();
+    let colors = FileColors::default();
+    assert_eq!(colors.style(&path), Some(Style::default().fg(CODE)));
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    assert_eq!(colors.style(&path), Some(Style::default().fg(EXECUTABLE).add_modifier(Modifier::BOLD)));
+    assert!(!is_executable(&dir));
+    assert!(FileCategory::Executable.contains(&path));
+  }
+
+  #[test]
+  fn test_file_category() {
+    assert!(FileCategory::Image.contains(Path::new("a.JPG")));
+    assert!(!FileCategory::Image.contains(Path::new("a.rs")));
+    assert!(!FileCategory::Code.contains(Path::new("rs")));
+    assert_eq!(FileCategory::cycle(None), Some(FileCategory::Archive));
+    assert_eq!(FileCategory::cycle(Some(FileCategory::Image)), Some(FileCategory::Audio));
+    assert_eq!(FileCategory::cycle(Some(FileCategory::Executable)), None);
+    assert_eq!(Only::Images.category(), Some(FileCategory::Image));
+  }
+}
@@ -0,0 +1,140 @@
+use std::{
+  fs, io,
+  path::{Path, PathBuf},
+};
+
+/// A file operation that `R` repeats on the current selection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+  /// A directory of this name in the working directory
+  CreateDir(String),
+  /// The marked or selected entries, asked again
+  Delete,
+  /// The marked or selected entries copied or moved into `to`
+  Paste { to: PathBuf, cut: bool },
+}
+
+/// Where `delete` puts entries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Removal {
+  /// Into `_ED_TRASH_DIR`
+  MoveInto(PathBuf),
+  /// Into the trash or recycle bin of the system
+  Trash,
+  Permanent,
+}
+
+impl Removal {
+  /// Shown once entries are removed
+  pub fn verb(&self) -> &'static str {
+    match self {
+      Removal::MoveInto(_) | Removal::Trash => "Trashed",
+      Removal::Permanent => "Deleted",
+    }
+  }
+}
+
+/// Copy `from` to `to`, recursively for directories
+pub fn copy(from: &Path, to: &Path) -> io::Result<()> {
+  if from.is_dir() && !from.is_symlink() {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+      let entry = entry?;
+      copy(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+  } else {
+    fs::copy(from, to).map(|_| ())
+  }
+}
+
+/// Remove `path` as `removal` says
+pub fn delete(path: &Path, removal: &Removal) -> io::Result<()> {
+  match removal {
+    Removal::MoveInto(trash_dir) => {
+      fs::create_dir_all(trash_dir)?;
+      fs::rename(path, unique_path(trash_dir, path.file_name().unwrap_or_default()))
+    }
+    Removal::Trash => trash::delete(path).map_err(io::Error::other),
+    Removal::Permanent if path.is_dir() && !path.is_symlink() => fs::remove_dir_all(path),
+    Removal::Permanent => fs::remove_file(path),
+  }
+}
+
+/// Copy or move `from` into `dir` without overwriting, returning the new path
+pub fn paste(from: &Path, dir: &Path, cut: bool) -> io::Result<PathBuf> {
+  if dir.starts_with(from) {
+    return Err(io::Error::new(io::ErrorKind::InvalidInput, "cannot paste a directory into itself"));
+  }
+  if cut && from.parent() == Some(dir) {
+    return Ok(from.into());
+  }
+  let to = unique_path(dir, from.file_name().unwrap_or_default());
+  if !cut {
+    copy(from, &to)?;
+    return Ok(to);
+  }
+  match fs::rename(from, &to) {
+    // e.g. another filesystem
+    Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+      copy(from, &to)?;
+      delete(from, &Removal::Permanent)?;
+    }
+    result => result?,
+  }
+  Ok(to)
+}
+
+/// `dir/name`, or `dir/name (1).ext` and so on if it already exists
+pub fn unique_path(dir: &Path, name: &std::ffi::OsStr) -> PathBuf {
+  let path = dir.join(name);
+  if path.symlink_metadata().is_err() {
+    return path;
+  }
+  let name = Path::new(name);
+  let stem = name.file_stem().unwrap_or_default().to_string_lossy();
+  let ext = name.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+  (1..).map(|i| dir.join(format!("{} ({}){}", stem, i, ext))).find(|path| path.symlink_metadata().is_err()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::util::TestDir;
+
+  #[test]
+  fn test_unique_path() {
+    let dir = TestDir::new("test_unique_path");
+    fs::write(dir.join("file.txt"), "").unwrap();
+    fs::write(dir.join("file (1).txt"), "").unwrap();
+    assert_eq!(unique_path(&dir, "other.txt".as_ref()), dir.join("other.txt"));
+    assert_eq!(unique_path(&dir, "file.txt".as_ref()), dir.join("file (2).txt"));
+  }
+
+  #[test]
+  fn test_paste() {
+    let dir = TestDir::new("test_paste");
+    fs::create_dir_all(dir.join("sub").join("deep")).unwrap();
+    fs::write(dir.join("sub").join("deep").join("file"), "a").unwrap();
+    fs::write(dir.join("file"), "b").unwrap();
+    let copied = paste(&dir.join("sub"), &dir, false).unwrap();
+    assert_eq!(copied, dir.join("sub (1)"));
+    assert_eq!(fs::read_to_string(copied.join("deep").join("file")).unwrap(), "a");
+    let moved = paste(&dir.join("file"), &dir.join("sub"), true).unwrap();
+    assert_eq!(moved, dir.join("sub").join("file"));
+    assert!(!dir.join("file").exists());
+    assert!(paste(&dir.join("sub"), &dir.join("sub").join("deep"), false).is_err());
+  }
+
+  #[test]
+  fn test_delete() {
+    let dir = TestDir::new("test_delete");
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    fs::write(dir.join("sub").join("file"), "").unwrap();
+    fs::write(dir.join("file"), "").unwrap();
+    delete(&dir.join("file"), &Removal::MoveInto(dir.join("trash"))).unwrap();
+    assert!(dir.join("trash").join("file").exists());
+    delete(&dir.join("sub"), &Removal::Permanent).unwrap();
+    assert!(!dir.join("sub").exists());
+  }
+}
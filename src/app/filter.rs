@@ -0,0 +1,47 @@
+use globset::{GlobBuilder, GlobMatcher};
+
+use super::{CaseMode, ItemInfo};
+
+/// Glob the names in the working block are narrowed down to
+#[derive(Debug, Clone)]
+pub struct Filter {
+  pub text: String,
+  matcher: GlobMatcher,
+}
+
+impl Filter {
+  pub fn new(text: &str, case: CaseMode) -> anyhow::Result<Self> {
+    let glob = GlobBuilder::new(text).case_insensitive(case.is_ignore_case(text)).build()?;
+    Ok(Filter { text: text.into(), matcher: glob.compile_matcher() })
+  }
+  /// File contents and `..` are always kept
+  pub fn is_match(&self, item: &ItemInfo) -> bool {
+    item.get_path().is_none() || item.generate_filename().is_some_and(|name| self.matcher.is_match(name))
+  }
+  /// Renumber `index` like sorting does
+  pub fn apply(&self, items: Vec<ItemInfo>) -> Vec<ItemInfo> {
+    let mut items = items.into_iter().filter(|item| self.is_match(item)).collect::<Vec<_>>();
+    items.iter_mut().filter(|item| !item.is_parent()).enumerate().for_each(|(i, item)| item.index = Some(i));
+    items
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::{Item, ItemPath};
+
+  #[test]
+  fn test_apply() {
+    let path = |s: &str| ItemInfo { item: Item::Path(ItemPath::File(s.into())), index: None };
+    let filter = Filter::new("*.RS", CaseMode::Insensitive).unwrap();
+    let items = filter.apply(vec![path("/tmp/main.rs"), path("/tmp/README.md"), path("/tmp/lib.rs")]);
+    assert_eq!(items.iter().filter_map(|item| item.generate_filename()).collect::<Vec<_>>(), ["main.rs", "lib.rs"]);
+    assert_eq!(items[1].index, Some(1));
+    assert!(filter.is_match(&ItemInfo { item: Item::Content("fn main() {}".into()), index: None }));
+    let items = filter.apply(vec![ItemInfo { item: Item::Parent, index: None }, path("/tmp/lib.rs")]);
+    assert_eq!(items.iter().map(|item| item.index).collect::<Vec<_>>(), [None, Some(0)]);
+    assert!(!Filter::new("*.RS", CaseMode::Smart).unwrap().is_match(&path("/tmp/main.rs")));
+    assert!(Filter::new("[a", CaseMode::Smart).is_err());
+  }
+}
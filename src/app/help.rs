@@ -0,0 +1,143 @@
+use super::{Command, KeyMap};
+
+/// Keys of normal mode that cannot be rebound, each as its alternatives, the one table of the help, `--help` and the README
+const FIXED_KEYS: [(&[&str], &str); 51] = [
+  (&["Ctrl+c"], "Exit and return to original directory"),
+  (&["Home"], "Move to top"),
+  (&["End"], "Move to bottom"),
+  (&["PageUp"], "Move up one page"),
+  (&["PageDown"], "Move down one page"),
+  (&["g g"], "Move to top"),
+  (&["G"], "Move to bottom"),
+  (&["number + G"], "Move to the entry with that number"),
+  (&["Insert", "Ctrl+s"], "Search mode switch (Char key will not work)"),
+  (&["Backspace"], "Delete one character from the search string"),
+  (&["Delete"], "Delete all search strings"),
+  (&["/"], "Jump to the first entry matching the input"),
+  (&["f"], "Filter entries by a glob, empty to clear"),
+  (&[":"], "Go to a path, Tab completes directories"),
+  (&["~"], "Go to a path under the home directory"),
+  (&["H"], "Move to the home directory"),
+  (&["-"], "Back to the previous directory"),
+  (&["Tab"], "Forward to the next directory"),
+  (&["C"], "Change the case sensitivity"),
+  (&["."], "Show/hide hidden files"),
+  (&["i"], "Show/hide files ignored by git"),
+  (&["D"], "Show only directories/all entries"),
+  (&["T"], "Show only a type of files, in turn"),
+  (&["z"], "Show/hide the preview"),
+  (&["Z"], "Show/hide the grandparent directory"),
+  (&["|"], "Show only the current directory or all"),
+  (&["J", "K"], "Scroll the preview down/up"),
+  (&["Ctrl+d", "Ctrl+u"], "Scroll the preview down/up half a page"),
+  (&["S"], "Change the sort order"),
+  (&["F"], "Group/mix directories and files"),
+  (&["s"], "Count the total size of the directory"),
+  (&["Space"], "Mark/unmark the selected entry"),
+  (&["Ctrl+a"], "Mark all entries"),
+  (&["v"], "Invert the marks"),
+  (&["a"], "Create a directory"),
+  (&["r"], "Rename the selected entry"),
+  (&["d"], "Delete the marked or selected entries"),
+  (&["y"], "Yank the marked or selected entries"),
+  (&["Y"], "Copy the selected path to the clipboard"),
+  (&["x"], "Cut the marked or selected entries"),
+  (&["P"], "Paste yanked or cut entries here"),
+  (&["R"], "Repeat the last file operation on selection"),
+  (&["W"], "Split into two panes, or close the other"),
+  (&["w"], "Focus the other pane"),
+  (&["m + char"], "Bookmark the current directory"),
+  (&["' + char"], "Move to the bookmarked directory"),
+  (&["e"], "Open the selected file in $EDITOR"),
+  (&["o"], "Open the selected entry in the default app"),
+  (&["O"], "Show the selected entry in the file manager"),
+  (&["V"], "Open vscode"),
+  (&["Other letters"], "Select the next entry starting with them"),
+];
+
+/// Keys and their descriptions, those of commands as bound in `keymap`, then the fixed ones not taken by a command
+pub fn rows(keymap: &KeyMap) -> Vec<(String, &'static str)> {
+  Command::ALL
+    .into_iter()
+    .map(|command| (keymap.keys(command).join(" "), command.description()))
+    .chain(FIXED_KEYS.into_iter().map(|(keys, description)| {
+      let keys = keys.iter().copied().filter(|key| !is_shadowed(keymap, key)).collect::<Vec<_>>();
+      (keys.join(" "), description)
+    }))
+    .filter(|(keys, _)| !keys.is_empty())
+    .collect()
+}
+
+/// Whether a command takes the key, or the first key of `g g` and `m + char`, as commands run before the fixed keys
+fn is_shadowed(keymap: &KeyMap, key: &str) -> bool {
+  key.split(' ').filter(|key| *key != "+").find_map(|key| keymap.is_bound(key)).unwrap_or_default()
+}
+
+/// The keys as bound by default, as a table for `--help`
+pub fn usage() -> String {
+  let rows = rows(&KeyMap::default());
+  let width = rows.iter().map(|(keys, _)| keys.chars().count()).max().unwrap_or_default().max("Key".len());
+  let description_width = rows.iter().map(|(_, description)| description.len()).max().unwrap_or_default();
+  let line = format!(" {}", "-".repeat(width + description_width + 5));
+  let row = |keys: &str, description: &str| format!("| {:width$} | {:description_width$} |", keys, description);
+  let mut lines = vec!["ed\n".to_string(), line.clone(), row("Key", "Description")];
+  lines.push(format!("|{}|{}|", "-".repeat(width + 2), "-".repeat(description_width + 2)));
+  lines.extend(rows.iter().map(|(keys, description)| row(keys, description)));
+  lines.push(line);
+  lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_rows() {
+    let rows = rows(&KeyMap::default());
+    assert_eq!(rows[0], ("j Down".to_string(), "Move down"));
+    assert!(rows.contains(&("?".to_string(), "Show/hide this help")));
+    assert!(rows.contains(&("Ctrl+a".to_string(), "Mark all entries")));
+    assert!(rows.contains(&("g g".to_string(), "Move to top")));
+
+    let rows = super::rows(&KeyMap::new(Some("move_down=n,help=F,print=K,quit=g,confirm=m")).unwrap());
+    assert_eq!(rows[0], ("n".to_string(), "Move down"));
+    assert!(rows.contains(&("F".to_string(), "Show/hide this help")));
+    assert!(!rows.iter().any(|(_, description)| *description == "Group/mix directories and files"));
+    assert!(rows.contains(&("J".to_string(), "Scroll the preview down/up")));
+    assert!(!rows.iter().any(|(keys, _)| keys == "g g"));
+    assert!(!rows.iter().any(|(keys, _)| keys == "m + char"));
+    assert!(rows.contains(&("m".to_string(), "Change directory to current directory")));
+
+    // Never shadowed, as it cannot be bound
+    assert!(KeyMap::new(Some("confirm=Ctrl+c")).is_err());
+    assert!(rows.contains(&("Ctrl+c".to_string(), "Exit and return to original directory")));
+  }
+
+  #[test]
+  fn test_usage() {
+    let usage = usage();
+    assert!(usage.starts_with("ed\n\n ---"));
+    assert!(usage.contains("\n| Ctrl+p        | Preview the selected entry now              |\n"));
+    assert_eq!(usage.lines().map(|line| line.chars().count()).filter(|width| *width > 0).max(), Some(63));
+  }
+
+  /// The table of the README is the same as the usage
+  #[test]
+  fn test_readme() {
+    let readme = include_str!("../../README.md");
+    let table = readme
+      .lines()
+      .skip_while(|line| !line.starts_with("| Key "))
+      .skip(2)
+      .take_while(|line| line.starts_with('|'))
+      .map(|line| {
+        let line = line.replace("\\|", "|");
+        let (keys, description) = line[1..line.len() - 1].rsplit_once('|').unwrap();
+        (keys.trim().replace("` `", " ").replace('`', ""), description.trim().to_string())
+      })
+      .collect::<Vec<_>>();
+    let rows = rows(&KeyMap::default());
+    let rows = rows.iter().map(|(keys, description)| (keys.clone(), description.to_string())).collect::<Vec<_>>();
+    assert_eq!(table, rows);
+  }
+}
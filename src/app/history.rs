@@ -0,0 +1,76 @@
+use std::path::{Path, PathBuf};
+
+/// The oldest directories are forgotten beyond this
+const MAX_HISTORY: usize = 100;
+
+/// Directories visited, gone back and forward through like a browser
+#[derive(Debug, Default)]
+pub struct History {
+  entries: Vec<PathBuf>,
+  /// The entry of the working directory
+  cursor: usize,
+}
+
+impl History {
+  /// Directories after the current one are dropped, as they are no longer reached by going forward
+  pub fn push(&mut self, path: &Path) {
+    if self.entries.get(self.cursor).is_some_and(|entry| entry == path) {
+      return;
+    }
+    self.entries.truncate(self.cursor + 1);
+    self.entries.push(path.into());
+    if self.entries.len() > MAX_HISTORY {
+      self.entries.remove(0);
+    }
+    self.cursor = self.entries.len() - 1;
+  }
+  pub fn back(&mut self) -> Option<&Path> {
+    self.cursor = self.cursor.checked_sub(1)?;
+    Some(&self.entries[self.cursor])
+  }
+  pub fn forward(&mut self) -> Option<&Path> {
+    if self.cursor + 1 >= self.entries.len() {
+      return None;
+    }
+    self.cursor += 1;
+    Some(&self.entries[self.cursor])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_history() {
+    let mut history = History::default();
+    assert_eq!(history.back(), None);
+    for path in ["/a", "/b", "/b", "/c"] {
+      history.push(Path::new(path));
+    }
+    assert_eq!(history.back(), Some(Path::new("/b")));
+    assert_eq!(history.back(), Some(Path::new("/a")));
+    assert_eq!(history.back(), None);
+    assert_eq!(history.forward(), Some(Path::new("/b")));
+    // Arriving where the cursor points, as going back or forward does, keeps the entries after it
+    history.push(Path::new("/b"));
+    assert_eq!(history.forward(), Some(Path::new("/c")));
+    assert_eq!(history.forward(), None);
+
+    history.back();
+    history.push(Path::new("/d"));
+    assert_eq!(history.forward(), None);
+    assert_eq!(history.back(), Some(Path::new("/b")));
+  }
+
+  #[test]
+  fn test_max_history() {
+    let mut history = History::default();
+    for i in 0..MAX_HISTORY + 5 {
+      history.push(Path::new(&i.to_string()));
+    }
+    assert_eq!(history.entries.len(), MAX_HISTORY);
+    while history.back().is_some() {}
+    assert_eq!(history.entries[history.cursor], Path::new("5"));
+  }
+}
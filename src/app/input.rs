@@ -0,0 +1,196 @@
+use std::{
+  fs,
+  path::{is_separator, Path, PathBuf, MAIN_SEPARATOR},
+};
+
+use super::CaseMode;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputKind {
+  CreateDir,
+  Delete(Vec<PathBuf>),
+  /// Asked when the entries could not be moved to the trash
+  DeletePermanently(Vec<PathBuf>),
+  Filter(CaseMode),
+  /// Directory to move to, `Tab` completes it
+  Path,
+  /// Asked before quitting with `_ED_CONFIRM_QUIT`
+  Quit,
+  Rename(PathBuf),
+}
+
+impl InputKind {
+  pub fn prompt(&self) -> String {
+    match self {
+      InputKind::CreateDir => "New directory: ".into(),
+      InputKind::Delete(paths) => match paths.as_slice() {
+        [path] => format!("Delete {}? (y/n)", path.file_name().unwrap_or_default().to_string_lossy()),
+        paths => format!("Delete {} items? (y/n)", paths.len()),
+      },
+      InputKind::DeletePermanently(paths) => match paths.as_slice() {
+        [path] => format!(
+          "Cannot move to the trash, delete {} permanently? (y/n)",
+          path.file_name().unwrap_or_default().to_string_lossy()
+        ),
+        paths => format!("Cannot move to the trash, delete {} items permanently? (y/n)", paths.len()),
+      },
+      InputKind::Filter(case) => format!("Filter ({}): ", case.name()),
+      InputKind::Path => "Go to: ".into(),
+      InputKind::Quit => "Quit? (y/n)".into(),
+      InputKind::Rename(_) => "Rename to: ".into(),
+    }
+  }
+  /// Answered with a single `y`, any other key cancels
+  pub fn is_confirm(&self) -> bool {
+    matches!(self, InputKind::Delete(_) | InputKind::DeletePermanently(_) | InputKind::Quit)
+  }
+  /// Only `y`, `n` and `Esc` are handled while the prompt is shown
+  pub fn is_modal(&self) -> bool {
+    matches!(self, InputKind::Quit)
+  }
+}
+
+/// One-line text entry shown in the status line
+#[derive(Debug)]
+pub struct Input {
+  pub kind: InputKind,
+  pub text: String,
+  /// Shown after the text until it is edited
+  pub error: Option<String>,
+  /// Candidates cycled with `Tab` and the one shown
+  completion: Option<(Vec<String>, usize)>,
+}
+
+impl Input {
+  pub fn new(kind: InputKind) -> Self {
+    Self::with_text(kind, String::new())
+  }
+  pub fn with_text(kind: InputKind, text: String) -> Self {
+    Input { kind, text, error: None, completion: None }
+  }
+  pub fn push(&mut self, c: char) {
+    self.text.push(c);
+    self.edited();
+  }
+  pub fn pop(&mut self) {
+    self.text.pop();
+    self.edited();
+  }
+  fn edited(&mut self) {
+    self.error = None;
+    self.completion = None;
+  }
+  /// Complete the last component with a directory in `base` or the path typed, repeated calls cycle through them
+  pub fn complete(&mut self, base: &Path, show_hidden: bool) {
+    if let Some((candidates, i)) = &mut self.completion {
+      *i = (*i + 1) % candidates.len();
+      self.text = candidates[*i].clone();
+      return;
+    }
+    let candidates = candidates(&self.text, base, show_hidden);
+    self.error = None;
+    match candidates.len() {
+      0 => {}
+      // The next `Tab` continues into it
+      1 => self.text = candidates[0].clone(),
+      _ => {
+        self.text = candidates[0].clone();
+        self.completion = Some((candidates, 0));
+      }
+    }
+  }
+}
+
+/// `~` at the start stands for the home directory
+pub fn expand_home(text: &str) -> PathBuf {
+  match (text.strip_prefix('~'), home::home_dir()) {
+    (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(is_separator) => {
+      home.join(rest.trim_start_matches(is_separator))
+    }
+    _ => text.into(),
+  }
+}
+
+/// Directories whose names start with the last component of `text`, as the whole text ending with a separator
+fn candidates(text: &str, base: &Path, show_hidden: bool) -> Vec<String> {
+  let (dir, prefix) = match text.rfind(is_separator) {
+    Some(i) => text.split_at(i + 1),
+    None => ("", text),
+  };
+  let entries = if let Ok(entries) = fs::read_dir(base.join(expand_home(dir))) { entries } else { return vec![] };
+  let mut names = entries
+    .flatten()
+    .filter(|entry| entry.path().is_dir())
+    .map(|entry| entry.file_name().to_string_lossy().to_string())
+    .filter(|name| name.starts_with(prefix) && (show_hidden || prefix.starts_with('.') || !name.starts_with('.')))
+    .collect::<Vec<_>>();
+  names.sort();
+  names.into_iter().map(|name| format!("{}{}{}", dir, name, MAIN_SEPARATOR)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::util::TestDir;
+
+  #[test]
+  fn test_new() {
+    let input = Input::new(InputKind::CreateDir);
+    assert_eq!(input.kind, InputKind::CreateDir);
+    assert!(input.text.is_empty());
+    let input = Input::with_text(InputKind::Rename("a".into()), "a".into());
+    assert_eq!(input.text, "a");
+  }
+
+  #[test]
+  fn test_prompt() {
+    let kind = InputKind::Delete(vec!["/tmp/a.txt".into()]);
+    assert_eq!(kind.prompt(), "Delete a.txt? (y/n)");
+    let kind = InputKind::Delete(vec!["/tmp/a.txt".into(), "/tmp/b.txt".into()]);
+    assert_eq!(kind.prompt(), "Delete 2 items? (y/n)");
+    let kind = InputKind::DeletePermanently(vec!["/tmp/a.txt".into()]);
+    assert_eq!(kind.prompt(), "Cannot move to the trash, delete a.txt permanently? (y/n)");
+    assert!(kind.is_confirm());
+    assert!(!kind.is_modal());
+    assert!(!InputKind::CreateDir.is_confirm());
+    assert!(InputKind::Quit.is_confirm() && InputKind::Quit.is_modal());
+    assert_eq!(InputKind::Filter(CaseMode::Smart).prompt(), "Filter (smart): ");
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_complete() {
+    let dir = TestDir::new("test_complete");
+    for name in ["src", "sub", ".secret", "target/debug"] {
+      fs::create_dir_all(dir.join(name)).unwrap();
+    }
+    fs::write(dir.join("st.txt"), "").unwrap();
+    assert_eq!(candidates("s", &dir, false), ["src/", "sub/"]);
+    assert_eq!(candidates("s", &dir, true), ["src/", "sub/"]);
+    assert_eq!(candidates(".", &dir, false), [".secret/"]);
+    assert_eq!(candidates("", &dir, true), [".secret/", "src/", "sub/", "target/"]);
+
+    let mut input = Input::with_text(InputKind::Path, "s".into());
+    input.complete(&dir, false);
+    assert_eq!(input.text, "src/");
+    input.complete(&dir, false);
+    assert_eq!(input.text, "sub/");
+    input.complete(&dir, false);
+    assert_eq!(input.text, "src/");
+    input.text.clear();
+    input.push('t');
+    input.complete(&dir, false);
+    assert_eq!(input.text, "target/");
+    input.complete(&dir, false);
+    assert_eq!(input.text, "target/debug/");
+  }
+
+  #[test]
+  fn test_expand_home() {
+    let home = home::home_dir().unwrap();
+    assert_eq!(expand_home("~"), home);
+    assert_eq!(expand_home("~/a"), home.join("a"));
+    assert_eq!(expand_home("~a"), PathBuf::from("~a"));
+    assert_eq!(expand_home("/a"), PathBuf::from("/a"));
+  }
+}
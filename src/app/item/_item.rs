@@ -1,17 +1,26 @@
 use std::{
-  fs,
+  fs::{self, File},
+  io::{BufRead, BufReader, Read, Seek},
   path::{Path, PathBuf},
 };
 
 use anyhow::Context;
+use log::warn;
 
-use super::App;
+use super::{archive, App, ReadOptions, Thumbnail};
+use crate::util::{format_size, SizeUnit};
+
+/// Bytes read to judge whether a file is binary
+const BINARY_CHECK_SIZE: u64 = 8 * 1024;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Item {
   Path(ItemPath),
   Content(String),
+  Image(Thumbnail),
   Search(String),
+  /// `..` at the top of a listing with `_ED_SHOW_PARENT_ENTRY`, which has no path so that it is never operated on
+  Parent,
   None,
 }
 
@@ -54,6 +63,8 @@ pub enum ItemPath {
 pub enum ItemSymlink {
   Dir(PathBuf),
   File(PathBuf),
+  /// The target does not exist
+  Broken(PathBuf),
 }
 
 impl ItemSymlink {
@@ -61,6 +72,7 @@ impl ItemSymlink {
     match self {
       ItemSymlink::Dir(path) => path,
       ItemSymlink::File(path) => path,
+      ItemSymlink::Broken(path) => path,
     }
   }
 }
@@ -76,18 +88,6 @@ impl ItemPath {
     .into()
   }
 
-  fn read_link(&self) -> anyhow::Result<PathBuf> {
-    Ok(
-      match self {
-        ItemPath::Dir(path) => path,
-        ItemPath::File(path) => path,
-        ItemPath::Symlink(symlink) => symlink.get_path(),
-        ItemPath::Unknown(path) => path,
-      }
-      .read_link()?,
-    )
-  }
-
   fn is_dir(&self) -> bool {
     matches!(self, ItemPath::Dir(_) | ItemPath::Symlink(ItemSymlink::Dir(_)))
   }
@@ -107,27 +107,91 @@ impl ItemInfo {
   pub fn default() -> Self {
     Self { item: Item::new(), index: None }
   }
-  pub fn generate_child_items(&self) -> anyhow::Result<Vec<Self>> {
-    if self.is_symlink() {
-      if let Item::Path(path) = &self.item {
-        return App::make_items(path.read_link()?);
-      }
-    }
+  pub fn generate_child_items(&self, options: &ReadOptions) -> anyhow::Result<Vec<Self>> {
+    // Symlinked directories are read through the link, broken links have nothing to show
     Ok(if self.is_dir() {
-      App::make_items(self.get_path().unwrap())?
+      App::make_items(self.get_path().unwrap(), options)?
+    } else if self.is_file() && self.can_read() && archive::is_archive(&self.get_path().unwrap()) {
+      Self::generate_archive(&self.get_path().unwrap(), options)
+    } else if self.is_file() && self.can_read() && Thumbnail::is_image(self.get_path().unwrap()) {
+      vec![Self::generate_image(&self.get_path().unwrap(), options.size_unit)]
     } else if self.is_file() && self.can_read() {
-      if let Ok(s) = fs::read_to_string(self.get_path().context("Non-string files are being read.")?) {
-        s.lines().enumerate().map(|(i, s)| Self { item: Item::Content(s.to_string()), index: Some(i) }).collect()
-      } else {
-        vec![Self::default()]
-      }
+      Self::generate_contents(&self.get_path().context("Non-string files are being read.")?, options)
     } else {
-      vec![Self::default()]
+      vec![]
     })
   }
+  /// Only the head of the file is read, binary and large files are replaced by a description
+  ///
+  /// `index` is the line number from `0`, descriptions have none
+  fn generate_contents(path: &Path, options: &ReadOptions) -> Vec<Self> {
+    let mut file = if let Ok(file) = File::open(path) { file } else { return vec![] };
+    let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    if size > options.max_preview_bytes {
+      let text = format!("<file too large to preview ({})>", format_size(size, options.size_unit));
+      return vec![Self { item: Item::Content(text), index: None }];
+    }
+    let mut head = vec![];
+    if (&mut file).take(BINARY_CHECK_SIZE).read_to_end(&mut head).is_err() || file.rewind().is_err() {
+      return vec![];
+    }
+    if is_binary(&head) {
+      let text = format!("<binary file, {}>", format_size(size, options.size_unit));
+      return vec![Self { item: Item::Content(text), index: None }];
+    }
+    // Special files may be larger than their reported size
+    BufReader::new(file.take(options.max_preview_bytes))
+      .lines()
+      .take(options.preview_lines)
+      .map_while(Result::ok)
+      .enumerate()
+      .map(|(i, s)| Self { item: Item::Content(s), index: Some(i) })
+      .collect()
+  }
+  /// The top-level entries, or the same as other files if it cannot be read
+  fn generate_archive(path: &Path, options: &ReadOptions) -> Vec<Self> {
+    let (names, is_partial) = match archive::top_level_entries(path) {
+      Ok(entries) => entries,
+      Err(e) => {
+        warn!("Cannot read the archive {}: {}", path.display(), e);
+        return Self::generate_contents(path, options);
+      }
+    };
+    if names.is_empty() && !is_partial {
+      return vec![Self { item: Item::Content("<empty archive>".into()), index: None }];
+    }
+    let is_partial = is_partial || names.len() > options.preview_lines;
+    let mut items = names
+      .into_iter()
+      .take(options.preview_lines)
+      .enumerate()
+      .map(|(i, name)| Self { item: Item::Content(name), index: Some(i) })
+      .collect::<Vec<_>>();
+    if is_partial {
+      items.push(Self { item: Item::Content("<more entries not listed>".into()), index: None });
+    }
+    items
+  }
+  /// Falls back to a description of the file if it cannot be decoded
+  fn generate_image(path: &Path, size_unit: SizeUnit) -> Self {
+    let ext = path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    let size = fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0);
+    let description = format!("{} image, {}", ext, format_size(size, size_unit));
+    match Thumbnail::open(path, description.clone()) {
+      Ok(thumbnail) => Self { item: Item::Image(thumbnail), index: Some(0) },
+      Err(_) => Self { item: Item::Content(description), index: None },
+    }
+  }
   pub fn generate_filename(&self) -> Option<String> {
     Some(self.get_path()?.file_name()?.to_string_lossy().into())
   }
+  /// The path under `base`, or the whole path outside it, the file name without `base`
+  pub fn generate_name(&self, base: Option<&Path>) -> Option<String> {
+    let base = if let Some(base) = base { base } else { return self.generate_filename() };
+    let path = self.get_path()?;
+    let name = path.strip_prefix(base).ok().filter(|rest| !rest.as_os_str().is_empty()).unwrap_or(&path);
+    Some(name.to_string_lossy().into())
+  }
   pub fn can_read(&self) -> bool {
     if let Item::Path(path) = &self.item {
       path.is_file()
@@ -138,16 +202,27 @@ impl ItemInfo {
   pub fn is_dir(&self) -> bool {
     self.item.is_dir()
   }
+  pub fn is_parent(&self) -> bool {
+    self.item == Item::Parent
+  }
   pub fn is_file(&self) -> bool {
     self.item.is_file()
   }
-  fn is_symlink(&self) -> bool {
+  pub fn is_symlink(&self) -> bool {
     if let Some(p) = self.get_path() {
       p.is_symlink()
     } else {
       false
     }
   }
+  /// Target of the symlink as written in the link
+  pub fn read_link(&self) -> Option<PathBuf> {
+    if self.is_symlink() {
+      self.get_path()?.read_link().ok()
+    } else {
+      None
+    }
+  }
   pub fn get_path(&self) -> Option<PathBuf> {
     if let Item::Path(path) = &self.item {
       Some(path.get_path())
@@ -157,9 +232,15 @@ impl ItemInfo {
   }
 }
 
+/// Contains NUL or is not UTF-8, a character cut off at the end is allowed
+fn is_binary(head: &[u8]) -> bool {
+  head.contains(&0) || std::str::from_utf8(head).is_err_and(|e| e.error_len().is_some())
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::util::TestDir;
 
   #[test]
   fn test_item() {
@@ -175,4 +256,26 @@ mod tests {
     assert!(!item.is_symlink());
     assert_eq!(item.get_path(), None);
   }
+
+  #[test]
+  fn test_generate_contents() {
+    let dir = TestDir::new("test_contents");
+    let path = dir.join("contents");
+    fs::write(&path, "a\nb\nc\n").unwrap();
+    let options = ReadOptions { preview_lines: 2, ..ReadOptions::default() };
+    assert_eq!(ItemInfo::generate_contents(&path, &options).len(), 2);
+    let options = ReadOptions { max_preview_bytes: 4, ..ReadOptions::default() };
+    let items = ItemInfo::generate_contents(&path, &options);
+    assert_eq!(items[0].item, Item::Content("<file too large to preview (6 B)>".into()));
+    assert_eq!(items[0].index, None);
+  }
+
+  #[test]
+  fn test_is_binary() {
+    assert!(!is_binary(b"fn main() {}\n"));
+    assert!(!is_binary("日本語".as_bytes()));
+    assert!(!is_binary(&"日本語".as_bytes()[..4]));
+    assert!(is_binary(b"\x7fELF\x00\x01"));
+    assert!(is_binary(b"\xff\xfe abc"));
+  }
 }
@@ -0,0 +1,147 @@
+use std::{
+  collections::BTreeMap,
+  fs::File,
+  io::{BufReader, Read},
+  path::Path,
+};
+
+use anyhow::Context;
+use flate2::read::GzDecoder;
+
+/// Entries read from an archive at most, the rest is not listed
+pub const MAX_ARCHIVE_ENTRIES: usize = 10_000;
+/// Bytes decompressed from a `.tar.gz` at most, as its entries can only be found by reading through it
+const MAX_DECOMPRESSED_BYTES: u64 = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+  Zip,
+  Tar,
+  TarGz,
+}
+
+impl ArchiveKind {
+  fn from_path(path: &Path) -> Option<Self> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+      Some(ArchiveKind::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+      Some(ArchiveKind::TarGz)
+    } else if name.ends_with(".tar") {
+      Some(ArchiveKind::Tar)
+    } else {
+      None
+    }
+  }
+}
+
+/// Judged by the extension, `.zip` `.tar` `.tar.gz` `.tgz`
+pub fn is_archive(path: &Path) -> bool {
+  ArchiveKind::from_path(path).is_some()
+}
+
+/// Top-level entries sorted by name with `/` after directories, and whether some entries were left unread
+pub fn top_level_entries(path: &Path) -> anyhow::Result<(Vec<String>, bool)> {
+  let kind = ArchiveKind::from_path(path).context("Not an archive")?;
+  let file = File::open(path)?;
+  let mut entries = BTreeMap::new();
+  let is_partial = match kind {
+    ArchiveKind::Zip => {
+      let archive = zip::ZipArchive::new(BufReader::new(file))?;
+      for name in archive.file_names().take(MAX_ARCHIVE_ENTRIES) {
+        let name = name?;
+        insert(&mut entries, &name, name.ends_with('/'));
+      }
+      archive.len() > MAX_ARCHIVE_ENTRIES
+    }
+    ArchiveKind::Tar => read_tar(tar::Archive::new(file).entries_with_seek()?, &mut entries)?,
+    ArchiveKind::TarGz => {
+      let reader = GzDecoder::new(BufReader::new(file)).take(MAX_DECOMPRESSED_BYTES);
+      read_tar(tar::Archive::new(reader).entries()?, &mut entries)?
+    }
+  };
+  let names = entries.into_iter().map(|(name, is_dir)| if is_dir { name + "/" } else { name }).collect();
+  Ok((names, is_partial))
+}
+
+/// An entry cut off by the limit ends the listing, while one before any entry was read is an error
+fn read_tar<R: Read>(tar_entries: tar::Entries<R>, entries: &mut BTreeMap<String, bool>) -> anyhow::Result<bool> {
+  for (i, entry) in tar_entries.enumerate() {
+    if i >= MAX_ARCHIVE_ENTRIES {
+      return Ok(true);
+    }
+    let entry = match entry {
+      Ok(entry) => entry,
+      Err(_) if !entries.is_empty() => return Ok(true),
+      Err(e) => return Err(e.into()),
+    };
+    let is_dir = entry.header().entry_type().is_dir();
+    insert(entries, &entry.path()?.to_string_lossy(), is_dir);
+  }
+  Ok(false)
+}
+
+/// Only the first component of `name` is kept, which is a directory if anything follows it
+fn insert(entries: &mut BTreeMap<String, bool>, name: &str, is_dir: bool) {
+  let name = name.trim_start_matches("./").trim_start_matches('/');
+  let (first, rest) = name.split_once('/').unwrap_or((name, ""));
+  if first.is_empty() || first == "." {
+    return;
+  }
+  *entries.entry(first.into()).or_default() |= is_dir || !rest.is_empty();
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{fs, io::Write};
+
+  use flate2::{write::GzEncoder, Compression};
+
+  use super::*;
+  use crate::util::TestDir;
+
+  fn write_tar<W: Write>(writer: W) -> W {
+    let mut builder = tar::Builder::new(writer);
+    let mut header = tar::Header::new_gnu();
+    header.set_size(0);
+    builder.append_data(&mut header.clone(), "./src/main.rs", &[][..]).unwrap();
+    builder.append_data(&mut header.clone(), "README.md", &[][..]).unwrap();
+    header.set_entry_type(tar::EntryType::Directory);
+    builder.append_data(&mut header, "empty/", &[][..]).unwrap();
+    builder.into_inner().unwrap()
+  }
+
+  #[test]
+  fn test_is_archive() {
+    assert!(is_archive(Path::new("a.zip")));
+    assert!(is_archive(Path::new("a.TAR.GZ")));
+    assert!(is_archive(Path::new("a.tgz")));
+    assert!(is_archive(Path::new("a.tar")));
+    assert!(!is_archive(Path::new("a.gz")));
+    assert!(!is_archive(Path::new("zip")));
+  }
+
+  #[test]
+  fn test_top_level_entries() {
+    let dir = TestDir::new("test_archive");
+    let expected = (vec!["README.md".to_string(), "empty/".into(), "src/".into()], false);
+
+    let mut zip = zip::ZipWriter::new(File::create(dir.join("a.zip")).unwrap());
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    zip.start_file("src/main.rs", options).unwrap();
+    zip.start_file("README.md", options).unwrap();
+    zip.add_directory("empty/", options).unwrap();
+    zip.finish().unwrap();
+    assert_eq!(top_level_entries(&dir.join("a.zip")).unwrap(), expected);
+
+    write_tar(File::create(dir.join("a.tar")).unwrap());
+    assert_eq!(top_level_entries(&dir.join("a.tar")).unwrap(), expected);
+    write_tar(GzEncoder::new(File::create(dir.join("a.tar.gz")).unwrap(), Compression::fast())).finish().unwrap();
+    assert_eq!(top_level_entries(&dir.join("a.tar.gz")).unwrap(), expected);
+
+    fs::write(dir.join("broken.zip"), "not a zip").unwrap();
+    assert!(top_level_entries(&dir.join("broken.zip")).is_err());
+    fs::write(dir.join("broken.tgz"), "not a tar").unwrap();
+    assert!(top_level_entries(&dir.join("broken.tgz")).is_err());
+  }
+}
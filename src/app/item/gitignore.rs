@@ -0,0 +1,90 @@
+use std::{
+  cell::RefCell,
+  collections::HashMap,
+  fs,
+  path::{Path, PathBuf},
+  rc::Rc,
+  time::SystemTime,
+};
+
+use ignore::{
+  gitignore::{Gitignore, GitignoreBuilder},
+  Match,
+};
+
+thread_local! {
+  /// Compiled ignore files, compiled again only when modified
+  static CACHE: RefCell<HashMap<PathBuf, (SystemTime, Rc<Gitignore>)>> = RefCell::new(HashMap::new());
+}
+
+/// Ignore files that apply to a directory, from the deepest one
+pub struct GitIgnore(Vec<Rc<Gitignore>>);
+
+impl GitIgnore {
+  /// `None` outside a git repository
+  pub fn new(dir: &Path) -> Option<Self> {
+    let root = dir.ancestors().find(|ancestor| ancestor.join(".git").exists())?;
+    let mut matchers = vec![];
+    for ancestor in dir.ancestors() {
+      matchers.extend(load(ancestor, &ancestor.join(".gitignore")));
+      if ancestor == root {
+        break;
+      }
+    }
+    matchers.extend(load(root, &root.join(".git").join("info").join("exclude")));
+    Some(GitIgnore(matchers))
+  }
+
+  pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+    for matcher in &self.0 {
+      match matcher.matched(path, is_dir) {
+        Match::None => continue,
+        Match::Ignore(_) => return true,
+        Match::Whitelist(_) => return false,
+      }
+    }
+    false
+  }
+}
+
+fn load(dir: &Path, file: &Path) -> Option<Rc<Gitignore>> {
+  let modified = fs::metadata(file).and_then(|metadata| metadata.modified()).ok()?;
+  CACHE.with(|cache| {
+    let mut cache = cache.borrow_mut();
+    if let Some((cached, matcher)) = cache.get(file) {
+      if *cached == modified {
+        return Some(matcher.clone());
+      }
+    }
+    let mut builder = GitignoreBuilder::new(dir);
+    builder.add(file);
+    let matcher = Rc::new(builder.build().ok()?);
+    cache.insert(file.into(), (modified, matcher.clone()));
+    Some(matcher)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::util::TestDir;
+
+  #[test]
+  fn test_is_ignored() {
+    let dir = TestDir::new("test_gitignore");
+    fs::create_dir_all(dir.join(".git")).unwrap();
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    fs::write(dir.join(".gitignore"), "*.log\ntarget/\n").unwrap();
+    fs::write(dir.join("sub").join(".gitignore"), "!keep.log\n").unwrap();
+
+    let gitignore = GitIgnore::new(&dir.join("sub")).unwrap();
+    assert!(gitignore.is_ignored(&dir.join("sub").join("a.log"), false));
+    assert!(!gitignore.is_ignored(&dir.join("sub").join("keep.log"), false));
+    assert!(!gitignore.is_ignored(&dir.join("sub").join("a.txt"), false));
+    let gitignore = GitIgnore::new(&dir).unwrap();
+    assert!(gitignore.is_ignored(&dir.join("target"), true));
+    assert!(!gitignore.is_ignored(&dir.join("target"), false));
+
+    assert!(GitIgnore::new(Path::new("/")).is_none());
+  }
+}
@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use super::{Item, ItemPath, ItemSymlink};
+
+const DIR: &str = "\u{f07b}";
+const FILE: &str = "\u{f15b}";
+const SYMLINK_DIR: &str = "\u{f482}";
+const SYMLINK_FILE: &str = "\u{f481}";
+const UNKNOWN: &str = "\u{f128}";
+
+/// Nerd Font glyphs by lowercase extension
+const EXTENSIONS: [(&str, &str); 36] = [
+  ("7z", "\u{f410}"),
+  ("bash", "\u{f489}"),
+  ("c", "\u{e61e}"),
+  ("conf", "\u{e615}"),
+  ("cpp", "\u{e61d}"),
+  ("css", "\u{e749}"),
+  ("fish", "\u{f489}"),
+  ("gif", "\u{f1c5}"),
+  ("go", "\u{e626}"),
+  ("gz", "\u{f410}"),
+  ("h", "\u{e61e}"),
+  ("html", "\u{e736}"),
+  ("ini", "\u{e615}"),
+  ("java", "\u{e738}"),
+  ("jpeg", "\u{f1c5}"),
+  ("jpg", "\u{f1c5}"),
+  ("js", "\u{e74e}"),
+  ("json", "\u{e60b}"),
+  ("lock", "\u{f023}"),
+  ("md", "\u{e73e}"),
+  ("pdf", "\u{f1c1}"),
+  ("png", "\u{f1c5}"),
+  ("ps1", "\u{f489}"),
+  ("py", "\u{e73c}"),
+  ("rb", "\u{e739}"),
+  ("rs", "\u{e7a8}"),
+  ("sh", "\u{f489}"),
+  ("svg", "\u{f1c5}"),
+  ("tar", "\u{f410}"),
+  ("toml", "\u{e615}"),
+  ("ts", "\u{e628}"),
+  ("txt", "\u{f15c}"),
+  ("xz", "\u{f410}"),
+  ("yaml", "\u{e615}"),
+  ("yml", "\u{e615}"),
+  ("zip", "\u{f410}"),
+];
+
+/// Glyph shown before the filename, `None` for anything other than paths
+pub fn icon(item: &Item) -> Option<&'static str> {
+  let path = match item {
+    Item::Path(ItemPath::Dir(_)) | Item::Parent => return Some(DIR),
+    Item::Path(ItemPath::Symlink(ItemSymlink::Dir(_))) => return Some(SYMLINK_DIR),
+    Item::Path(ItemPath::Symlink(_)) => return Some(SYMLINK_FILE),
+    Item::Path(ItemPath::Unknown(_)) => return Some(UNKNOWN),
+    Item::Path(ItemPath::File(path)) => path,
+    _ => return None,
+  };
+  Some(file_icon(path))
+}
+
+fn file_icon(path: &Path) -> &'static str {
+  let ext = if let Some(ext) = path.extension() { ext.to_string_lossy().to_lowercase() } else { return FILE };
+  EXTENSIONS.binary_search_by_key(&ext.as_str(), |(ext, _)| ext).map_or(FILE, |i| EXTENSIONS[i].1)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_icon() {
+    assert!(EXTENSIONS.windows(2).all(|w| w[0].0 < w[1].0));
+    assert_eq!(icon(&Item::create_dir("src")), Some(DIR));
+    assert_eq!(icon(&Item::Path(ItemPath::File("main.RS".into()))), Some("\u{e7a8}"));
+    assert_eq!(icon(&Item::Path(ItemPath::File("Makefile".into()))), Some(FILE));
+    assert_eq!(icon(&Item::Path(ItemPath::File("a.xyz".into()))), Some(FILE));
+    assert_eq!(icon(&Item::Content("fn main() {}".into())), None);
+  }
+}
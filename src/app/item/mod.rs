@@ -1,7 +1,19 @@
 mod _item;
+mod archive;
+mod gitignore;
+mod icon;
+mod preview_command;
 mod read;
+mod sort;
+mod thumbnail;
+mod tree;
 
 pub use self::_item::{Item, ItemInfo, ItemPath, ItemSymlink};
-pub use self::read::read_items;
+pub use self::icon::icon;
+pub use self::preview_command::{PreviewCommand, PreviewCommands, PREVIEW_COMMAND_TIMEOUT};
+pub use self::read::{read_items, read_list, ReadOptions, DEFAULT_MAX_PREVIEW_BYTES, DEFAULT_PREVIEW_LINES};
+pub use self::sort::SortMode;
+pub use self::thumbnail::Thumbnail;
+pub use self::tree::{build_tree, PreviewMode, TreeEntry};
 
 pub use super::App;
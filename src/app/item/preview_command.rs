@@ -0,0 +1,190 @@
+use std::{
+  io::Read,
+  path::Path,
+  process::{Command, Stdio},
+  sync::mpsc::{self, RecvTimeoutError},
+  thread,
+  time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use log::warn;
+
+use super::{Item, ItemInfo, ReadOptions};
+use crate::app::ansi;
+
+/// The command is stopped and the output so far is shown after this long
+pub const PREVIEW_COMMAND_TIMEOUT: Duration = Duration::from_secs(2);
+/// Replaced by the path of the file, the path is the last argument when the command has none
+const PLACEHOLDER: &str = "{}";
+
+/// A program and its arguments run to preview a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewCommand(Vec<String>);
+
+/// Preview commands by lowercase extension
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreviewCommands(Vec<(Vec<String>, PreviewCommand)>);
+
+impl PreviewCommands {
+  /// `spec` is like `rs|toml=bat --color=always {};pdf=pdftotext {} -`
+  pub fn new(spec: Option<&str>) -> anyhow::Result<Self> {
+    let mut commands = vec![];
+    for entry in spec.unwrap_or_default().split(';').map(str::trim).filter(|entry| !entry.is_empty()) {
+      let (extensions, command) =
+        entry.split_once('=').with_context(|| format!("Expected `extensions=command`: {}", entry))?;
+      let extensions = extensions.split('|').map(|ext| ext.trim().to_lowercase()).collect::<Vec<_>>();
+      if extensions.iter().any(String::is_empty) {
+        anyhow::bail!("Empty extension: {}", entry);
+      }
+      let command = command.split_whitespace().map(String::from).collect::<Vec<_>>();
+      if command.is_empty() {
+        anyhow::bail!("Empty command: {}", entry);
+      }
+      commands.push((extensions, PreviewCommand(command)));
+    }
+    Ok(PreviewCommands(commands))
+  }
+  /// The first command for the extension of `path`
+  pub fn find(&self, path: &Path) -> Option<&PreviewCommand> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    self.0.iter().find(|(extensions, _)| extensions.contains(&ext)).map(|(_, command)| command)
+  }
+}
+
+impl PreviewCommand {
+  /// The output of the command, or the usual preview of `item` when the command cannot be run or prints nothing
+  pub fn preview(&self, item: &ItemInfo, options: &ReadOptions) -> anyhow::Result<Vec<ItemInfo>> {
+    let path = item.get_path().context("Non-string files are being read.")?;
+    match self.run(&path, options, PREVIEW_COMMAND_TIMEOUT) {
+      Some(items) => Ok(items),
+      None => item.generate_child_items(options),
+    }
+  }
+  fn command(&self, path: &Path) -> Command {
+    let mut command = Command::new(&self.0[0]);
+    let args = &self.0[1..];
+    if args.iter().any(|arg| arg.contains(PLACEHOLDER)) {
+      command.args(args.iter().map(|arg| arg.replace(PLACEHOLDER, &path.to_string_lossy())));
+    } else {
+      command.args(args).arg(path);
+    }
+    command
+  }
+  fn run(&self, path: &Path, options: &ReadOptions, timeout: Duration) -> Option<Vec<ItemInfo>> {
+    let mut child = match self.command(path).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::null()).spawn() {
+      Ok(child) => child,
+      Err(e) => {
+        warn!("Cannot run the preview command {}: {}", self.0[0], e);
+        return None;
+      }
+    };
+    let stdout = child.stdout.take()?;
+    let (sender, receiver) = mpsc::channel();
+    let limit = options.max_preview_bytes;
+    thread::spawn(move || {
+      let mut stdout = stdout.take(limit);
+      let mut buf = [0; 8192];
+      // Ends when the command closes its output, reaches the limit or the result is no longer needed
+      while let Ok(n @ 1..) = stdout.read(&mut buf) {
+        if sender.send(buf[..n].to_vec()).is_err() {
+          break;
+        }
+      }
+    });
+    let deadline = Instant::now() + timeout;
+    let mut output = vec![];
+    let timed_out = loop {
+      match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        Ok(chunk) => output.extend(chunk),
+        Err(RecvTimeoutError::Disconnected) => break false,
+        Err(RecvTimeoutError::Timeout) => break true,
+      }
+    };
+    // Also stops the command when the output was cut off at the limit
+    let _ = child.kill();
+    let _ = child.wait();
+    if timed_out {
+      warn!("The preview command {} timed out for {}", self.0[0], path.display());
+    }
+    if output.is_empty() && !timed_out {
+      return None;
+    }
+    let text = String::from_utf8_lossy(&output);
+    let mut lines = text
+      .lines()
+      .take(options.preview_lines)
+      .map(|line| if options.preview_ansi { line.to_string() } else { ansi::strip(line) })
+      .enumerate()
+      .map(|(i, s)| ItemInfo { item: Item::Content(s), index: Some(i) })
+      .collect::<Vec<_>>();
+    if timed_out {
+      let text = format!("<preview command timed out after {}s>", timeout.as_secs_f32());
+      lines.push(ItemInfo { item: Item::Content(text), index: None });
+    }
+    Some(lines)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_preview_commands() {
+    assert_eq!(PreviewCommands::new(None).unwrap(), PreviewCommands::default());
+    let commands = PreviewCommands::new(Some("rs|TOML = bat --color=always {}; pdf=pdftotext {} -")).unwrap();
+    assert_eq!(
+      commands.find(Path::new("a.toml")),
+      Some(&PreviewCommand(vec!["bat".into(), "--color=always".into(), "{}".into()]))
+    );
+    assert_eq!(commands.find(Path::new("a.PDF")).map(|command| &command.0[0]), Some(&"pdftotext".to_string()));
+    assert_eq!(commands.find(Path::new("a.txt")), None);
+    assert_eq!(commands.find(Path::new("Makefile")), None);
+
+    assert!(PreviewCommands::new(Some("rs")).is_err());
+    assert!(PreviewCommands::new(Some("rs=")).is_err());
+    assert!(PreviewCommands::new(Some("|rs=bat")).is_err());
+  }
+
+  #[test]
+  fn test_command() {
+    let path = Path::new("/tmp/a.rs");
+    let command = PreviewCommand(vec!["bat".into(), "--file={}".into()]).command(path);
+    assert_eq!(command.get_args().collect::<Vec<_>>(), ["--file=/tmp/a.rs"]);
+    let command = PreviewCommand(vec!["bat".into(), "-p".into()]).command(path);
+    assert_eq!(command.get_args().collect::<Vec<_>>(), ["-p", "/tmp/a.rs"]);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_run() {
+    let path = Path::new("a.rs");
+    let options = ReadOptions { preview_lines: 2, ..ReadOptions::default() };
+    let lines = |command: &[&str], options: &ReadOptions, timeout| {
+      let command = PreviewCommand(command.iter().map(|arg| arg.to_string()).collect());
+      let items = command.run(path, options, timeout)?;
+      Some(
+        items
+          .into_iter()
+          .map(|item| match item.item {
+            Item::Content(s) => s,
+            _ => unreachable!(),
+          })
+          .collect::<Vec<_>>(),
+      )
+    };
+    assert_eq!(
+      lines(&["printf", "\x1b[31m%s\x1b[0m\nb\nc"], &options, PREVIEW_COMMAND_TIMEOUT).unwrap(),
+      ["a.rs", "b"]
+    );
+    let ansi = ReadOptions { preview_ansi: true, ..options };
+    assert_eq!(lines(&["printf", "\x1b[31m%s"], &ansi, PREVIEW_COMMAND_TIMEOUT).unwrap(), ["\x1b[31ma.rs"]);
+    assert_eq!(lines(&["true"], &options, PREVIEW_COMMAND_TIMEOUT), None);
+    assert_eq!(lines(&["_easychangedirectory_not_exist"], &options, PREVIEW_COMMAND_TIMEOUT), None);
+    assert_eq!(
+      lines(&["sh", "-c", "echo a; sleep 5", "{}"], &options, Duration::from_millis(100)).unwrap(),
+      ["a", "<preview command timed out after 0.1s>"]
+    );
+  }
+}
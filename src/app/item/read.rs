@@ -1,43 +1,287 @@
 use std::fs;
-use std::path::Path;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
 
-use crate::app::{Item, ItemInfo};
+use log::warn;
+
+use crate::app::{FileCategory, Item, ItemInfo};
+use crate::{util::SizeUnit, Config};
 
 use super::_item::{ItemPath, ItemSymlink};
+use super::gitignore::GitIgnore;
+use super::{PreviewMode, SortMode};
+
+pub const DEFAULT_PREVIEW_LINES: usize = 500;
+pub const DEFAULT_MAX_PREVIEW_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReadOptions {
+  pub show_hidden: bool,
+  /// Hide files ignored by git, no effect outside a repository
+  pub respect_gitignore: bool,
+  pub sort: SortMode,
+  pub dirs_first: bool,
+  /// Maximum number of lines read for the file preview
+  pub preview_lines: usize,
+  /// Larger files are not previewed
+  pub max_preview_bytes: u64,
+  /// Units of the sizes in the descriptions of files that are not previewed
+  pub size_unit: SizeUnit,
+  /// Whether the directories in the child block are read as a tree
+  pub preview_mode: PreviewMode,
+  /// Only directories and symbolic links to them are read
+  pub dirs_only: bool,
+  /// Only files of this category are read, along with the directories
+  pub category: Option<FileCategory>,
+  /// Escape sequences in the output of preview commands are kept to show the colors
+  pub preview_ansi: bool,
+  /// Symbolic links to directories are expanded in the tree
+  pub follow_symlinks: bool,
+  /// `..` is put before the entries of directories that have a parent
+  pub show_parent_entry: bool,
+}
+
+impl Default for ReadOptions {
+  fn default() -> Self {
+    ReadOptions {
+      show_hidden: false,
+      respect_gitignore: false,
+      sort: SortMode::default(),
+      dirs_first: false,
+      preview_lines: DEFAULT_PREVIEW_LINES,
+      max_preview_bytes: DEFAULT_MAX_PREVIEW_BYTES,
+      size_unit: SizeUnit::default(),
+      preview_mode: PreviewMode::default(),
+      dirs_only: false,
+      category: None,
+      preview_ansi: false,
+      follow_symlinks: false,
+      show_parent_entry: false,
+    }
+  }
+}
+
+impl ReadOptions {
+  pub fn from_config(config: &Config) -> Self {
+    ReadOptions {
+      sort: config.sort_mode(),
+      dirs_first: config.is_dirs_first(),
+      preview_lines: config.preview_lines(),
+      max_preview_bytes: config.max_preview_bytes(),
+      size_unit: config.size_unit(),
+      preview_mode: config.preview_mode(),
+      respect_gitignore: config.is_respect_gitignore(),
+      dirs_only: config.is_dirs_only(),
+      preview_ansi: config.is_preview_ansi(),
+      follow_symlinks: config.is_follow_symlinks(),
+      show_parent_entry: config.is_show_parent_entry(),
+      ..ReadOptions::default()
+    }
+  }
+  fn is_visible(&self, entry: &fs::DirEntry) -> bool {
+    self.show_hidden || !entry.file_name().to_string_lossy().starts_with('.')
+  }
+  /// `..` stays at the top
+  pub fn sort_items(&self, items: &mut [ItemInfo]) {
+    let start = usize::from(items.first().is_some_and(ItemInfo::is_parent));
+    self.sort.sort(&mut items[start..], self.dirs_first);
+  }
+}
 
-pub fn read_items<P: AsRef<Path>>(path: P) -> anyhow::Result<Vec<ItemInfo>> {
-  let mut items = if let Ok(read_dir) = fs::read_dir(&path) {
-    read_dir
+pub fn read_items<P: AsRef<Path>>(path: P, options: &ReadOptions) -> anyhow::Result<Vec<ItemInfo>> {
+  let gitignore = if options.respect_gitignore { GitIgnore::new(path.as_ref()) } else { None };
+  let mut items = match fs::read_dir(&path) {
+    Ok(read_dir) => read_dir
       .filter_map(|entry| {
         let entry = entry.ok()?;
+        if !options.is_visible(&entry) {
+          return None;
+        }
+        let item = Item::Path(entry_path(&entry));
+        let is_dir = item.is_dir();
+        if options.dirs_only && !is_dir {
+          return None;
+        }
         let filepath = entry.path();
-        let path = if filepath.is_file() && filepath.is_symlink() {
-          ItemPath::Symlink(ItemSymlink::File(filepath))
-        } else if filepath.is_dir() && filepath.is_symlink() {
-          ItemPath::Symlink(ItemSymlink::Dir(filepath))
-        } else if filepath.is_file() {
-          ItemPath::File(filepath)
-        } else if filepath.is_dir() {
-          ItemPath::Dir(filepath)
-        } else {
-          ItemPath::Unknown(filepath)
-        };
-        Some(ItemInfo { item: Item::Path(path), index: Some(0) })
+        if options.category.is_some_and(|category| !is_dir && !category.contains(&filepath)) {
+          return None;
+        }
+        if gitignore.as_ref().is_some_and(|gitignore| gitignore.is_ignored(&filepath, is_dir)) {
+          return None;
+        }
+        Some(ItemInfo { item, index: Some(0) })
       })
-      .collect::<Vec<_>>()
-  } else {
-    return Ok(vec![ItemInfo::default()]);
+      .collect::<Vec<_>>(),
+    // Unreadable directories are shown as empty
+    Err(e) => {
+      warn!("Cannot read {}: {}", path.as_ref().display(), e);
+      return Ok(vec![]);
+    }
   };
 
-  items.sort_by_key(|item| item.get_path());
-  Ok(
-    items
-      .iter_mut()
-      .enumerate()
-      .map(|(i, item)| {
-        item.index = Some(i);
-        item.clone()
-      })
-      .collect(),
-  )
+  options.sort_items(&mut items);
+  if options.show_parent_entry && path.as_ref().parent().is_some() {
+    items.insert(0, ItemInfo { item: Item::Parent, index: None });
+  }
+  Ok(items)
+}
+
+/// Paths one per line, relative ones under `base`, in the given order and numbered in it
+///
+/// Blank lines are skipped, paths that do not exist are kept and shown as unknown
+pub fn read_list<R: BufRead>(reader: R, base: &Path) -> io::Result<Vec<ItemInfo>> {
+  let mut items = vec![];
+  for line in reader.lines() {
+    let line = line?;
+    let line = line.trim_end_matches('\r');
+    if line.trim().is_empty() {
+      continue;
+    }
+    let index = Some(items.len());
+    items.push(ItemInfo { item: Item::Path(item_path(base.join(line))), index });
+  }
+  Ok(items)
+}
+
+/// The type read with the directory saves a `stat` per entry, only symbolic links are followed to their targets
+fn entry_path(entry: &fs::DirEntry) -> ItemPath {
+  let filepath = entry.path();
+  match entry.file_type() {
+    Ok(file_type) if file_type.is_dir() => ItemPath::Dir(filepath),
+    Ok(file_type) if file_type.is_file() => ItemPath::File(filepath),
+    Ok(file_type) if file_type.is_symlink() => match fs::metadata(&filepath) {
+      Ok(metadata) if metadata.is_dir() => ItemPath::Symlink(ItemSymlink::Dir(filepath)),
+      Ok(metadata) if metadata.is_file() => ItemPath::Symlink(ItemSymlink::File(filepath)),
+      Ok(_) => ItemPath::Unknown(filepath),
+      Err(_) => ItemPath::Symlink(ItemSymlink::Broken(filepath)),
+    },
+    Ok(_) => ItemPath::Unknown(filepath),
+    Err(_) => item_path(filepath),
+  }
+}
+
+fn item_path(filepath: PathBuf) -> ItemPath {
+  let is_symlink = fs::symlink_metadata(&filepath).is_ok_and(|metadata| metadata.file_type().is_symlink());
+  if filepath.is_file() && is_symlink {
+    ItemPath::Symlink(ItemSymlink::File(filepath))
+  } else if filepath.is_dir() && is_symlink {
+    ItemPath::Symlink(ItemSymlink::Dir(filepath))
+  } else if is_symlink && !filepath.exists() {
+    ItemPath::Symlink(ItemSymlink::Broken(filepath))
+  } else if filepath.is_file() {
+    ItemPath::File(filepath)
+  } else if filepath.is_dir() {
+    ItemPath::Dir(filepath)
+  } else {
+    ItemPath::Unknown(filepath)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Instant;
+
+  use super::*;
+  use crate::util::TestDir;
+
+  /// `cargo test --release bench_read_items -- --ignored --nocapture` compares the listing with `item_path`, which
+  /// stats every entry several times
+  #[test]
+  #[ignore]
+  fn bench_read_items() {
+    let dir = TestDir::new("bench_read_items");
+    for i in 0..5000 {
+      if i % 10 == 0 {
+        fs::create_dir_all(dir.join(format!("dir{}", i))).unwrap();
+      } else {
+        fs::write(dir.join(format!("file{}", i)), "").unwrap();
+      }
+    }
+    let options = ReadOptions { dirs_only: true, ..ReadOptions::default() };
+    let time = |f: &dyn Fn() -> usize| {
+      let started = Instant::now();
+      let len = (0..20).map(|_| f()).last().unwrap();
+      (started.elapsed() / 20, len)
+    };
+    let (by_entry, len) = time(&|| read_items(&dir, &options).unwrap().len());
+    let (by_stat, stat_len) = time(&|| {
+      fs::read_dir(&dir)
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .map(item_path)
+        .collect::<Vec<_>>()
+        .len()
+    });
+    assert_eq!(len, stat_len);
+    println!("{} entries: {:?} with the entry types, {:?} with stat", len, by_entry, by_stat);
+  }
+
+  #[test]
+  fn test_read_empty() {
+    let dir = TestDir::new("test_read_empty");
+    assert!(read_items(&dir, &ReadOptions::default()).unwrap().is_empty());
+    assert!(read_items(dir.join("missing"), &ReadOptions::default()).unwrap().is_empty());
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_read_symlinks() {
+    use std::os::unix::fs::symlink;
+
+    let dir = TestDir::new("test_read_symlinks");
+    fs::create_dir_all(dir.join("dir")).unwrap();
+    fs::write(dir.join("file"), "").unwrap();
+    symlink("dir", dir.join("link_dir")).unwrap();
+    symlink("file", dir.join("link_file")).unwrap();
+    symlink("missing", dir.join("link_broken")).unwrap();
+
+    let items = read_items(&dir, &ReadOptions::default()).unwrap();
+    let paths = items.into_iter().map(|item| item.item).collect::<Vec<_>>();
+    assert_eq!(
+      paths,
+      vec![
+        Item::Path(ItemPath::Dir(dir.join("dir"))),
+        Item::Path(ItemPath::File(dir.join("file"))),
+        Item::Path(ItemPath::Symlink(ItemSymlink::Broken(dir.join("link_broken")))),
+        Item::Path(ItemPath::Symlink(ItemSymlink::Dir(dir.join("link_dir")))),
+        Item::Path(ItemPath::Symlink(ItemSymlink::File(dir.join("link_file")))),
+      ]
+    );
+    let broken = ItemInfo { item: paths[2].clone(), index: None };
+    assert_eq!(broken.read_link(), Some("missing".into()));
+    assert!(broken.generate_child_items(&ReadOptions::default()).is_ok());
+
+    let options = ReadOptions { dirs_only: true, ..ReadOptions::default() };
+    let paths = read_items(&dir, &options).unwrap().into_iter().map(|item| item.item).collect::<Vec<_>>();
+    assert_eq!(
+      paths,
+      vec![
+        Item::Path(ItemPath::Dir(dir.join("dir"))),
+        Item::Path(ItemPath::Symlink(ItemSymlink::Dir(dir.join("link_dir")))),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_read_list() {
+    let dir = TestDir::new("test_read_list");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("src/main.rs"), "").unwrap();
+
+    let input = "src/main.rs\r\n\nsrc\n  \nmissing\n";
+    let items = read_list(io::Cursor::new(input), &dir).unwrap();
+    let paths = items.iter().map(|item| item.item.clone()).collect::<Vec<_>>();
+    assert_eq!(
+      paths,
+      vec![
+        Item::Path(ItemPath::File(dir.join("src/main.rs"))),
+        Item::Path(ItemPath::Dir(dir.join("src"))),
+        Item::Path(ItemPath::Unknown(dir.join("missing"))),
+      ]
+    );
+    assert_eq!(items.iter().map(|item| item.index).collect::<Vec<_>>(), [Some(0), Some(1), Some(2)]);
+    assert_eq!(read_list(io::Cursor::new("/"), &dir).unwrap()[0].get_path(), Some(PathBuf::from("/")));
+  }
 }
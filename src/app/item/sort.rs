@@ -0,0 +1,114 @@
+use std::{cmp::Reverse, fs};
+
+use serde::Deserialize;
+
+use super::ItemInfo;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortMode {
+  #[default]
+  Name,
+  NameDesc,
+  ModifiedTime,
+  Size,
+  Extension,
+}
+
+impl SortMode {
+  pub fn name(&self) -> &'static str {
+    match self {
+      SortMode::Name => "name",
+      SortMode::NameDesc => "name_desc",
+      SortMode::ModifiedTime => "modified_time",
+      SortMode::Size => "size",
+      SortMode::Extension => "extension",
+    }
+  }
+  pub fn next(&self) -> Self {
+    match self {
+      SortMode::Name => SortMode::NameDesc,
+      SortMode::NameDesc => SortMode::ModifiedTime,
+      SortMode::ModifiedTime => SortMode::Size,
+      SortMode::Size => SortMode::Extension,
+      SortMode::Extension => SortMode::Name,
+    }
+  }
+  /// Sort by name first so that equal keys always keep the same order, then renumber `index`
+  pub fn sort(&self, items: &mut [ItemInfo], dirs_first: bool) {
+    items.sort_by_key(|item| item.get_path());
+    match self {
+      SortMode::Name => {}
+      SortMode::NameDesc => items.reverse(),
+      SortMode::ModifiedTime => items
+        .sort_by_cached_key(|item| Reverse(item.get_path().and_then(|path| fs::metadata(path).ok()?.modified().ok()))),
+      SortMode::Size => items.sort_by_cached_key(|item| {
+        Reverse(item.get_path().and_then(|path| fs::metadata(path).ok()).map_or(0, |metadata| metadata.len()))
+      }),
+      SortMode::Extension => items.sort_by_cached_key(|item| {
+        item.get_path().and_then(|path| path.extension().map(|ext| ext.to_string_lossy().to_lowercase()))
+      }),
+    }
+    if dirs_first {
+      items.sort_by_key(|item| !item.is_dir());
+    }
+    items.iter_mut().enumerate().for_each(|(i, item)| item.index = Some(i));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::{Item, ItemPath};
+
+  fn names(items: &[ItemInfo]) -> Vec<String> {
+    items.iter().map(|item| item.generate_filename().unwrap()).collect()
+  }
+
+  #[test]
+  fn test_sort() {
+    let mut items = vec![
+      ItemInfo { item: Item::Path(ItemPath::File("b.txt".into())), index: None },
+      ItemInfo { item: Item::Path(ItemPath::Dir("c".into())), index: None },
+      ItemInfo { item: Item::Path(ItemPath::File("a.rs".into())), index: None },
+    ];
+    SortMode::Name.sort(&mut items, false);
+    assert_eq!(names(&items), ["a.rs", "b.txt", "c"]);
+    assert_eq!(items[2].index, Some(2));
+    SortMode::NameDesc.sort(&mut items, false);
+    assert_eq!(names(&items), ["c", "b.txt", "a.rs"]);
+    SortMode::Extension.sort(&mut items, false);
+    assert_eq!(names(&items), ["c", "a.rs", "b.txt"]);
+    SortMode::Name.sort(&mut items, true);
+    assert_eq!(names(&items), ["c", "a.rs", "b.txt"]);
+  }
+
+  #[test]
+  fn test_dirs_first() {
+    let item = |path: &str, is_dir| {
+      let path = path.into();
+      ItemInfo { item: Item::Path(if is_dir { ItemPath::Dir(path) } else { ItemPath::File(path) }), index: None }
+    };
+    let items = vec![item("b.txt", false), item("d", true), item("a.rs", false), item("c.d", true), item("e", false)];
+    let mut mode = SortMode::Name;
+    for _ in 0..5 {
+      let mut grouped = items.clone();
+      mode.sort(&mut grouped, true);
+      let (mut dirs, mut files): (Vec<_>, Vec<_>) = items.iter().cloned().partition(ItemInfo::is_dir);
+      mode.sort(&mut dirs, false);
+      mode.sort(&mut files, false);
+      assert_eq!(names(&grouped), [names(&dirs), names(&files)].concat(), "{}", mode.name());
+      assert_eq!(grouped.last().unwrap().index, Some(4));
+      mode = mode.next();
+    }
+  }
+
+  #[test]
+  fn test_next() {
+    let mut mode = SortMode::Name;
+    for _ in 0..5 {
+      mode = mode.next();
+    }
+    assert_eq!(mode, SortMode::Name);
+  }
+}
@@ -0,0 +1,81 @@
+use std::path::Path;
+
+const EXTENSIONS: [&str; 4] = ["gif", "jpeg", "jpg", "png"];
+/// Upper bound of the decoded size, the preview is scaled down further when rendered
+const MAX_SIZE: u32 = 256;
+
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+  width: u32,
+  height: u32,
+  pixels: Vec<Rgb>,
+  /// Shown instead of the image when it cannot be drawn
+  pub description: String,
+}
+
+impl Thumbnail {
+  pub fn is_image<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().extension().is_some_and(|ext| EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+  }
+
+  pub fn open<P: AsRef<Path>>(path: P, description: String) -> anyhow::Result<Self> {
+    let image = image::open(path)?.thumbnail(MAX_SIZE, MAX_SIZE).to_rgb8();
+    Ok(Thumbnail {
+      width: image.width(),
+      height: image.height(),
+      pixels: image.pixels().map(|p| p.0).collect(),
+      description,
+    })
+  }
+
+  /// Scale to fit `width` x `height` cells, each cell is a (top, bottom) pair for a half block
+  pub fn to_cells(&self, width: u16, height: u16) -> Vec<Vec<(Rgb, Rgb)>> {
+    if self.width == 0 || self.height == 0 {
+      return vec![];
+    }
+    let scale = f64::min(width as f64 / self.width as f64, (height as f64 * 2.0) / self.height as f64);
+    let cols = ((self.width as f64 * scale) as u32).max(1);
+    let rows = ((self.height as f64 * scale) as u32).max(1);
+    let pixel = |x: u32, y: u32| {
+      let sx = (x * self.width / cols).min(self.width - 1);
+      let sy = (y * self.height / rows).min(self.height - 1);
+      self.pixels[(sy * self.width + sx) as usize]
+    };
+    (0..rows.div_ceil(2))
+      .map(|row| {
+        (0..cols)
+          .map(|x| (pixel(x, row * 2), if row * 2 + 1 < rows { pixel(x, row * 2 + 1) } else { [0; 3] }))
+          .collect()
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_is_image() {
+    assert!(Thumbnail::is_image("a.PNG"));
+    assert!(Thumbnail::is_image("a.jpg"));
+    assert!(!Thumbnail::is_image("a.txt"));
+    assert!(!Thumbnail::is_image("png"));
+  }
+
+  #[test]
+  fn test_to_cells() {
+    let thumbnail = Thumbnail {
+      width: 2,
+      height: 2,
+      pixels: vec![[1, 1, 1], [2, 2, 2], [3, 3, 3], [4, 4, 4]],
+      description: String::new(),
+    };
+    assert_eq!(thumbnail.to_cells(2, 1), vec![vec![([1, 1, 1], [3, 3, 3]), ([2, 2, 2], [4, 4, 4])]]);
+    let cells = thumbnail.to_cells(10, 2);
+    assert_eq!(cells.len(), 2);
+    assert_eq!(cells[0].len(), 4);
+  }
+}
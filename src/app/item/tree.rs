@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use super::{read_items, Item, ItemInfo, ItemPath, ItemSymlink, ReadOptions};
+use crate::app::Visited;
+
+/// Levels of directories shown in the tree, the entries of the selected directory are the first
+const TREE_DEPTH: usize = 2;
+/// The tree is cut off after this many entries
+const MAX_TREE_ENTRIES: usize = 200;
+
+/// How directories are shown in the child block
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewMode {
+  /// The entries of the directory
+  #[default]
+  Flat,
+  /// The entries and those of the directories inside
+  Tree,
+}
+
+impl PreviewMode {
+  pub fn name(&self) -> &'static str {
+    match self {
+      PreviewMode::Flat => "flat",
+      PreviewMode::Tree => "tree",
+    }
+  }
+}
+
+/// A line of the tree, `prefix` is the branches drawn before the name
+#[derive(Debug, Clone)]
+pub struct TreeEntry {
+  pub prefix: String,
+  pub item: ItemInfo,
+}
+
+/// `items` are the entries of the directory, symbolic links to directories are expanded only with `follow_symlinks`
+pub fn build_tree(items: &[ItemInfo], options: &ReadOptions) -> Vec<TreeEntry> {
+  let mut tree = vec![];
+  let mut visited = Visited::default();
+  if let Some(dir) = items.first().and_then(|item| item.get_path()).as_deref().and_then(Path::parent) {
+    visited.insert(dir);
+  }
+  push_entries(&mut tree, items, "", 1, options, &mut visited);
+  if tree.len() > MAX_TREE_ENTRIES {
+    tree.truncate(MAX_TREE_ENTRIES);
+    tree.push(TreeEntry { prefix: String::new(), item: ItemInfo { item: Item::Content("…".into()), index: None } });
+  }
+  tree
+}
+
+/// Stops reading once there are more entries than shown, each directory is expanded once
+fn push_entries(
+  tree: &mut Vec<TreeEntry>,
+  items: &[ItemInfo],
+  indent: &str,
+  depth: usize,
+  options: &ReadOptions,
+  visited: &mut Visited,
+) {
+  let items = items.iter().filter(|item| !item.is_parent()).collect::<Vec<_>>();
+  for (i, &item) in items.iter().enumerate() {
+    if tree.len() > MAX_TREE_ENTRIES {
+      return;
+    }
+    let is_last = i == items.len() - 1;
+    let branch = if is_last { "└── " } else { "├── " };
+    tree.push(TreeEntry { prefix: format!("{}{}", indent, branch), item: item.clone() });
+    let dir = match &item.item {
+      Item::Path(ItemPath::Dir(path)) => Some(path),
+      Item::Path(ItemPath::Symlink(ItemSymlink::Dir(path))) if options.follow_symlinks => Some(path),
+      _ => None,
+    };
+    if let Some(dir) = dir.filter(|dir| depth < TREE_DEPTH && visited.insert(dir)) {
+      let children = read_items(dir, options).unwrap_or_default();
+      let indent = format!("{}{}", indent, if is_last { "    " } else { "│   " });
+      push_entries(tree, &children, &indent, depth + 1, options, visited);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+
+  use super::*;
+  use crate::util::TestDir;
+
+  #[test]
+  fn test_build_tree() {
+    let dir = TestDir::new("test_tree");
+    fs::create_dir_all(dir.join("a/b/c")).unwrap();
+    fs::write(dir.join("a/x"), "").unwrap();
+    fs::write(dir.join("y"), "").unwrap();
+    let options = ReadOptions::default();
+    let tree = build_tree(&read_items(&dir, &options).unwrap(), &options);
+    let lines = tree
+      .iter()
+      .map(|entry| format!("{}{}", entry.prefix, entry.item.generate_filename().unwrap()))
+      .collect::<Vec<_>>();
+    assert_eq!(lines, ["├── a", "│   ├── b", "│   └── x", "└── y"]);
+
+    for i in 0..MAX_TREE_ENTRIES {
+      fs::write(dir.join(format!("z{}", i)), "").unwrap();
+    }
+    let tree = build_tree(&read_items(&dir, &options).unwrap(), &options);
+    assert_eq!(tree.len(), MAX_TREE_ENTRIES + 1);
+    assert_eq!(tree[MAX_TREE_ENTRIES].item.item, Item::Content("…".into()));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_build_tree_symlinks() {
+    let dir = TestDir::new("test_tree_symlinks");
+    fs::create_dir_all(dir.join("a/b")).unwrap();
+    fs::write(dir.join("a/b/f"), "").unwrap();
+    let _ = std::os::unix::fs::symlink(dir.join("a/b"), dir.join("link"));
+    let _ = std::os::unix::fs::symlink(&dir, dir.join("self"));
+    let lines = |options: &ReadOptions| {
+      build_tree(&read_items(&dir, options).unwrap(), options)
+        .iter()
+        .map(|entry| format!("{}{}", entry.prefix, entry.item.generate_filename().unwrap()))
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(lines(&ReadOptions::default()), ["├── a", "│   └── b", "├── link", "└── self"]);
+    let options = ReadOptions { follow_symlinks: true, ..ReadOptions::default() };
+    assert_eq!(lines(&options), ["├── a", "│   └── b", "├── link", "│   └── f", "└── self"]);
+  }
+}
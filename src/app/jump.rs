@@ -0,0 +1,58 @@
+use super::{CaseMode, Item, ItemInfo};
+
+#[derive(Debug, Default)]
+pub struct Jump {
+  pub text: String,
+  origin: usize,
+}
+
+impl Jump {
+  pub fn new(origin: usize) -> Self {
+    Jump { text: String::new(), origin }
+  }
+  /// Index selected before the jump started
+  pub fn origin(&self) -> usize {
+    self.origin
+  }
+  /// Index of the first item whose name contains `text`
+  pub fn find(&self, items: &[ItemInfo], case: CaseMode) -> Option<usize> {
+    if self.text.is_empty() {
+      return None;
+    }
+    items.iter().position(|item| {
+      let name = if let Item::Content(s) = &item.item { Some(s.clone()) } else { item.generate_filename() };
+      name.is_some_and(|name| case.contains(&name, &self.text))
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::ItemPath;
+
+  impl ItemInfo {
+    fn new_in_jump_tests(s: &str) -> Self {
+      Self { item: Item::Path(ItemPath::File(s.into())), index: None }
+    }
+  }
+
+  #[test]
+  fn test_find() {
+    let items = vec![
+      ItemInfo::new_in_jump_tests("/tmp/Cargo.toml"),
+      ItemInfo::new_in_jump_tests("/tmp/README.md"),
+      ItemInfo::new_in_jump_tests("/tmp/src"),
+    ];
+    let mut jump = Jump::new(2);
+    assert_eq!(jump.find(&items, CaseMode::Insensitive), None);
+    jump.text = "read".into();
+    assert_eq!(jump.find(&items, CaseMode::Insensitive), Some(1));
+    jump.text = "R".into();
+    assert_eq!(jump.find(&items, CaseMode::Insensitive), Some(0));
+    assert_eq!(jump.find(&items, CaseMode::Smart), Some(1));
+    jump.text = "xyz".into();
+    assert_eq!(jump.find(&items, CaseMode::Insensitive), None);
+    assert_eq!(jump.origin(), 2);
+  }
+}
@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Operations that can be bound to keys in normal mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Command {
+  MoveDown,
+  MoveUp,
+  MoveParent,
+  MoveChild,
+  /// Finish without changing directory
+  Quit,
+  /// Finish and change to the working directory
+  Confirm,
+  /// Finish and print the selected path without changing directory
+  Print,
+  /// Show the keys until another key is pressed
+  Help,
+  /// Read the preview of the selected entry now
+  Preview,
+}
+
+impl Command {
+  pub const ALL: [Command; 9] = [
+    Command::MoveDown,
+    Command::MoveUp,
+    Command::MoveParent,
+    Command::MoveChild,
+    Command::Quit,
+    Command::Confirm,
+    Command::Print,
+    Command::Help,
+    Command::Preview,
+  ];
+
+  /// Name used in `_ED_KEYMAP`
+  pub fn name(&self) -> &'static str {
+    match self {
+      Command::MoveDown => "move_down",
+      Command::MoveUp => "move_up",
+      Command::MoveParent => "move_parent",
+      Command::MoveChild => "move_child",
+      Command::Quit => "quit",
+      Command::Confirm => "confirm",
+      Command::Print => "print",
+      Command::Help => "help",
+      Command::Preview => "preview",
+    }
+  }
+  /// Shown in the help
+  pub fn description(&self) -> &'static str {
+    match self {
+      Command::MoveDown => "Move down",
+      Command::MoveUp => "Move up",
+      Command::MoveParent => "Move parent directory",
+      Command::MoveChild => "Move child directory",
+      Command::Quit => "Exit and return to original directory",
+      Command::Confirm => "Change directory to current directory",
+      Command::Print => "Print the selected path without cd",
+      Command::Help => "Show/hide this help",
+      Command::Preview => "Preview the selected entry now",
+    }
+  }
+  fn default_keys(&self) -> &'static [&'static str] {
+    match self {
+      Command::MoveDown => &["j", "Down"],
+      Command::MoveUp => &["k", "Up"],
+      Command::MoveParent => &["h", "Left"],
+      Command::MoveChild => &["l", "Right"],
+      Command::Quit => &["q", "Esc"],
+      Command::Confirm => &["c", ";", "Enter"],
+      Command::Print => &["p"],
+      Command::Help => &["?"],
+      Command::Preview => &["Ctrl+p"],
+    }
+  }
+}
+
+#[derive(Debug)]
+pub struct KeyMap {
+  commands: HashMap<KeyEvent, Command>,
+  /// Keys of each command in the order they were given
+  bindings: HashMap<Command, Vec<KeyEvent>>,
+}
+
+impl Default for KeyMap {
+  fn default() -> Self {
+    Self::new(None).unwrap()
+  }
+}
+
+impl KeyMap {
+  /// `spec` is like `move_down=n|Down,move_up=e`, listed commands lose their default keys
+  pub fn new(spec: Option<&str>) -> anyhow::Result<Self> {
+    let mut bindings: HashMap<Command, Vec<KeyEvent>> = HashMap::new();
+    for command in Command::ALL {
+      bindings.insert(command, command.default_keys().iter().map(|key| parse_key(key).unwrap()).collect());
+    }
+    for entry in spec.unwrap_or_default().split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+      let (name, keys) = entry.split_once('=').with_context(|| format!("Expected `command=key`: {}", entry))?;
+      let command = Command::ALL
+        .into_iter()
+        .find(|command| command.name() == name.trim())
+        .with_context(|| format!("Unknown command: {}", name.trim()))?;
+      let keys = keys.split('|').map(|key| parse_key(key.trim())).collect::<anyhow::Result<Vec<_>>>()?;
+      // Always exits before the keymap is looked up
+      if keys.contains(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)) {
+        bail!("Ctrl+c cannot be bound: {}", entry);
+      }
+      bindings.insert(command, keys);
+    }
+    let commands = bindings.iter().flat_map(|(command, keys)| keys.iter().map(move |key| (*key, *command))).collect();
+    Ok(KeyMap { commands, bindings })
+  }
+
+  pub fn get(&self, key: &KeyEvent) -> Option<Command> {
+    self.commands.get(&normalize(key.code, key.modifiers)).copied()
+  }
+  /// `None` if `name` is not a key
+  pub fn is_bound(&self, name: &str) -> Option<bool> {
+    parse_key(name).ok().map(|key| self.commands.contains_key(&key))
+  }
+  /// Names of the keys that run `command`, a key bound to several commands runs only one of them
+  pub fn keys(&self, command: Command) -> Vec<String> {
+    let keys = self.bindings.get(&command).map(Vec::as_slice).unwrap_or_default();
+    keys.iter().filter(|key| self.get(key) == Some(command)).map(key_name).collect()
+  }
+}
+
+/// Shift is part of the character itself, e.g. `S`
+fn normalize(code: KeyCode, mut modifiers: KeyModifiers) -> KeyEvent {
+  if let KeyCode::Char(_) = code {
+    modifiers.remove(KeyModifiers::SHIFT);
+  }
+  KeyEvent::new(code, modifiers)
+}
+
+/// A single character or a key name, optionally prefixed with `Ctrl+` or `Alt+`
+fn parse_key(s: &str) -> anyhow::Result<KeyEvent> {
+  let mut modifiers = KeyModifiers::NONE;
+  let mut rest = s;
+  loop {
+    if let Some(r) = rest.strip_prefix("Ctrl+") {
+      modifiers.insert(KeyModifiers::CONTROL);
+      rest = r;
+    } else if let Some(r) = rest.strip_prefix("Alt+") {
+      modifiers.insert(KeyModifiers::ALT);
+      rest = r;
+    } else {
+      break;
+    }
+  }
+  let mut chars = rest.chars();
+  let code = match (chars.next(), chars.next()) {
+    (Some(c), None) => KeyCode::Char(c),
+    _ => match rest {
+      "Up" => KeyCode::Up,
+      "Down" => KeyCode::Down,
+      "Left" => KeyCode::Left,
+      "Right" => KeyCode::Right,
+      "Enter" => KeyCode::Enter,
+      "Esc" => KeyCode::Esc,
+      "Backspace" => KeyCode::Backspace,
+      "Tab" => KeyCode::Tab,
+      "Space" => KeyCode::Char(' '),
+      "Home" => KeyCode::Home,
+      "End" => KeyCode::End,
+      "PageUp" => KeyCode::PageUp,
+      "PageDown" => KeyCode::PageDown,
+      "Insert" => KeyCode::Insert,
+      "Delete" => KeyCode::Delete,
+      _ => bail!("Unknown key: {}", s),
+    },
+  };
+  Ok(normalize(code, modifiers))
+}
+
+/// The inverse of `parse_key`
+fn key_name(key: &KeyEvent) -> String {
+  let mut name = String::new();
+  if key.modifiers.contains(KeyModifiers::CONTROL) {
+    name.push_str("Ctrl+");
+  }
+  if key.modifiers.contains(KeyModifiers::ALT) {
+    name.push_str("Alt+");
+  }
+  match key.code {
+    KeyCode::Char(' ') => name.push_str("Space"),
+    KeyCode::Char(c) => name.push(c),
+    code => name.push_str(&format!("{:?}", code)),
+  }
+  name
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_key() {
+    assert_eq!(parse_key("j").unwrap(), KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE));
+    assert_eq!(parse_key("Ctrl+n").unwrap(), KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL));
+    assert_eq!(parse_key("PageDown").unwrap(), KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE));
+    assert!(parse_key("Foo").is_err());
+  }
+
+  #[test]
+  fn test_key_name() {
+    for name in ["j", "?", "Ctrl+n", "Ctrl+Alt+x", "PageDown", "Space", "Esc"] {
+      assert_eq!(key_name(&parse_key(name).unwrap()), name);
+    }
+  }
+
+  #[test]
+  fn test_keymap() {
+    let keymap = KeyMap::default();
+    assert_eq!(keymap.get(&KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE)), Some(Command::MoveDown));
+    assert_eq!(keymap.get(&KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)), Some(Command::Confirm));
+    assert_eq!(keymap.get(&KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE)), Some(Command::Print));
+    assert_eq!(keymap.get(&KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)), None);
+
+    let keymap = KeyMap::new(Some("move_down=n|Down, move_up=e")).unwrap();
+    assert_eq!(keymap.get(&KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)), Some(Command::MoveDown));
+    assert_eq!(keymap.get(&KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE)), None);
+    assert_eq!(keymap.get(&KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE)), Some(Command::MoveUp));
+    assert_eq!(keymap.get(&KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE)), Some(Command::MoveChild));
+
+    assert_eq!(keymap.keys(Command::MoveDown), ["n", "Down"]);
+    assert_eq!(keymap.keys(Command::Confirm), ["c", ";", "Enter"]);
+    assert_eq!(keymap.keys(Command::Preview), ["Ctrl+p"]);
+    assert_eq!(keymap.is_bound("n"), Some(true));
+    assert_eq!(keymap.is_bound("j"), Some(false));
+    assert_eq!(keymap.is_bound("number"), None);
+
+    let keymap = KeyMap::new(Some("preview=Space")).unwrap();
+    assert_eq!(keymap.get(&KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE)), Some(Command::Preview));
+
+    let keymap = KeyMap::new(Some("quit=j")).unwrap();
+    assert_eq!(keymap.keys(Command::Quit).len() + keymap.keys(Command::MoveDown).len(), 2);
+
+    assert!(KeyMap::new(Some("jump=x")).is_err());
+    assert!(KeyMap::new(Some("confirm=Ctrl+c")).is_err());
+    assert!(KeyMap::new(Some("move_down")).is_err());
+  }
+}
@@ -0,0 +1,106 @@
+use std::{
+  path::{Path, PathBuf},
+  sync::mpsc::{self, Receiver, Sender},
+  thread,
+  time::{Duration, Instant},
+};
+
+use super::{build_tree, read_items, ItemInfo, PreviewCommand, PreviewMode, ReadOptions, TreeEntry};
+
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The path, its entries and the tree when the preview is a tree
+type Loaded = (PathBuf, anyhow::Result<Vec<ItemInfo>>, Option<Vec<TreeEntry>>);
+
+/// Reads directories and runs preview commands on a background thread, only the latest request is kept
+#[derive(Debug)]
+pub struct Loader {
+  sender: Sender<Loaded>,
+  receiver: Receiver<Loaded>,
+  pending: Option<(PathBuf, Instant)>,
+}
+
+impl Default for Loader {
+  fn default() -> Self {
+    let (sender, receiver) = mpsc::channel();
+    Loader { sender, receiver, pending: None }
+  }
+}
+
+impl Loader {
+  /// Results of earlier requests are discarded when they arrive
+  pub fn request(&mut self, path: PathBuf, options: ReadOptions) {
+    self.spawn(path, move |path| {
+      let items = read_items(path, &options);
+      let tree = match &items {
+        Ok(items) if options.preview_mode == PreviewMode::Tree => Some(build_tree(items, &options)),
+        _ => None,
+      };
+      (items, tree)
+    });
+  }
+  /// The preview of the file `item` by `command`, which may take a while
+  pub fn request_preview(&mut self, item: ItemInfo, command: PreviewCommand, options: ReadOptions) {
+    let path = if let Some(path) = item.get_path() { path } else { return };
+    self.spawn(path, move |_| (command.preview(&item, &options), None));
+  }
+  fn spawn<F>(&mut self, path: PathBuf, load: F)
+  where
+    F: FnOnce(&Path) -> (anyhow::Result<Vec<ItemInfo>>, Option<Vec<TreeEntry>>) + Send + 'static,
+  {
+    let sender = self.sender.clone();
+    self.pending = Some((path.clone(), Instant::now()));
+    thread::spawn(move || {
+      let (items, tree) = load(&path);
+      // The receiver is gone when the app has finished
+      let _ = sender.send((path, items, tree));
+    });
+  }
+  pub fn cancel(&mut self) {
+    self.pending = None;
+  }
+  /// Path being read
+  pub fn pending(&self) -> Option<&Path> {
+    self.pending.as_ref().map(|(path, _)| path.as_path())
+  }
+  /// Wait up to `timeout` for the pending request
+  pub fn receive(&mut self, timeout: Duration) -> Option<Loaded> {
+    let deadline = Instant::now() + timeout;
+    while let Some((pending, _)) = &self.pending {
+      let loaded = self.receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())).ok()?;
+      if &loaded.0 == pending {
+        self.pending = None;
+        return Some(loaded);
+      }
+    }
+    None
+  }
+  pub fn spinner(&self) -> char {
+    let elapsed = self.pending.as_ref().map_or(Duration::ZERO, |(_, started)| started.elapsed());
+    SPINNER[(elapsed.as_millis() / SPINNER_INTERVAL.as_millis()) as usize % SPINNER.len()]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::util::TestDir;
+
+  #[test]
+  fn test_receive() {
+    let dir = TestDir::new("test_loader");
+    std::fs::create_dir_all(dir.join("a")).unwrap();
+    std::fs::create_dir_all(dir.join("b")).unwrap();
+    let mut loader = Loader::default();
+    loader.request(dir.join("a"), ReadOptions::default());
+    loader.request(dir.to_path_buf(), ReadOptions::default());
+    assert_eq!(loader.pending(), Some(&*dir));
+    let (path, items, tree) = loader.receive(Duration::from_secs(5)).unwrap();
+    assert_eq!(path, *dir);
+    assert_eq!(items.unwrap().len(), 2);
+    assert!(tree.is_none());
+    assert_eq!(loader.pending(), None);
+    assert!(loader.receive(Duration::ZERO).is_none());
+  }
+}
@@ -0,0 +1,106 @@
+use std::{
+  collections::VecDeque,
+  time::{Duration, Instant},
+};
+
+use tui::style::Color;
+
+/// How long a message stays in the status line unless a key is pressed first
+pub const MESSAGE_DURATION: Duration = Duration::from_secs(4);
+/// Older messages are dropped
+const MAX_MESSAGES: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+  Info,
+  Warn,
+  Error,
+}
+
+impl Severity {
+  pub fn color(&self) -> Color {
+    match self {
+      Severity::Info => Color::Green,
+      Severity::Warn => Color::Yellow,
+      Severity::Error => Color::Red,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+  pub text: String,
+  pub severity: Severity,
+  /// When it was added
+  pub at: Instant,
+}
+
+/// Messages for the status line, of which the latest is shown until it expires
+#[derive(Debug, Default)]
+pub struct Messages(VecDeque<Message>);
+
+impl Messages {
+  pub fn info(&mut self, text: impl Into<String>) {
+    self.push(Severity::Info, text.into());
+  }
+  pub fn warn(&mut self, text: impl Into<String>) {
+    self.push(Severity::Warn, text.into());
+  }
+  pub fn error(&mut self, text: impl Into<String>) {
+    self.push(Severity::Error, text.into());
+  }
+  fn push(&mut self, severity: Severity, text: String) {
+    if self.0.len() == MAX_MESSAGES {
+      self.0.pop_front();
+    }
+    self.0.push_back(Message { text, severity, at: Instant::now() });
+  }
+  pub fn latest(&self) -> Option<&Message> {
+    self.0.back()
+  }
+  pub fn text(&self) -> Option<&str> {
+    self.latest().map(|message| message.text.as_str())
+  }
+  pub fn clear(&mut self) {
+    self.0.clear();
+  }
+  /// Drop the messages shown for `MESSAGE_DURATION` by `now`
+  pub fn expire(&mut self, now: Instant) {
+    self.0.retain(|message| now.duration_since(message.at) < MESSAGE_DURATION);
+  }
+  /// Time left until the latest message expires, to redraw without it
+  pub fn wait(&self) -> Option<Duration> {
+    self.latest().map(|message| MESSAGE_DURATION.saturating_sub(message.at.elapsed()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_messages() {
+    let mut messages = Messages::default();
+    assert_eq!(messages.latest(), None);
+    assert_eq!(messages.wait(), None);
+    messages.info("Copied");
+    messages.error("Failed");
+    assert_eq!(messages.text(), Some("Failed"));
+    assert_eq!(messages.latest().unwrap().severity.color(), Color::Red);
+    assert!(messages.wait().is_some_and(|wait| wait <= MESSAGE_DURATION));
+
+    let at = messages.latest().unwrap().at;
+    messages.expire(at);
+    assert_eq!(messages.text(), Some("Failed"));
+    messages.expire(at + MESSAGE_DURATION);
+    assert_eq!(messages.latest(), None);
+
+    for i in 0..=MAX_MESSAGES {
+      messages.warn(i.to_string());
+    }
+    assert_eq!(messages.0.len(), MAX_MESSAGES);
+    assert_eq!(messages.text(), Some(MAX_MESSAGES.to_string().as_str()));
+    messages.clear();
+    assert_eq!(messages.latest(), None);
+  }
+}
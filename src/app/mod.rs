@@ -1,13 +1,70 @@
 mod _app;
+mod ansi;
+mod case;
+mod clipboard;
+mod columns;
+mod dir_size;
+mod editor;
+mod file_colors;
+mod file_op;
+mod filter;
+mod help;
+mod history;
+mod input;
 mod item;
+mod jump;
+mod keymap;
+mod loader;
+mod message;
+mod mouse;
+mod opener;
+mod preview_trigger;
+mod quick_select;
+mod register;
 mod run;
 mod search;
+mod selection;
 mod state;
+mod status;
+mod symlink;
+mod theme;
+mod truncate;
 mod ui;
+mod walk;
+mod watcher;
 
-pub use self::_app::{app, App, AppMode};
-pub use self::item::{read_items, Item, ItemInfo, ItemPath};
+pub use self::_app::{app, pick_directory, pick_directory_in, App, AppMode, DEFAULT_PREVIEW_DELAY};
+pub use self::case::CaseMode;
+pub use self::clipboard::Clipboard;
+pub use self::columns::ColumnRatios;
+pub use self::dir_size::{DirSize, DirSizes};
+pub use self::file_colors::{FileCategory, FileColors, Only};
+pub use self::file_op::{Operation, Removal};
+pub use self::filter::Filter;
+pub use self::help::usage;
+pub use self::history::History;
+pub use self::input::{expand_home, Input, InputKind};
+pub use self::item::{
+  build_tree, icon, read_items, read_list, Item, ItemInfo, ItemPath, PreviewCommand, PreviewCommands, PreviewMode,
+  ReadOptions, SortMode, Thumbnail, TreeEntry, DEFAULT_MAX_PREVIEW_BYTES, DEFAULT_PREVIEW_LINES,
+  PREVIEW_COMMAND_TIMEOUT,
+};
+pub use self::jump::Jump;
+pub use self::keymap::{Command, KeyMap};
+pub use self::loader::Loader;
+pub use self::message::{Messages, Severity};
+pub use self::mouse::Areas;
+pub use self::preview_trigger::PreviewTrigger;
+pub use self::quick_select::QuickSelect;
+pub use self::register::{Register, RegisterKind};
 pub use self::run::run;
 pub use self::search::Search;
-pub use self::state::{State, StatefulList};
-pub use self::ui::ui;
+pub use self::selection::InitialSelection;
+pub use self::state::{list_offset, State, StatefulList};
+pub use self::status::{disk_space, Status};
+pub use self::symlink::SymlinkNavigation;
+pub use self::theme::Theme;
+pub use self::truncate::Truncation;
+pub use self::ui::{ui, DEFAULT_COMPACT_WIDTH};
+pub use self::walk::{walk, Visited};
+pub use self::watcher::DirWatcher;
@@ -0,0 +1,219 @@
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use tui::layout::Rect;
+use unicode_width::UnicodeWidthStr;
+
+use super::{ui::header_path, App, AppMode, Item};
+use crate::config::SavedColumnRatios;
+
+/// Lines of the file contents scrolled by one step of the wheel
+const SCROLL_LINES: isize = 3;
+
+/// Where the blocks were drawn last, to find what is under the mouse
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Areas {
+  /// The whole pane, half of the terminal while split
+  pub pane: Rect,
+  pub header: Rect,
+  pub grandparent: Rect,
+  pub parent: Rect,
+  pub current: Rect,
+  /// `None` while the preview is hidden
+  pub child: Option<Rect>,
+}
+
+/// Clicking the header moves to that ancestor, clicking an entry selects it or enters it when already selected,
+/// dragging a border between blocks resizes them
+pub fn handle(app: &mut App, mouse: MouseEvent) -> anyhow::Result<()> {
+  let (x, y) = (mouse.column, mouse.row);
+  // The other pane of the split view takes the focus when clicked or scrolled
+  let is_pointed =
+    matches!(mouse.kind, MouseEventKind::Down(_) | MouseEventKind::ScrollDown | MouseEventKind::ScrollUp);
+  if is_pointed && app.split.is_some() && !contains(app.areas.pane, x, y) {
+    app.toggle_focus()?;
+  }
+  let areas = app.areas;
+  let is_contents = matches!(app.child_items.items.first().map(|item| &item.item), Some(Item::Content(_)));
+  match mouse.kind {
+    MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+      let is_down = mouse.kind == MouseEventKind::ScrollDown;
+      if is_contents && areas.child.is_some_and(|area| contains(area, x, y)) {
+        app.scroll_preview(if is_down { SCROLL_LINES } else { -SCROLL_LINES });
+      } else if is_down {
+        app.move_next()?;
+      } else {
+        app.move_previous()?;
+      }
+    }
+    MouseEventKind::Down(MouseButton::Left) => {
+      if y == areas.header.y && contains(areas.header, x, y) {
+        let display = header_path(app, areas.header.width);
+        let home = home::home_dir();
+        match breadcrumb(&display, &app.wd, home.as_deref(), (x - areas.header.x) as usize) {
+          Some(path) if path != app.wd => app.move_to(path)?,
+          _ => {}
+        }
+      } else if let Some(border) = border_at(&shown_blocks(app), x, y) {
+        app.dragged_border = Some(border);
+      } else if let Some(i) = row(areas.grandparent, app.grandparent_items.offset, x, y) {
+        select_in_parent(app, app.grandparent_items.items.get(i).and_then(|item| item.get_path()))?;
+      } else if let Some(i) = row(areas.parent, app.parent_items.offset, x, y) {
+        select_in_parent(app, app.parent_items.items.get(i).and_then(|item| item.get_path()))?;
+      } else if let Some(i) = row(areas.current, current_offset(app), x, y) {
+        let (len, selected) = match app.judge_mode() {
+          AppMode::Normal => (app.items.items.len(), app.items.state.selected()),
+          AppMode::Search => (app.search.list.len(), app.search.state.selected()),
+        };
+        if selected == Some(i) {
+          app.move_child()?;
+        } else if i < len {
+          app.move_index(i)?;
+        }
+      } else if let Some(i) = areas.child.and_then(|area| row(area, app.child_items.offset, x, y)) {
+        let path = match app.get_child_tree() {
+          _ if is_contents => None,
+          Some(tree) => tree.get(i).and_then(|entry| entry.item.get_path()),
+          None => app.child_items.items.get(i).and_then(|item| item.get_path()),
+        };
+        if is_contents || path.is_some() {
+          app.move_child()?;
+        }
+        // Not entered when it is still being read or the link was followed, nor deeper in the tree
+        if let Some(path) = path.filter(|path| path.parent() == Some(app.wd.as_path())) {
+          app.select_path(&path)?;
+        }
+      }
+    }
+    MouseEventKind::Drag(MouseButton::Left) => {
+      let blocks = shown_blocks(app);
+      let border = app.dragged_border.filter(|i| i + 1 < blocks.len());
+      if let Some(i) = border {
+        let (left, right) = (blocks[i], blocks[i + 1]);
+        let width = (x + 1).saturating_sub(left.x);
+        app.columns.resize(app.show_grandparent, app.show_preview, i, width, left.width + right.width);
+      }
+    }
+    MouseEventKind::Up(MouseButton::Left) => {
+      let is_resized = app.dragged_border.take().is_some();
+      if is_resized && app.config.is_save_column_ratios() {
+        if let Err(e) = SavedColumnRatios::write(&app.columns.to_string()) {
+          app.messages.error(format!("Failed to save the column ratios: {}", e));
+        }
+      }
+    }
+    _ => {}
+  }
+  Ok(())
+}
+
+/// Areas of the blocks from the left
+fn shown_blocks(app: &App) -> Vec<Rect> {
+  let areas = app.areas;
+  [app.show_grandparent.then_some(areas.grandparent), Some(areas.parent), Some(areas.current), areas.child]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Index of the block whose right border is at the column, the last block has none to drag
+fn border_at(blocks: &[Rect], x: u16, y: u16) -> Option<usize> {
+  let (_, blocks) = blocks.split_last()?;
+  blocks.iter().position(|area| area.width > 0 && contains(*area, x, y) && x == area.x + area.width - 1)
+}
+
+fn contains(area: Rect, x: u16, y: u16) -> bool {
+  x >= area.x && x < area.x + area.width && y >= area.y && y < area.y + area.height
+}
+
+/// Index of the entry drawn at `y`, which may be past the last one
+fn row(area: Rect, offset: usize, x: u16, y: u16) -> Option<usize> {
+  contains(area, x, y).then(|| offset + (y - area.y) as usize)
+}
+
+fn current_offset(app: &App) -> usize {
+  match app.judge_mode() {
+    AppMode::Normal => app.items.offset,
+    AppMode::Search => app.search.offset,
+  }
+}
+
+/// Move to the directory containing `path` and select it, or to `path` itself when it is a root such as a drive
+fn select_in_parent(app: &mut App, path: Option<PathBuf>) -> anyhow::Result<()> {
+  let path = if let Some(path) = path.filter(|path| !path.as_os_str().is_empty()) { path } else { return Ok(()) };
+  match path.parent() {
+    Some(dir) => {
+      app.move_to(dir)?;
+      app.select_path(&path)
+    }
+    None => app.move_to(path),
+  }
+}
+
+/// The path of the component of `display` under `column`, each component owns the separator after it
+fn breadcrumb(display: &str, wd: &Path, home: Option<&Path>, column: usize) -> Option<PathBuf> {
+  let components = display.split(MAIN_SEPARATOR).collect::<Vec<_>>();
+  let last = components.len() - 1;
+  let mut end = 0;
+  let i = components.iter().position(|component| {
+    end += component.width() + 1;
+    column < end
+  })?;
+  // Components are counted from the end since the middle ones may be elided
+  match components[i] {
+    "…" => None,
+    _ if i == last => Some(wd.into()),
+    "~" if i == 0 => home.map(Path::to_path_buf),
+    component if i == 0 && !component.starts_with('…') => wd.ancestors().last().map(Path::to_path_buf),
+    _ => wd.ancestors().nth(last - i).map(Path::to_path_buf),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[cfg(unix)]
+  #[test]
+  fn test_breadcrumb() {
+    let wd = Path::new("/usr/local/share/doc");
+    let at = |display, column| breadcrumb(display, wd, None, column);
+    assert_eq!(at("/usr/local/share/doc", 0), Some("/".into()));
+    assert_eq!(at("/usr/local/share/doc", 2), Some("/usr".into()));
+    assert_eq!(at("/usr/local/share/doc", 4), Some("/usr".into()));
+    assert_eq!(at("/usr/local/share/doc", 5), Some("/usr/local".into()));
+    assert_eq!(at("/usr/local/share/doc", 18), Some(wd.into()));
+    assert_eq!(at("/…/share/doc", 0), Some("/".into()));
+    assert_eq!(at("/…/share/doc", 1), None);
+    assert_eq!(at("/…/share/doc", 4), Some("/usr/local/share".into()));
+    assert_eq!(at("…are/doc", 2), Some("/usr/local/share".into()));
+
+    // Columns of wide characters belong to their component
+    let wd = Path::new("/写真/旅行");
+    assert_eq!(breadcrumb("/写真/旅行", wd, None, 4), Some("/写真".into()));
+    assert_eq!(breadcrumb("/写真/旅行", wd, None, 6), Some(wd.into()));
+
+    let wd = Path::new("/home/user/app");
+    assert_eq!(breadcrumb("~/app", wd, Some(Path::new("/home/user")), 0), Some("/home/user".into()));
+  }
+
+  #[test]
+  fn test_border_at() {
+    let blocks = [Rect::new(0, 1, 10, 5), Rect::new(10, 1, 20, 5), Rect::new(30, 1, 10, 5)];
+    assert_eq!(border_at(&blocks, 9, 3), Some(0));
+    assert_eq!(border_at(&blocks, 29, 1), Some(1));
+    assert_eq!(border_at(&blocks, 39, 3), None);
+    assert_eq!(border_at(&blocks, 8, 3), None);
+    assert_eq!(border_at(&blocks, 9, 6), None);
+    assert_eq!(border_at(&[], 9, 3), None);
+  }
+
+  #[test]
+  fn test_row() {
+    let area = Rect::new(10, 5, 20, 4);
+    assert_eq!(row(area, 3, 10, 5), Some(3));
+    assert_eq!(row(area, 3, 29, 8), Some(6));
+    assert_eq!(row(area, 3, 30, 8), None);
+    assert_eq!(row(area, 3, 10, 9), None);
+  }
+}
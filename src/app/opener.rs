@@ -0,0 +1,137 @@
+#[cfg(not(any(target_os = "macos", windows)))]
+use std::env;
+use std::{
+  io,
+  path::Path,
+  process::{Command, Stdio},
+};
+
+#[cfg(not(any(target_os = "macos", windows)))]
+use super::editor::in_path;
+
+/// Open `path` with the default application without waiting for it
+pub fn open(path: &Path) -> io::Result<()> {
+  spawn(command(path))
+}
+
+/// Open the file manager in `dir`, with `selected` highlighted where the platform allows it
+pub fn reveal(dir: &Path, selected: Option<&Path>) -> io::Result<()> {
+  spawn(reveal_command(dir, selected))
+}
+
+fn spawn(mut command: Command) -> io::Result<()> {
+  command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn().map(|_| ())
+}
+
+/// A directory opened with the default application is shown in the file manager
+fn reveal_command(dir: &Path, selected: Option<&Path>) -> Command {
+  match selected.filter(|_| can_select()) {
+    Some(path) => select_command(path),
+    None => command(dir),
+  }
+}
+
+#[cfg(any(target_os = "macos", windows))]
+fn can_select() -> bool {
+  true
+}
+
+/// `ShowItems` of the freedesktop file manager interface is called with `dbus-send` within a session bus
+#[cfg(not(any(target_os = "macos", windows)))]
+fn can_select() -> bool {
+  let has_bus = env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some()
+    || env::var_os("XDG_RUNTIME_DIR").is_some_and(|dir| Path::new(&dir).join("bus").exists());
+  has_bus && in_path("dbus-send")
+}
+
+fn select_command(path: &Path) -> Command {
+  #[cfg(target_os = "macos")]
+  let command = {
+    let mut command = Command::new("open");
+    command.arg("-R").arg(path);
+    command
+  };
+  #[cfg(windows)]
+  let command = {
+    use std::os::windows::process::CommandExt;
+
+    // Explorer does not take the path quoted together with the switch
+    let mut command = Command::new("explorer");
+    command.raw_arg(format!("/select,\"{}\"", path.display()));
+    command
+  };
+  #[cfg(not(any(target_os = "macos", windows)))]
+  let command = {
+    let mut command = Command::new("dbus-send");
+    command
+      .args(["--session", "--type=method_call", "--dest=org.freedesktop.FileManager1"])
+      .args(["/org/freedesktop/FileManager1", "org.freedesktop.FileManager1.ShowItems"])
+      .arg(format!("array:string:{}", file_uri(path)))
+      .arg("string:");
+    command
+  };
+  command
+}
+
+/// `file://` followed by the path with the bytes other than unreserved characters and `/` percent-encoded
+#[cfg(not(any(target_os = "macos", windows)))]
+fn file_uri(path: &Path) -> String {
+  use std::os::unix::ffi::OsStrExt;
+
+  path.as_os_str().as_bytes().iter().fold("file://".to_string(), |mut uri, &b| {
+    if b.is_ascii_alphanumeric() || b"/-._~".contains(&b) {
+      uri.push(b as char);
+    } else {
+      uri.push_str(&format!("%{:02X}", b));
+    }
+    uri
+  })
+}
+
+fn command(path: &Path) -> Command {
+  #[cfg(target_os = "macos")]
+  let mut command = Command::new("open");
+  #[cfg(windows)]
+  let mut command = {
+    // The empty argument is the window title
+    let mut command = Command::new("cmd");
+    command.args(["/C", "start", ""]);
+    command
+  };
+  #[cfg(not(any(target_os = "macos", windows)))]
+  let mut command = Command::new("xdg-open");
+  command.arg(path);
+  command
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn test_command() {
+    let command = command(Path::new("/tmp/a.pdf"));
+    assert_eq!(command.get_program(), "xdg-open");
+    assert_eq!(command.get_args().collect::<Vec<_>>(), ["/tmp/a.pdf"]);
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn test_reveal_command() {
+    let command = reveal_command(Path::new("/tmp"), None);
+    assert_eq!(command.get_program(), "xdg-open");
+    assert_eq!(command.get_args().collect::<Vec<_>>(), ["/tmp"]);
+
+    let command = select_command(Path::new("/tmp/a b.pdf"));
+    assert_eq!(command.get_program(), "dbus-send");
+    assert_eq!(command.get_args().nth(5).unwrap(), "array:string:file:///tmp/a%20b.pdf");
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn test_file_uri() {
+    assert_eq!(file_uri(Path::new("/home/user/a_b-c.txt")), "file:///home/user/a_b-c.txt");
+    assert_eq!(file_uri(Path::new("/tmp/100% é")), "file:///tmp/100%25%20%C3%A9");
+  }
+}
@@ -0,0 +1,24 @@
+use serde::Deserialize;
+
+/// When the preview of the selected entry is read
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewTrigger {
+  /// Moving through the list waits for `_ED_PREVIEW_DELAY`, entering a directory or jumping reads it at once
+  #[default]
+  Auto,
+  /// Every change of the selection waits for `_ED_PREVIEW_DELAY`
+  Debounced,
+  /// Only the `preview` command reads it, for slow or networked filesystems
+  Manual,
+}
+
+impl PreviewTrigger {
+  pub fn name(&self) -> &'static str {
+    match self {
+      PreviewTrigger::Auto => "auto",
+      PreviewTrigger::Debounced => "debounced",
+      PreviewTrigger::Manual => "manual",
+    }
+  }
+}
@@ -0,0 +1,61 @@
+use std::time::{Duration, Instant};
+
+use super::{CaseMode, ItemInfo};
+
+/// Letters typed within this time of each other make one prefix
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Type-ahead selection by the start of names, with the letters no key is bound to
+#[derive(Debug, Default)]
+pub struct QuickSelect {
+  text: String,
+  last: Option<Instant>,
+}
+
+impl QuickSelect {
+  /// Index of the entry to select when `c` is typed at `now` with `current` selected
+  ///
+  /// The same letter again moves on to the next entry starting with it, another letter extends the prefix
+  pub fn find(&mut self, c: char, items: &[ItemInfo], current: usize, case: CaseMode, now: Instant) -> Option<usize> {
+    let is_recent = self.last.is_some_and(|last| now.duration_since(last) < TYPE_AHEAD_TIMEOUT);
+    self.last = Some(now);
+    let is_next = if is_recent && self.text.chars().any(|typed| typed != c) {
+      self.text.push(c);
+      false
+    } else {
+      self.text = c.to_string();
+      true
+    };
+    let start = if is_next { current + 1 } else { current };
+    (0..items.len())
+      .map(|i| (start + i) % items.len())
+      .find(|&i| items[i].generate_filename().is_some_and(|name| case.starts_with(&name, &self.text)))
+  }
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::{Item, ItemPath};
+
+  #[test]
+  fn test_find() {
+    let items = ["bin", "Build", "lib", "src", "sbin"]
+      .map(|name| ItemInfo { item: Item::Path(ItemPath::Dir(name.into())), index: None });
+    let mut quick = QuickSelect::default();
+    let at = |i: u32| Instant::now() + TYPE_AHEAD_TIMEOUT * 2 * i;
+    assert_eq!(quick.find('b', &items, 0, CaseMode::Smart, at(0)), Some(1));
+    assert_eq!(quick.find('b', &items, 1, CaseMode::Smart, at(0)), Some(0));
+    assert_eq!(quick.find('B', &items, 0, CaseMode::Smart, at(1)), Some(1));
+    assert_eq!(quick.find('b', &items, 0, CaseMode::Sensitive, at(2)), Some(0));
+    // Extended before the timeout
+    assert_eq!(quick.find('b', &items, 3, CaseMode::Smart, at(3)), Some(0));
+    assert_eq!(quick.find('i', &items, 0, CaseMode::Smart, at(3)), Some(0));
+    assert_eq!(quick.text(), "bi");
+    assert_eq!(quick.find('s', &items, 0, CaseMode::Smart, at(4)), Some(3));
+    assert_eq!(quick.find('x', &items, 3, CaseMode::Smart, at(5)), None);
+  }
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterKind {
+  Copy,
+  Cut,
+}
+
+/// Paths yanked or cut, kept until pasted
+#[derive(Debug)]
+pub struct Register {
+  pub kind: RegisterKind,
+  pub paths: Vec<PathBuf>,
+}
+
+impl Register {
+  pub fn new(kind: RegisterKind, paths: Vec<PathBuf>) -> Self {
+    Register { kind, paths }
+  }
+  pub fn describe(&self) -> String {
+    let verb = match self.kind {
+      RegisterKind::Copy => "Yanked",
+      RegisterKind::Cut => "Cut",
+    };
+    match self.paths.as_slice() {
+      [path] => format!("{} {}", verb, path.file_name().unwrap_or_default().to_string_lossy()),
+      paths => format!("{} {} items", verb, paths.len()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_describe() {
+    let register = Register::new(RegisterKind::Copy, vec!["/tmp/a.txt".into()]);
+    assert_eq!(register.describe(), "Yanked a.txt");
+    let register = Register::new(RegisterKind::Cut, vec!["/tmp/a".into(), "/tmp/b".into()]);
+    assert_eq!(register.describe(), "Cut 2 items");
+  }
+}
@@ -1,55 +1,230 @@
-use std::process::Command;
+use std::{
+  io,
+  path::{PathBuf, MAIN_SEPARATOR},
+  process,
+  time::{Duration, Instant},
+};
 
-use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::{
+  event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+  execute,
+  terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use log::{error, info, warn, LevelFilter};
 use tui::{backend::Backend, Terminal};
 
-use crate::{action::Action, Log};
+use crate::{
+  action::{Action, EnterAction},
+  Log,
+};
 
-use super::{App, AppMode};
+use super::{App, AppMode, Command, Input, InputKind, Severity};
 
-pub fn run<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> anyhow::Result<Action> {
+/// Redraw interval while a directory is being read
+const LOADING_TICK: Duration = Duration::from_millis(100);
+/// Redraw interval otherwise, so that results of background work show up without a key
+const IDLE_TICK: Duration = Duration::from_millis(250);
+
+pub fn run<B: Backend + io::Write>(terminal: &mut Terminal<B>, app: &mut App) -> anyhow::Result<Action> {
   if app.config.is_log() {
-    Log::init();
+    if let Err(e) = Log::init(LevelFilter::Debug) {
+      app.messages.error(format!("Failed to create the log: {}", e));
+    }
   }
+  // First key of a two-key command
+  let mut pending: Option<char> = None;
+  // Digits typed before `G`
+  let mut count: Option<usize> = None;
+  // What was logged last
+  let mut logged: (Option<PathBuf>, Option<Instant>) = (None, None);
   loop {
-    terminal.draw(|f| super::ui(f, &mut app))?;
-    if let Ok(Event::Key(key)) = event::read() {
+    if logged.0.as_ref() != Some(&app.wd) {
+      info!("Moved to {}", app.wd.display());
+      logged.0 = Some(app.wd.clone());
+    }
+    app.messages.expire(Instant::now());
+    if let Some(message) = app.messages.latest().filter(|message| logged.1 != Some(message.at)) {
+      match message.severity {
+        Severity::Info => info!("Message: {}", message.text),
+        Severity::Warn => warn!("Message: {}", message.text),
+        Severity::Error => error!("Message: {}", message.text),
+      }
+      logged.1 = Some(message.at);
+    }
+    app.receive_preview()?;
+    app.receive_items(Duration::ZERO)?;
+    app.dir_sizes.receive();
+    app.receive_changes()?;
+    terminal.draw(|f| super::ui(f, app))?;
+    // Keep the loading indicator and the count moving until they finish, and wake up for the deferred preview
+    let is_busy = app.loader.pending().is_some() || app.dir_sizes.is_walking();
+    let timeout = [is_busy.then_some(LOADING_TICK), app.preview_wait(), app.watcher.wait(), app.messages.wait()]
+      .into_iter()
+      .flatten()
+      .min()
+      .unwrap_or(IDLE_TICK);
+    if !event::poll(timeout)? {
+      continue;
+    }
+    let event = event::read();
+    if let Ok(Event::Mouse(mouse)) = event {
+      // Typing in the status line or jumping is not interrupted, and the blocks under the help are not clicked
+      if app.input.is_none() && app.jump.is_none() && !app.show_help {
+        super::mouse::handle(app, mouse)?;
+      }
+      continue;
+    }
+    if let Ok(Event::Key(key)) = event {
       if key.kind == KeyEventKind::Release {
         continue;
       }
-      if app.config.is_log() {
-        Log::write(&app, &key);
+      Log::write(app, &key);
+      // Any key only closes the help
+      if app.show_help {
+        app.show_help = false;
+        continue;
+      }
+      app.messages.clear();
+      if let Some(input) = app.input.as_mut() {
+        if input.kind.is_confirm() {
+          match key.code {
+            KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => return Ok(Action::Keep),
+            KeyCode::Char('y') if input.kind == InputKind::Quit => return Ok(Action::Keep),
+            KeyCode::Char('y') => app.submit_input()?,
+            KeyCode::Char('n') | KeyCode::Esc => app.input = None,
+            _ if input.kind.is_modal() => {}
+            _ => app.input = None,
+          }
+          continue;
+        }
+        match key.code {
+          KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => return Ok(Action::Keep),
+          KeyCode::Esc => app.input = None,
+          KeyCode::Enter => app.submit_input()?,
+          KeyCode::Tab if input.kind == InputKind::Path => input.complete(&app.wd, app.read_options.show_hidden),
+          KeyCode::Char(c) => input.push(c),
+          KeyCode::Backspace => input.pop(),
+          _ => {}
+        }
+        continue;
+      }
+      if let Some(jump) = app.jump.as_mut() {
+        match key.code {
+          KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => return Ok(Action::Keep),
+          KeyCode::Esc => app.cancel_jump()?,
+          KeyCode::Enter => app.commit_jump()?,
+          KeyCode::Char(c) => {
+            jump.text.push(c);
+            app.update_jump_effect();
+          }
+          KeyCode::Backspace => {
+            jump.text.pop();
+            app.update_jump_effect();
+          }
+          _ => {}
+        }
+        continue;
+      }
+      if let Some(prefix) = pending.take() {
+        if let KeyCode::Char(c) = key.code {
+          match prefix {
+            'm' => app.save_bookmark(c),
+            '\'' => app.move_bookmark(c)?,
+            'g' if c == 'g' => app.move_home()?,
+            _ => {}
+          }
+        }
+        continue;
       }
       match app.mode {
         AppMode::Normal => {
+          if key.code == KeyCode::Char('c') && key.modifiers == KeyModifiers::CONTROL {
+            return Ok(Action::Keep);
+          }
+          // Any key other than a digit or `G` cancels the count
+          let typed_count = count.take();
+          // Configurable keys take precedence over the fixed ones below
+          if let Some(command) = app.keymap.get(&key) {
+            if let Some(action) = execute(app, command)? {
+              return Ok(action);
+            }
+            continue;
+          }
           match key.code {
-            // finish
-            KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => return Ok(Action::Keep),
-            KeyCode::Char('q') => return Ok(Action::Keep),
-            KeyCode::Esc => return Ok(Action::Keep),
-
-            // change directory
-            KeyCode::Char('c') => return Ok(Action::Change(app.wd)),
-            KeyCode::Char(';') => return Ok(Action::Change(app.wd)),
-            KeyCode::Enter => return Ok(Action::Change(app.wd)),
-
             // move
             KeyCode::Home => app.move_home()?,
             KeyCode::End => app.move_end()?,
             KeyCode::PageUp => app.move_page_up()?,
             KeyCode::PageDown => app.move_page_down()?,
-            KeyCode::Char('j') => app.move_next()?,
-            KeyCode::Down => app.move_next()?,
-            KeyCode::Char('k') => app.move_previous()?,
-            KeyCode::Up => app.move_previous()?,
-            KeyCode::Char('h') => app.move_parent()?,
-            KeyCode::Left => app.move_parent()?,
-            KeyCode::Char('l') => app.move_child()?,
-            KeyCode::Right => app.move_child()?,
+            KeyCode::Char('g') => pending = Some('g'),
+            KeyCode::Char('G') => match typed_count {
+              Some(n) => app.move_index(n.saturating_sub(1))?,
+              None => app.move_end()?,
+            },
+            KeyCode::Char(c @ '0'..='9') if typed_count.is_some() || c != '0' => {
+              let digit = c.to_digit(10).unwrap() as usize;
+              count = Some(typed_count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+            }
 
             // search
             KeyCode::Char('s') if key.modifiers == KeyModifiers::CONTROL => app.mode = AppMode::Search,
             KeyCode::Insert => app.mode = AppMode::Search,
+            KeyCode::Char('/') => app.start_jump(),
+            KeyCode::Char('f') => app.start_filter(),
+            KeyCode::Char(':') => app.start_path_input(format!("{}{}", app.generate_wd_str(), MAIN_SEPARATOR)),
+            KeyCode::Char('~') => app.start_path_input(format!("~{}", MAIN_SEPARATOR)),
+            KeyCode::Char('H') => app.move_home_dir()?,
+            KeyCode::Char('-') => app.move_back()?,
+            KeyCode::Tab => app.move_forward()?,
+            KeyCode::Char('C') => app.toggle_case()?,
+
+            // toggle hidden files
+            KeyCode::Char('.') => app.toggle_hidden()?,
+            KeyCode::Char('i') => app.toggle_gitignore()?,
+            KeyCode::Char('D') => app.toggle_dirs_only()?,
+            KeyCode::Char('T') => app.cycle_category()?,
+
+            // preview
+            KeyCode::Char('z') => app.toggle_preview()?,
+            KeyCode::Char('Z') => app.toggle_grandparent(),
+            KeyCode::Char('|') => app.toggle_compact(),
+            KeyCode::Char('J') => app.scroll_preview(1),
+            KeyCode::Char('K') => app.scroll_preview(-1),
+            KeyCode::Char('d') if key.modifiers == KeyModifiers::CONTROL => {
+              app.scroll_preview((app.list_height / 2).max(1) as isize)
+            }
+            KeyCode::Char('u') if key.modifiers == KeyModifiers::CONTROL => {
+              app.scroll_preview(-((app.list_height / 2).max(1) as isize))
+            }
+
+            // sort
+            KeyCode::Char('S') => app.change_sort()?,
+            KeyCode::Char('F') => app.toggle_dirs_first()?,
+
+            // size
+            KeyCode::Char('s') => app.measure_selected(),
+
+            // mark
+            KeyCode::Char(' ') => app.toggle_mark()?,
+            KeyCode::Char('a') if key.modifiers == KeyModifiers::CONTROL => app.mark_all(),
+            KeyCode::Char('v') => app.invert_marks(),
+
+            // file operation
+            KeyCode::Char('a') => app.start_create_dir(),
+            KeyCode::Char('r') => app.start_rename(),
+            KeyCode::Char('d') => app.start_delete(),
+            KeyCode::Char('y') => app.yank(),
+            KeyCode::Char('Y') => app.copy_path(),
+            KeyCode::Char('x') => app.cut(),
+            KeyCode::Char('P') => app.paste()?,
+            KeyCode::Char('R') => app.repeat_operation()?,
+
+            // split view
+            KeyCode::Char('W') => app.toggle_split()?,
+            KeyCode::Char('w') => app.toggle_focus()?,
+
+            // bookmark
+            KeyCode::Char(c @ ('m' | '\'')) => pending = Some(c),
             KeyCode::Backspace => {
               app.search.text.pop();
               app.update_search_effect()?;
@@ -60,28 +235,49 @@ pub fn run<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> anyhow::Resu
             }
 
             // Execute command
+            KeyCode::Char('e') => {
+              let is_opened = open_editor(terminal, app)?;
+              if is_opened && app.config.is_quit_on_open() {
+                return Ok(confirm(app));
+              }
+            }
+            KeyCode::Char('o') => {
+              let is_opened = app.open_selected();
+              if is_opened && app.config.is_quit_on_open() {
+                return Ok(confirm(app));
+              }
+            }
+            KeyCode::Char('O') => app.reveal_selected(),
             KeyCode::Char('V') => {
-              Command::new("code").arg(&app.wd).output()?;
+              process::Command::new("code").arg(&app.wd).output()?;
             }
             // KeyCode::Char('L') => {
             //   Command::new("lapce").arg(&app.wd).output()?;
             // }
 
-            // print selected filepath
-            KeyCode::Char('p') => return Ok(Action::Print(app.get_selected_filepath())),
-
+            // Letters bound to nothing above
+            KeyCode::Char(c)
+              if c.is_alphabetic() && !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+              app.quick_select(c)?
+            }
             _ => {}
           }
         }
         AppMode::Search => {
+          if key.code == KeyCode::Char('c') && key.modifiers == KeyModifiers::CONTROL {
+            return Ok(Action::Keep);
+          }
+          // Keys editing the search are not rebound
+          if !is_search_edit(&key) {
+            if let Some(command) = app.keymap.get(&key) {
+              if let Some(action) = execute(app, command)? {
+                return Ok(action);
+              }
+              continue;
+            }
+          }
           match key.code {
-            // finish
-            KeyCode::Char('c') if key.modifiers == KeyModifiers::CONTROL => return Ok(Action::Keep),
-            KeyCode::Esc => return Ok(Action::Keep),
-
-            // change directory
-            KeyCode::Enter => return Ok(Action::Change(app.wd)),
-
             // search
             KeyCode::Char('s') if key.modifiers == KeyModifiers::CONTROL => app.mode = AppMode::Normal,
             KeyCode::Insert => app.mode = AppMode::Normal,
@@ -105,10 +301,6 @@ pub fn run<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> anyhow::Resu
             KeyCode::End => app.move_end()?,
             KeyCode::PageUp => app.move_page_up()?,
             KeyCode::PageDown => app.move_page_down()?,
-            KeyCode::Down => app.move_next()?,
-            KeyCode::Up => app.move_previous()?,
-            KeyCode::Left => app.move_parent()?,
-            KeyCode::Right => app.move_child()?,
 
             _ => {}
           }
@@ -117,3 +309,105 @@ pub fn run<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> anyhow::Resu
     }
   }
 }
+
+/// `Some` when the app should finish
+fn execute(app: &mut App, command: Command) -> anyhow::Result<Option<Action>> {
+  match command {
+    Command::Quit if app.config.is_confirm_quit() => app.input = Some(Input::new(InputKind::Quit)),
+    Command::Quit => return Ok(Some(Action::Keep)),
+    Command::Confirm => return Ok(Some(confirm(app))),
+    Command::Print => return Ok(app.get_selected_path().map(Action::Print)),
+    Command::MoveDown => app.move_next()?,
+    Command::MoveUp => app.move_previous()?,
+    Command::MoveParent => app.move_parent()?,
+    Command::MoveChild => app.move_child()?,
+    Command::Help => app.show_help = true,
+    Command::Preview => app.preview_now()?,
+  }
+  Ok(None)
+}
+
+/// Change to the working directory, or print a path as `_ED_ENTER_ACTION` says
+fn confirm(app: &App) -> Action {
+  let change = Action::Change(app.confirm_path()).normalize(app.config.is_canonicalize_output());
+  match app.config.enter_action() {
+    EnterAction::Change => change,
+    EnterAction::Print => change.print_only(),
+    EnterAction::ChangeOrPrintForFiles => match app.get_selected_path() {
+      Some(path) if path.is_file() => Action::Print(path),
+      _ => change,
+    },
+  }
+}
+
+/// Leave the terminal to the editor until it exits, `true` when it exited successfully
+fn open_editor<B: Backend + io::Write>(terminal: &mut Terminal<B>, app: &mut App) -> anyhow::Result<bool> {
+  let path = match app.get_selected_path() {
+    Some(path) if path.is_file() => path,
+    _ => return Ok(false),
+  };
+  let command = if let Some(command) = super::editor::command() {
+    command
+  } else {
+    app.messages.warn("No editor found, set $EDITOR");
+    return Ok(false);
+  };
+
+  disable_raw_mode()?;
+  execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
+  terminal.show_cursor()?;
+
+  let status = process::Command::new(&command[0]).args(&command[1..]).arg(&path).status();
+
+  enable_raw_mode()?;
+  execute!(terminal.backend_mut(), EnterAlternateScreen, EnableMouseCapture)?;
+  terminal.clear()?;
+
+  let is_success = match status {
+    Ok(status) if !status.success() => {
+      app.messages.warn(format!("{} exited with {}", command[0], status));
+      false
+    }
+    Err(e) => {
+      app.messages.error(format!("Failed to run {}: {}", command[0], e));
+      false
+    }
+    Ok(_) => true,
+  };
+  // The file may have been changed
+  app.update_search_effect()?;
+  Ok(is_success)
+}
+
+fn is_search_edit(key: &KeyEvent) -> bool {
+  match key.code {
+    KeyCode::Char(_) => !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT),
+    KeyCode::Backspace | KeyCode::Delete => true,
+    _ => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+
+  use super::*;
+  use crate::util::{config, TestDir};
+
+  #[test]
+  fn test_confirm() {
+    let dir = TestDir::new("test_confirm");
+    fs::create_dir_all(dir.join("a")).unwrap();
+    fs::write(dir.join("b"), "").unwrap();
+    let confirm_on = |enter_action: &str, name: &str| {
+      let mut app =
+        App::with_config(config(&[("_ED_ENTER_ACTION", enter_action)]), Some(&dir), None, true, false).unwrap();
+      app.select_path(&dir.join(name)).unwrap();
+      confirm(&app)
+    };
+    assert!(matches!(confirm_on("change", "b"), Action::Change(path) if path == *dir));
+    assert!(matches!(confirm_on("print", "b"), Action::Print(path) if path == *dir));
+    assert!(matches!(confirm_on("change_or_print_for_files", "a"), Action::Change(path) if path == *dir));
+    assert!(matches!(confirm_on("change_or_print_for_files", "b"), Action::Print(path) if path == dir.join("b")));
+  }
+}
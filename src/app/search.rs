@@ -1,12 +1,14 @@
 use tui::widgets::ListState;
 
-use super::{ItemInfo, State};
+use super::{list_offset, ItemInfo, State};
 
 #[derive(Debug)]
 pub struct Search {
   pub text: String,
   pub list: Vec<ItemInfo>,
   pub state: ListState,
+  /// First visible row when last rendered
+  pub offset: usize,
 }
 
 #[allow(clippy::new_without_default)]
@@ -14,34 +16,55 @@ impl Search {
   pub fn new() -> Self {
     let mut state = ListState::default();
     state.select(Some(0));
-    Search { text: String::new(), list: vec![], state }
+    Search { text: String::new(), list: vec![], state, offset: 0 }
+  }
+  pub fn update_offset(&mut self, len: usize, height: u16) {
+    self.offset = list_offset(self.offset, self.state.selected(), len, height as usize);
   }
 }
 
 impl State for Search {
-  fn next(&mut self) -> usize {
+  fn next(&mut self, wrap: bool) -> usize {
+    let last = if let Some(last) = self.list.len().checked_sub(1) { last } else { return 0 };
     let i = match self.state.selected() {
-      Some(i) => {
-        if i >= self.list.len() - 1 {
+      Some(i) if i >= last => {
+        if wrap {
           0
         } else {
-          i + 1
+          last
         }
       }
+      Some(i) => i + 1,
       None => 0,
     };
     self.state.select(Some(i));
     i
   }
-  fn previous(&mut self) -> usize {
+  fn previous(&mut self, wrap: bool) -> usize {
+    let last = if let Some(last) = self.list.len().checked_sub(1) { last } else { return 0 };
     let i = match self.state.selected() {
-      Some(i) => {
-        if i == 0 {
-          self.list.len() - 1
-        } else {
-          i - 1
-        }
-      }
+      Some(0) if wrap => last,
+      Some(i) => i.saturating_sub(1).min(last),
+      None => 0,
+    };
+    self.state.select(Some(i));
+    i
+  }
+  fn page_down(&mut self, page: usize, wrap: bool) -> usize {
+    let last = if let Some(last) = self.list.len().checked_sub(1) { last } else { return 0 };
+    let i = match self.state.selected() {
+      Some(i) if i >= last && wrap => 0,
+      Some(i) => (i + page).min(last),
+      None => 0,
+    };
+    self.state.select(Some(i));
+    i
+  }
+  fn page_up(&mut self, page: usize, wrap: bool) -> usize {
+    let last = if let Some(last) = self.list.len().checked_sub(1) { last } else { return 0 };
+    let i = match self.state.selected() {
+      Some(0) if wrap => last,
+      Some(i) => i.saturating_sub(page).min(last),
       None => 0,
     };
     self.state.select(Some(i));
@@ -68,11 +91,11 @@ mod tests {
     let mut search = Search::new();
     search.list =
       vec![ItemInfo::new_in_search_tests("a"), ItemInfo::new_in_search_tests("b"), ItemInfo::new_in_search_tests("c")];
-    assert_eq!(search.next(), 1);
-    assert_eq!(search.next(), 2);
-    assert_eq!(search.next(), 0);
+    assert_eq!(search.next(true), 1);
+    assert_eq!(search.next(true), 2);
+    assert_eq!(search.next(true), 0);
     search.state.select(None);
-    assert_eq!(search.next(), 0);
+    assert_eq!(search.next(true), 0);
   }
 
   #[test]
@@ -80,11 +103,37 @@ mod tests {
     let mut search = Search::new();
     search.list =
       vec![ItemInfo::new_in_search_tests("a"), ItemInfo::new_in_search_tests("b"), ItemInfo::new_in_search_tests("c")];
-    assert_eq!(search.previous(), 2);
-    assert_eq!(search.previous(), 1);
-    assert_eq!(search.previous(), 0);
+    assert_eq!(search.previous(true), 2);
+    assert_eq!(search.previous(true), 1);
+    assert_eq!(search.previous(true), 0);
     search.state.select(None);
-    assert_eq!(search.previous(), 0);
+    assert_eq!(search.previous(true), 0);
+  }
+
+  #[test]
+  fn test_page_down() {
+    let mut search = Search::new();
+    search.list = ["a", "b", "c"].map(ItemInfo::new_in_search_tests).to_vec();
+    assert_eq!(search.page_down(5, true), 2);
+    assert_eq!(search.page_down(5, true), 0);
+  }
+
+  #[test]
+  fn test_page_up() {
+    let mut search = Search::new();
+    search.list = ["a", "b", "c"].map(ItemInfo::new_in_search_tests).to_vec();
+    assert_eq!(search.page_up(5, true), 2);
+    assert_eq!(search.page_up(5, true), 0);
+  }
+
+  #[test]
+  fn test_empty() {
+    let mut search = Search::new();
+    assert_eq!(search.next(true), 0);
+    assert_eq!(search.previous(true), 0);
+    assert_eq!(search.page_down(5, false), 0);
+    assert_eq!(search.page_up(5, false), 0);
+    assert_eq!(search.state.selected(), Some(0));
   }
 
   #[test]
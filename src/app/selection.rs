@@ -0,0 +1,61 @@
+use serde::Deserialize;
+
+use super::ItemInfo;
+
+/// Which entry is selected when the app starts
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InitialSelection {
+  #[default]
+  First,
+  /// The first directory or symbolic link to one
+  FirstDir,
+  Last,
+  /// The entry with this file name, only from `--select`
+  #[serde(skip)]
+  Name(String),
+}
+
+impl InitialSelection {
+  pub fn name(&self) -> &str {
+    match self {
+      InitialSelection::First => "first",
+      InitialSelection::FirstDir => "first_dir",
+      InitialSelection::Last => "last",
+      InitialSelection::Name(name) => name,
+    }
+  }
+  /// `None` when nothing matches, the first entry is selected then
+  pub fn index(&self, items: &[ItemInfo]) -> Option<usize> {
+    match self {
+      InitialSelection::First => None,
+      InitialSelection::FirstDir => items.iter().position(ItemInfo::is_dir),
+      InitialSelection::Last => items.len().checked_sub(1),
+      InitialSelection::Name(name) => {
+        items.iter().position(|item| item.generate_filename().as_deref() == Some(name.as_str()))
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::{Item, ItemPath};
+
+  #[test]
+  fn test_index() {
+    let items = [
+      ItemInfo { item: Item::Path(ItemPath::File("/a".into())), index: Some(0) },
+      ItemInfo { item: Item::create_dir("/b"), index: Some(1) },
+      ItemInfo { item: Item::Path(ItemPath::File("/c".into())), index: Some(2) },
+    ];
+    assert_eq!(InitialSelection::First.index(&items), None);
+    assert_eq!(InitialSelection::FirstDir.index(&items), Some(1));
+    assert_eq!(InitialSelection::Last.index(&items), Some(2));
+    assert_eq!(InitialSelection::Name("c".into()).index(&items), Some(2));
+    assert_eq!(InitialSelection::Name("d".into()).index(&items), None);
+    assert_eq!(InitialSelection::Last.index(&[]), None);
+    assert_eq!(InitialSelection::FirstDir.index(&items[..1]), None);
+  }
+}
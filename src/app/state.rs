@@ -1,10 +1,17 @@
+use std::mem;
+
 use tui::widgets::ListState;
 
-use super::ItemInfo;
+use super::{ItemInfo, ReadOptions};
 
+/// Without `wrap`, the selection stops at the first and last entries
+///
+/// Moving in an empty list changes nothing and returns `0`, a selection past the end is brought back into the list
 pub trait State {
-  fn next(&mut self) -> usize;
-  fn previous(&mut self) -> usize;
+  fn next(&mut self, wrap: bool) -> usize;
+  fn previous(&mut self, wrap: bool) -> usize;
+  fn page_down(&mut self, page: usize, wrap: bool) -> usize;
+  fn page_up(&mut self, page: usize, wrap: bool) -> usize;
   fn select(&mut self, index: usize);
 }
 
@@ -12,56 +19,118 @@ pub trait State {
 pub struct StatefulList {
   pub state: ListState,
   pub items: Vec<ItemInfo>,
+  /// First visible row when last rendered, `ListState` keeps its own private
+  pub offset: usize,
+}
+
+/// The first row `List` draws, given the one of the previous render and items of one line
+pub fn list_offset(offset: usize, selected: Option<usize>, len: usize, height: usize) -> usize {
+  if len == 0 || height == 0 {
+    return offset;
+  }
+  let start = offset.min(len - 1);
+  let selected = selected.unwrap_or(0).min(len - 1);
+  if selected >= start + height {
+    selected + 1 - height
+  } else {
+    start.min(selected)
+  }
 }
 
 impl StatefulList {
+  /// Keep the selected path if it still exists, otherwise clamp the index
+  pub fn replace_items(&mut self, items: Vec<ItemInfo>) {
+    self.map_items(|_| items);
+  }
+  /// `replace_items` with items made from the current ones, which are moved into `f` rather than cloned
+  pub fn map_items(&mut self, f: impl FnOnce(Vec<ItemInfo>) -> Vec<ItemInfo>) {
+    let selected_path = self.state.selected().and_then(|i| self.items.get(i)).and_then(|item| item.get_path());
+    let items = f(mem::take(&mut self.items));
+    let i = selected_path
+      .and_then(|path| items.iter().position(|item| item.get_path().as_ref() == Some(&path)))
+      .or_else(|| self.state.selected().map(|i| i.min(items.len().saturating_sub(1))));
+    self.items = items;
+    self.state.select(i);
+  }
+  /// Sort lists of paths in place keeping the selected entry, file contents are left as they are
+  pub fn sort_items(&mut self, options: &ReadOptions) {
+    if self.items.iter().any(|item| item.get_path().is_none() && !item.is_parent()) {
+      return;
+    }
+    self.map_items(|mut items| {
+      options.sort_items(&mut items);
+      items
+    });
+  }
   pub fn selected(&self) -> usize {
     self.state.selected().unwrap()
   }
+  /// Follow the `List` rendered from `len` of the items into `height` rows
+  pub fn update_offset(&mut self, len: usize, height: u16) {
+    self.offset = list_offset(self.offset, self.state.selected(), len, height as usize);
+  }
   pub fn unselect(&mut self) {
     self.state.select(None);
   }
   pub fn with_items(items: Vec<ItemInfo>) -> StatefulList {
     let mut state = ListState::default();
     state.select(Some(0));
-    StatefulList { state, items }
+    StatefulList { state, items, offset: 0 }
   }
   pub fn with_items_option(items: Vec<ItemInfo>, index: Option<usize>) -> StatefulList {
     let mut state = ListState::default();
     state.select(index);
-    StatefulList { state, items }
+    StatefulList { state, items, offset: 0 }
   }
   pub fn with_items_select(items: Vec<ItemInfo>, index: usize) -> StatefulList {
     let mut state = ListState::default();
     state.select(Some(index));
-    StatefulList { state, items }
+    StatefulList { state, items, offset: 0 }
   }
 }
 
 impl State for StatefulList {
-  fn next(&mut self) -> usize {
+  fn next(&mut self, wrap: bool) -> usize {
+    let last = if let Some(last) = self.items.len().checked_sub(1) { last } else { return 0 };
     let i = match self.state.selected() {
-      Some(i) => {
-        if i >= self.items.len() - 1 {
+      Some(i) if i >= last => {
+        if wrap {
           0
         } else {
-          i + 1
+          last
         }
       }
+      Some(i) => i + 1,
       None => 0,
     };
     self.state.select(Some(i));
     i
   }
-  fn previous(&mut self) -> usize {
+  fn previous(&mut self, wrap: bool) -> usize {
+    let last = if let Some(last) = self.items.len().checked_sub(1) { last } else { return 0 };
     let i = match self.state.selected() {
-      Some(i) => {
-        if i == 0 {
-          self.items.len() - 1
-        } else {
-          i - 1
-        }
-      }
+      Some(0) if wrap => last,
+      Some(i) => i.saturating_sub(1).min(last),
+      None => 0,
+    };
+    self.state.select(Some(i));
+    i
+  }
+  fn page_down(&mut self, page: usize, wrap: bool) -> usize {
+    let last = if let Some(last) = self.items.len().checked_sub(1) { last } else { return 0 };
+    let i = match self.state.selected() {
+      Some(i) if i >= last && wrap => 0,
+      Some(i) => (i + page).min(last),
+      None => 0,
+    };
+    self.state.select(Some(i));
+    i
+  }
+  fn page_up(&mut self, page: usize, wrap: bool) -> usize {
+    let last = if let Some(last) = self.items.len().checked_sub(1) { last } else { return 0 };
+    let i = match self.state.selected() {
+      Some(0) if wrap => last,
+      Some(i) => i.saturating_sub(page).min(last),
       None => 0,
     };
     self.state.select(Some(i));
@@ -83,6 +152,23 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_replace_items() {
+    use crate::app::ItemPath;
+    let path = |s: &str| ItemInfo { item: Item::Path(ItemPath::File(s.into())), index: None };
+    let mut state = StatefulList::with_items_select(vec![path(".a"), path("b"), path("c")], 1);
+    state.replace_items(vec![path("b"), path("c")]);
+    assert_eq!(state.selected(), 0);
+    state.select(1);
+    state.replace_items(vec![path("a")]);
+    assert_eq!(state.selected(), 0);
+
+    let mut state = StatefulList::with_items_select(vec![path("a"), path("b"), path("c")], 2);
+    state.map_items(|items| items.into_iter().skip(1).collect());
+    assert_eq!(state.selected(), 1);
+    assert_eq!(state.items.len(), 2);
+  }
+
   #[test]
   fn test_with_items_option() {
     let state = StatefulList::with_items_option(
@@ -103,20 +189,85 @@ mod tests {
   fn test_next() {
     let mut state =
       StatefulList::with_items(vec![ItemInfo::new_in_state_tests("a"), ItemInfo::new_in_state_tests("b")]);
-    assert_eq!(state.next(), 1);
-    assert_eq!(state.next(), 0);
+    assert_eq!(state.next(true), 1);
+    assert_eq!(state.next(true), 0);
     state.unselect();
-    assert_eq!(state.next(), 0);
+    assert_eq!(state.next(true), 0);
+    assert_eq!(state.next(false), 1);
+    assert_eq!(state.next(false), 1);
   }
 
   #[test]
   fn test_previous() {
     let mut state =
       StatefulList::with_items(vec![ItemInfo::new_in_state_tests("a"), ItemInfo::new_in_state_tests("b")]);
-    assert_eq!(state.previous(), 1);
-    assert_eq!(state.previous(), 0);
+    assert_eq!(state.previous(true), 1);
+    assert_eq!(state.previous(true), 0);
     state.unselect();
-    assert_eq!(state.previous(), 0);
+    assert_eq!(state.previous(true), 0);
+    assert_eq!(state.previous(false), 0);
+  }
+
+  #[test]
+  fn test_page_down() {
+    let mut state = StatefulList::with_items(["a", "b", "c", "d"].map(ItemInfo::new_in_state_tests).to_vec());
+    assert_eq!(state.page_down(2, true), 2);
+    assert_eq!(state.page_down(2, true), 3);
+    assert_eq!(state.page_down(2, true), 0);
+    state.select(3);
+    assert_eq!(state.page_down(2, false), 3);
+  }
+
+  #[test]
+  fn test_page_up() {
+    let mut state = StatefulList::with_items(["a", "b", "c", "d"].map(ItemInfo::new_in_state_tests).to_vec());
+    assert_eq!(state.page_up(2, true), 3);
+    assert_eq!(state.page_up(2, true), 1);
+    assert_eq!(state.page_up(2, true), 0);
+    assert_eq!(state.page_up(2, false), 0);
+  }
+
+  #[test]
+  fn test_empty() {
+    let mut state = StatefulList::with_items_option(vec![], None);
+    for wrap in [true, false] {
+      assert_eq!(state.next(wrap), 0);
+      assert_eq!(state.previous(wrap), 0);
+      assert_eq!(state.page_down(3, wrap), 0);
+      assert_eq!(state.page_up(3, wrap), 0);
+      assert_eq!(state.state.selected(), None);
+    }
+    let mut state = StatefulList::with_items(vec![]);
+    assert_eq!(state.next(true), 0);
+    assert_eq!(state.previous(false), 0);
+    assert_eq!(state.state.selected(), Some(0));
+  }
+
+  #[test]
+  fn test_single() {
+    let mut state = StatefulList::with_items(vec![ItemInfo::new_in_state_tests("a")]);
+    for wrap in [true, false] {
+      assert_eq!(state.next(wrap), 0);
+      assert_eq!(state.previous(wrap), 0);
+      assert_eq!(state.page_down(3, wrap), 0);
+      assert_eq!(state.page_up(3, wrap), 0);
+    }
+  }
+
+  #[test]
+  fn test_stays_in_bounds() {
+    let items = || ["a", "b", "c"].map(ItemInfo::new_in_state_tests).to_vec();
+    // Left past the end when the items were replaced without `replace_items`
+    let past_end = || StatefulList::with_items_select(items(), 5);
+    assert_eq!(past_end().next(true), 0);
+    assert_eq!(past_end().next(false), 2);
+    assert_eq!(past_end().previous(true), 2);
+    assert_eq!(past_end().previous(false), 2);
+    assert_eq!(past_end().page_down(2, false), 2);
+    assert_eq!(past_end().page_up(1, false), 2);
+    let mut state = past_end();
+    state.page_up(1, true);
+    assert!(state.selected() < state.items.len());
   }
 
   #[test]
@@ -126,4 +277,14 @@ mod tests {
     state.select(1);
     assert_eq!(state.selected(), 1);
   }
+
+  #[test]
+  fn test_list_offset() {
+    assert_eq!(list_offset(0, Some(2), 10, 5), 0);
+    assert_eq!(list_offset(0, Some(7), 10, 5), 3);
+    assert_eq!(list_offset(3, Some(1), 10, 5), 1);
+    assert_eq!(list_offset(3, Some(5), 10, 5), 3);
+    assert_eq!(list_offset(8, None, 3, 5), 0);
+    assert_eq!(list_offset(4, Some(0), 0, 5), 4);
+  }
 }
@@ -0,0 +1,101 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use crate::util::{format_size, DateFormat, SizeUnit};
+
+/// Metadata of the selected item, read again only when the selection changes
+#[derive(Debug, Default)]
+pub struct Status {
+  path: Option<PathBuf>,
+  text: String,
+  size_unit: SizeUnit,
+  date_format: DateFormat,
+}
+
+impl Status {
+  pub fn new(size_unit: SizeUnit, date_format: DateFormat) -> Self {
+    Status { size_unit, date_format, ..Default::default() }
+  }
+  pub fn get(&mut self, path: &Path) -> &str {
+    if self.path.as_deref() != Some(path) {
+      self.text = Self::generate(path, self.size_unit, &self.date_format);
+      self.path = Some(path.into());
+    }
+    &self.text
+  }
+  pub fn invalidate(&mut self) {
+    self.path = None;
+  }
+  fn generate(path: &Path, size_unit: SizeUnit, date_format: &DateFormat) -> String {
+    let metadata = match fs::metadata(path) {
+      Ok(metadata) => metadata,
+      Err(e) => return e.to_string(),
+    };
+    let mut fields = vec![];
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      fields.push(format_mode(metadata.permissions().mode(), metadata.is_dir()));
+    }
+    if metadata.is_dir() {
+      let count = fs::read_dir(path).map(|read_dir| read_dir.count()).unwrap_or(0);
+      fields.push(format!("{} items", count));
+    } else {
+      fields.push(format_size(metadata.len(), size_unit));
+    }
+    if let Ok(modified) = metadata.modified() {
+      fields.push(date_format.format(modified));
+    }
+    fields.join("  ")
+  }
+}
+
+/// Free and total space of the filesystem containing `path`, `None` when it cannot be read
+pub fn disk_space(path: &Path, size_unit: SizeUnit) -> Option<String> {
+  let free = fs2::available_space(path).ok()?;
+  let total = fs2::total_space(path).ok()?;
+  Some(format!("{} free of {}", format_size(free, size_unit), format_size(total, size_unit)))
+}
+
+/// `ls -l` style permission bits
+#[cfg(unix)]
+fn format_mode(mode: u32, is_dir: bool) -> String {
+  let mut s = String::from(if is_dir { "d" } else { "-" });
+  for shift in [6, 3, 0] {
+    let bits = mode >> shift;
+    s.push(if bits & 0b100 != 0 { 'r' } else { '-' });
+    s.push(if bits & 0b010 != 0 { 'w' } else { '-' });
+    s.push(if bits & 0b001 != 0 { 'x' } else { '-' });
+  }
+  s
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[cfg(unix)]
+  #[test]
+  fn test_format_mode() {
+    assert_eq!(format_mode(0o644, false), "-rw-r--r--");
+    assert_eq!(format_mode(0o40755, true), "drwxr-xr-x");
+  }
+
+  #[test]
+  fn test_invalidate() {
+    let mut status = Status::default();
+    status.get(Path::new("not_exist"));
+    assert!(status.path.is_some());
+    status.invalidate();
+    assert!(status.path.is_none());
+  }
+
+  #[test]
+  fn test_disk_space() {
+    let text = disk_space(&std::env::temp_dir(), SizeUnit::Binary).unwrap();
+    assert!(text.contains(" free of "));
+    assert_eq!(disk_space(Path::new("_easychangedirectory_not_exist"), SizeUnit::Binary), None);
+  }
+}
@@ -0,0 +1,21 @@
+use serde::Deserialize;
+
+/// Where entering a symbolic link to a directory leads
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkNavigation {
+  /// Stays on the path of the link, so that `h` goes back to the directory containing it, as `cd` in shells
+  #[default]
+  Logical,
+  /// Moves to the target, so that `h` goes to the parent of the target, as `cd -P`
+  Physical,
+}
+
+impl SymlinkNavigation {
+  pub fn name(&self) -> &'static str {
+    match self {
+      SymlinkNavigation::Logical => "logical",
+      SymlinkNavigation::Physical => "physical",
+    }
+  }
+}
@@ -0,0 +1,126 @@
+use anyhow::{bail, Context};
+use tui::style::{Color, Modifier, Style};
+
+use super::FileColors;
+
+/// Styles of the listing, unspecified elements keep the default colors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+  pub dir: Style,
+  pub file: Style,
+  pub symlink_dir: Style,
+  pub symlink_file: Style,
+  /// Selected entry of the working block
+  pub selection: Style,
+  /// Selected entries of the other blocks
+  pub highlight: Style,
+  pub header: Style,
+  /// Colors of files by type, `None` when all files have the same color
+  pub files: Option<FileColors>,
+}
+
+impl Default for Theme {
+  fn default() -> Self {
+    Theme {
+      dir: Style::default().fg(Color::Blue),
+      file: Style::default().fg(Color::Gray),
+      symlink_dir: Style::default().fg(Color::Cyan),
+      symlink_file: Style::default().fg(Color::LightCyan),
+      // The background fills the whole row of the working block
+      selection: Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD),
+      highlight: Style::default().fg(Color::Magenta),
+      header: Style::default().fg(Color::Yellow),
+      files: None,
+    }
+  }
+}
+
+impl Theme {
+  /// `spec` is like `dir=blue,selection=#ffffff/#303030`, the color after `/` is the background
+  pub fn new(spec: Option<&str>) -> anyhow::Result<Self> {
+    let mut theme = Theme::default();
+    for entry in spec.unwrap_or_default().split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+      let (name, colors) = entry.split_once('=').with_context(|| format!("Expected `element=color`: {}", entry))?;
+      let (fg, bg) = match colors.split_once('/') {
+        Some((fg, bg)) => (fg.trim(), Some(bg.trim())),
+        None => (colors.trim(), None),
+      };
+      let mut style = Style::default();
+      if !fg.is_empty() {
+        style = style.fg(parse_color(fg)?);
+      }
+      if let Some(bg) = bg {
+        style = style.bg(parse_color(bg)?);
+      }
+      match name.trim() {
+        "dir" => theme.dir = theme.dir.patch(style),
+        "file" => theme.file = theme.file.patch(style),
+        "symlink" => {
+          theme.symlink_dir = theme.symlink_dir.patch(style);
+          theme.symlink_file = theme.symlink_file.patch(style);
+        }
+        "selection" => theme.selection = theme.selection.patch(style),
+        "highlight" => theme.highlight = theme.highlight.patch(style),
+        "header" => theme.header = theme.header.patch(style),
+        name => bail!("Unknown element: {}", name),
+      }
+    }
+    Ok(theme)
+  }
+}
+
+/// A color name such as `blue` or `light_red`, or `#rrggbb`
+fn parse_color(s: &str) -> anyhow::Result<Color> {
+  if let Some(hex) = s.strip_prefix('#') {
+    let rgb =
+      u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 6).with_context(|| format!("Invalid color: {}", s))?;
+    return Ok(Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8));
+  }
+  Ok(match s.to_lowercase().replace(['_', '-'], "").as_str() {
+    "reset" => Color::Reset,
+    "black" => Color::Black,
+    "red" => Color::Red,
+    "green" => Color::Green,
+    "yellow" => Color::Yellow,
+    "blue" => Color::Blue,
+    "magenta" => Color::Magenta,
+    "cyan" => Color::Cyan,
+    "gray" | "grey" => Color::Gray,
+    "darkgray" | "darkgrey" => Color::DarkGray,
+    "lightred" => Color::LightRed,
+    "lightgreen" => Color::LightGreen,
+    "lightyellow" => Color::LightYellow,
+    "lightblue" => Color::LightBlue,
+    "lightmagenta" => Color::LightMagenta,
+    "lightcyan" => Color::LightCyan,
+    "white" => Color::White,
+    _ => bail!("Invalid color: {}", s),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_color() {
+    assert_eq!(parse_color("Blue").unwrap(), Color::Blue);
+    assert_eq!(parse_color("light_red").unwrap(), Color::LightRed);
+    assert_eq!(parse_color("#ff8000").unwrap(), Color::Rgb(255, 128, 0));
+    assert!(parse_color("#ff80").is_err());
+    assert!(parse_color("orange").is_err());
+  }
+
+  #[test]
+  fn test_theme() {
+    assert_eq!(Theme::new(None).unwrap(), Theme::default());
+    let theme = Theme::new(Some("dir=green, selection=/#303030, symlink=white")).unwrap();
+    assert_eq!(theme.dir, Style::default().fg(Color::Green));
+    assert_eq!(theme.selection, Theme::default().selection.bg(Color::Rgb(48, 48, 48)));
+    assert_eq!(theme.symlink_file, Style::default().fg(Color::White));
+    assert_eq!(theme.file, Theme::default().file);
+
+    assert!(Theme::new(Some("dirs=green")).is_err());
+    assert!(Theme::new(Some("dir")).is_err());
+  }
+}
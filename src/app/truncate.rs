@@ -0,0 +1,105 @@
+use std::borrow::Cow;
+
+use serde::Deserialize;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+const ELLIPSIS: char = '…';
+
+/// Where names wider than their column are cut
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Truncation {
+  /// Keeps the extension, e.g. `a_long…name.txt`
+  #[default]
+  Middle,
+  End,
+}
+
+impl Truncation {
+  pub fn name(&self) -> &'static str {
+    match self {
+      Truncation::Middle => "middle",
+      Truncation::End => "end",
+    }
+  }
+  /// `text` in at most `width` columns of the terminal, with `…` where it was cut
+  pub fn apply<'a>(&self, text: &'a str, width: usize) -> Cow<'a, str> {
+    if text.width() <= width {
+      return text.into();
+    }
+    let width = if let Some(width) = width.checked_sub(1) { width } else { return "".into() };
+    let (head, tail) = match self {
+      Truncation::End => (head(text, width), ""),
+      Truncation::Middle => match text.rfind('.').filter(|&i| i > 0) {
+        // The extension is kept whole if it leaves room for a few characters of the stem
+        Some(i) if text[i..].width() + 2 <= width => (head(&text[..i], width - text[i..].width()), &text[i..]),
+        _ => {
+          let head = head(text, width.div_ceil(2));
+          (head, tail(text, width - head.width()))
+        }
+      },
+    };
+    format!("{}{}{}", head, ELLIPSIS, tail).into()
+  }
+}
+
+/// The longest start of `text` within `width` columns, with the zero-width characters that follow it
+fn head(text: &str, width: usize) -> &str {
+  let mut total = 0;
+  for (i, c) in text.char_indices() {
+    total += c.width().unwrap_or(0);
+    if total > width {
+      return &text[..i];
+    }
+  }
+  text
+}
+
+/// The longest end of `text` within `width` columns that does not start with a zero-width character
+pub fn tail(text: &str, width: usize) -> &str {
+  let mut total = 0;
+  let mut start = text.len();
+  for (i, c) in text.char_indices().rev() {
+    total += c.width().unwrap_or(0);
+    if total > width {
+      break;
+    }
+    start = i;
+  }
+  // Combining marks without the character they belong to are dropped
+  text[start..].trim_start_matches(|c: char| c.width() == Some(0))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_apply() {
+    assert_eq!(Truncation::Middle.apply("short.txt", 9), "short.txt");
+    assert_eq!(Truncation::Middle.apply("a_long_file_name.txt", 12), "a_long_….txt");
+    assert_eq!(Truncation::Middle.apply("a_long_file_name.txt", 12).width(), 12);
+    assert_eq!(Truncation::End.apply("a_long_file_name.txt", 12), "a_long_file…");
+    assert_eq!(Truncation::Middle.apply("a_long_file_name", 9), "a_lo…name");
+    assert_eq!(Truncation::Middle.apply(".a_long_dotfile", 8), ".a_l…ile");
+    assert_eq!(Truncation::Middle.apply("name.longextension", 10), "name.…sion");
+    assert_eq!(Truncation::End.apply("abc", 1), "…");
+    assert_eq!(Truncation::End.apply("abc", 0), "");
+  }
+
+  #[test]
+  fn test_wide_characters() {
+    // Two columns each
+    let name = "日本語のファイル名.txt";
+    assert_eq!(name.width(), 22);
+    assert_eq!(Truncation::Middle.apply(name, 12), "日本語….txt");
+    assert_eq!(Truncation::Middle.apply(name, 13), "日本語の….txt");
+    assert_eq!(Truncation::End.apply(name, 12), "日本語のフ…");
+    assert!(Truncation::End.apply("🦀🦀🦀🦀", 6).width() <= 6);
+    assert_eq!(Truncation::Middle.apply("写真アルバム", 7), "写…バム");
+    // The accent stays with its letter
+    assert_eq!(Truncation::End.apply("cafe\u{301}s_menu", 6), "cafe\u{301}s…");
+    assert_eq!(tail("ae\u{301}", 1), "e\u{301}");
+    assert_eq!(tail("日\u{301}", 1), "");
+  }
+}
@@ -1,36 +1,62 @@
+use std::path::Path;
+
 use tui::{
   backend::Backend,
-  layout::{Constraint, Direction, Layout},
+  layout::{Alignment, Constraint, Direction, Layout, Rect},
   style::{Color, Modifier, Style},
-  text::Span,
-  widgets::{Block, Borders, List},
+  text::{Span, Spans},
+  widgets::{Block, Borders, Clear, List, Paragraph},
   widgets::{ListItem, ListState},
   Frame,
 };
 
-use super::{item::ItemSymlink, App, AppMode, Item, ItemInfo, ItemPath};
-use crate::Config;
+use unicode_width::UnicodeWidthStr;
+
+use super::{
+  ansi, help, icon, item::ItemSymlink, truncate, App, AppMode, DirSize, Item, ItemInfo, ItemPath, Theme, Thumbnail,
+  TreeEntry, Truncation,
+};
+use crate::{util::format_size, Config};
 
-/* Color
+/* Color, the ones marked with * can be changed with `_ED_THEME`
 - background: rgb(10, 10, 10)
 - border: gray
-- title: yellow
-- dir: blue
+- title: yellow *header
+- dir: blue *dir
 - search: green
-- file, content, none: gray
-- symlink: cyan
-- current-highlight: bold, underlined, bright
-- parent-highlight: magenta
+- file, content, none: gray *file
+- symlink: cyan, light cyan for files *symlink
+- current-highlight: bold, on dark gray across the block *selection
+- parent-highlight: magenta *highlight
 */
 
+/// Below this size the image description is shown instead
+const MIN_IMAGE_WIDTH: u16 = 8;
+const MIN_IMAGE_HEIGHT: u16 = 4;
+const READ_ONLY_MARK: &str = " [RO]";
+/// Panes narrower than this show only the working block
+pub const DEFAULT_COMPACT_WIDTH: u16 = 60;
+
 struct MyStyle;
 
 impl MyStyle {
   fn right_border<'a>() -> Block<'a> {
     Block::default().borders(Borders::RIGHT).border_style(Style::default().fg(Color::Gray))
   }
-  fn highlight_style() -> Style {
-    Style::default().fg(Color::Magenta)
+  /// Between the panes of the split view
+  fn left_border<'a>() -> Block<'a> {
+    Block::default().borders(Borders::LEFT).border_style(Style::default().fg(Color::Gray))
+  }
+  /// Background only, so the colors of the entry stay visible
+  fn marked_style() -> Style {
+    Style::default().bg(Color::DarkGray)
+  }
+  /// Shown instead of a list without entries, cannot be selected
+  fn empty<'a>() -> Paragraph<'a> {
+    Paragraph::new(Span::styled("<empty>", Style::default().fg(Color::DarkGray)))
+  }
+  fn loading<'a>(spinner: char) -> Paragraph<'a> {
+    Paragraph::new(Span::styled(format!("{} Loading...", spinner), Style::default().fg(Color::DarkGray)))
   }
 }
 
@@ -40,11 +66,43 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     f.render_widget(Block::default().style(Style::default().bg(Color::Rgb(10, 10, 10))), f.size());
   }
 
+  // panes, side by side when split
+  match app.split.take() {
+    Some(mut other) => {
+      let halves = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(f.size());
+      let right_area = MyStyle::left_border().inner(halves[1]);
+      f.render_widget(MyStyle::left_border(), halves[1]);
+      let (app_area, other_area) = if app.is_right_pane { (right_area, halves[0]) } else { (halves[0], right_area) };
+      render_pane(f, &mut other, other_area, false);
+      render_pane(f, app, app_area, true);
+      app.split = Some(other);
+    }
+    None => render_pane(f, app, f.size(), true),
+  }
+
+  // help---------------------------------------------------------
+  if app.show_help {
+    render_help(f, app);
+  }
+}
+
+/// The blocks of one pane within `area`, dimmed unless focused
+fn render_pane<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect, is_focused: bool) {
   // layout
+  app.areas.pane = area;
+  // A row for the message below the path, which a tenth of a short terminal does not leave
+  let header = if app.messages.latest().is_some() && area.height < 20 {
+    Constraint::Length(2)
+  } else {
+    Constraint::Percentage(10)
+  };
   let chunks = Layout::default()
     .direction(Direction::Vertical)
-    .constraints([Constraint::Percentage(10), Constraint::Max(100)])
-    .split(f.size());
+    .constraints([header, Constraint::Min(0), Constraint::Length(1)])
+    .split(area);
 
   // top----------------------------------------------------------
   let top_chunks = Layout::default()
@@ -53,89 +111,440 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     .split(chunks[0]);
 
   // show wd
-  f.render_widget(
-    Block::default().title(Span::styled(app.generate_wd_str(), Style::default().fg(Color::Yellow))),
-    top_chunks[0],
-  );
+  app.areas.header = top_chunks[0];
+  let header_style = if is_focused { app.theme.header } else { Style::default().fg(Color::DarkGray) };
+  let mut title = vec![Span::styled(header_path(app, top_chunks[0].width), header_style)];
+  if app.read_only {
+    title.push(Span::styled(READ_ONLY_MARK, Style::default().fg(Color::Red)));
+  }
+  f.render_widget(Block::default().title(Spans::from(title)), top_chunks[0]);
+
+  // message
+  if let Some(message) = app.messages.latest() {
+    let message_chunks =
+      Layout::default().constraints([Constraint::Length(1), Constraint::Min(0)]).split(top_chunks[0]);
+    let style = Style::default().fg(message.severity.color());
+    f.render_widget(Paragraph::new(Span::styled(message.text.as_str(), style)), message_chunks[1]);
+  }
 
   // search
-  let item = ItemInfo { item: Item::Search(app.search.text.clone()), index: Some(0) };
+  let text = if let Some(jump) = &app.jump {
+    format!("/{} ({})", jump.text, app.case.name())
+  } else if app.mode == AppMode::Search {
+    format!("{} ({})", app.search.text, app.case.name())
+  } else {
+    app.search.text.clone()
+  };
+  let item = ItemInfo { item: Item::Search(text), index: Some(0) };
   let search_items = vec![item];
-  let search_items = set_items(&search_items, app.config);
+  let search_items = set_items(&search_items, None, None, &app.config, &app.theme);
   let search_text = List::new(search_items).highlight_symbol("> ");
   let mut state = ListState::default();
-  if app.mode == AppMode::Normal {
-    state.select(None);
-  } else if app.mode == AppMode::Search {
+  if app.jump.is_some() || app.mode == AppMode::Search {
     state.select(Some(0));
+  } else {
+    state.select(None);
   }
   f.render_stateful_widget(search_text, top_chunks[1], &mut state);
 
   // bottom------------------------------------------------------
-  let bottom_chunks = Layout::default()
-    .direction(Direction::Horizontal)
-    .constraints([
-      Constraint::Percentage(20),
-      Constraint::Percentage(20),
-      Constraint::Max(100),
-      Constraint::Percentage(30),
-    ])
-    .split(chunks[1]);
+  let (grandparent_area, parent_area, current_area, child_area) = if app.is_compact() {
+    (None, None, chunks[1], None)
+  } else {
+    let constraints = app.columns.constraints(app.show_grandparent, app.show_preview);
+    let mut bottom_chunks =
+      Layout::default().direction(Direction::Horizontal).constraints(constraints).split(chunks[1]).into_iter();
+    let grandparent_area = if app.show_grandparent { bottom_chunks.next() } else { None };
+    let parent_area = bottom_chunks.next();
+    (grandparent_area, parent_area, bottom_chunks.next().unwrap(), bottom_chunks.next())
+  };
 
   // grandparent
-  let grandparent_items = set_items(&app.grandparent_items.items, app.config);
-  let grandparent_items =
-    List::new(grandparent_items).block(MyStyle::right_border()).highlight_style(MyStyle::highlight_style());
-  f.render_stateful_widget(grandparent_items, bottom_chunks[0], &mut app.grandparent_items.state);
+  app.areas.grandparent = grandparent_area.unwrap_or_default();
+  if let Some(area) = grandparent_area {
+    app.grandparent_items.update_offset(app.grandparent_items.items.len(), area.height);
+    let list = &app.grandparent_items;
+    let (items, mut state) = window(&list.items, list.offset, list.state.selected(), area.height);
+    let grandparent_items = set_items(items, Some(area.width.saturating_sub(1)), None, &app.config, &app.theme);
+    let grandparent_items =
+      List::new(grandparent_items).block(MyStyle::right_border()).highlight_style(app.theme.highlight);
+    f.render_stateful_widget(grandparent_items, area, &mut state);
+  }
 
   // parent
-  let parent_items = set_items(&app.parent_items.items, app.config);
-  let parent_items = List::new(parent_items).block(MyStyle::right_border()).highlight_style(MyStyle::highlight_style());
-  f.render_stateful_widget(parent_items, bottom_chunks[1], &mut app.parent_items.state);
+  app.areas.parent = parent_area.unwrap_or_default();
+  if let Some(area) = parent_area {
+    app.parent_items.update_offset(app.parent_items.items.len(), area.height);
+    let list = &app.parent_items;
+    let (items, mut state) = window(&list.items, list.offset, list.state.selected(), area.height);
+    let parent_items = set_items(items, Some(area.width.saturating_sub(1)), None, &app.config, &app.theme);
+    let parent_items = List::new(parent_items).block(MyStyle::right_border()).highlight_style(app.theme.highlight);
+    f.render_stateful_widget(parent_items, area, &mut state);
+  }
 
   // current
-  let (items, state) = match app.judge_mode() {
-    AppMode::Normal => (set_items(&app.items.items, app.config), &mut app.items.state),
-    AppMode::Search => (set_items(&app.search.list, app.config), &mut app.search.state),
+  app.list_height = current_area.height as usize;
+  app.areas.current = current_area;
+  match app.judge_mode() {
+    AppMode::Normal => app.items.update_offset(app.items.items.len(), current_area.height),
+    AppMode::Search => app.search.update_offset(app.search.list.len(), current_area.height),
+  }
+  let (items, offset, selected) = match app.judge_mode() {
+    AppMode::Normal => (&app.items.items, app.items.offset, app.items.state.selected()),
+    AppMode::Search => (&app.search.list, app.search.offset, app.search.state.selected()),
   };
-  let items = List::new(items)
-    .block(MyStyle::right_border())
-    .highlight_style(Style::default().add_modifier(Modifier::BOLD).add_modifier(Modifier::UNDERLINED))
-    .highlight_symbol("> ");
-  f.render_stateful_widget(items, bottom_chunks[2], state);
+  if items.is_empty() && app.loader.pending() == Some(app.wd.as_path()) {
+    f.render_widget(MyStyle::loading(app.loader.spinner()).block(MyStyle::right_border()), current_area);
+  } else if items.is_empty() {
+    f.render_widget(MyStyle::empty().block(MyStyle::right_border()), current_area);
+  } else {
+    let (items, mut state) = window(items, offset, selected, current_area.height);
+    // Less the border and the highlight symbol
+    let base = app.list.as_ref().map(|_| app.wd.as_path());
+    let items = set_items(items, Some(current_area.width.saturating_sub(3)), base, &app.config, &app.theme)
+      .into_iter()
+      .zip(items)
+      .map(|(list_item, item)| match item.get_path() {
+        Some(path) if app.marked.contains(&path) => list_item.style(MyStyle::marked_style()),
+        _ => list_item,
+      })
+      .collect::<Vec<_>>();
+    let selection = if is_focused { app.theme.selection } else { app.theme.highlight };
+    let items = List::new(items).block(MyStyle::right_border()).highlight_style(selection).highlight_symbol("> ");
+    f.render_stateful_widget(items, current_area, &mut state);
+  }
 
   // child
-  let child_items = set_items(&app.child_items.items, app.config);
-  let child_items = List::new(child_items).highlight_style(MyStyle::highlight_style());
-  f.render_stateful_widget(child_items, bottom_chunks[3], &mut app.child_items.state);
+  app.areas.child = child_area;
+  if let Some(area) = child_area {
+    app.clamp_preview_scroll(area.height);
+    let offset = app.preview_offset();
+    match app.child_items.items.first().map(|item| &item.item) {
+      Some(Item::Image(thumbnail)) if area.width >= MIN_IMAGE_WIDTH && area.height >= MIN_IMAGE_HEIGHT => {
+        f.render_widget(Paragraph::new(image_lines(thumbnail, area.width, area.height)), area);
+      }
+      Some(Item::Content(_)) => {
+        let (items, _) = window(&app.child_items.items, offset, None, area.height);
+        let child_items = if app.config.is_preview_line_numbers() {
+          let width = app.child_items.items.len().to_string().len();
+          set_numbered_items(items, width, &app.config, &app.theme)
+        } else {
+          set_items(items, None, None, &app.config, &app.theme)
+        };
+        f.render_widget(List::new(child_items), area);
+      }
+      None if app.loader.pending().is_some() && app.loader.pending() == app.get_selected_path().as_deref() => {
+        f.render_widget(MyStyle::loading(app.loader.spinner()), area)
+      }
+      None if app.preview_wait().is_none() && app.get_selected_path().is_some_and(|path| path.is_dir()) => {
+        f.render_widget(MyStyle::empty(), area)
+      }
+      Some(_) if app.get_child_tree().is_some() => {
+        // Clicks are mapped from the top of the tree
+        app.child_items.offset = 0;
+        let tree = app.get_child_tree().unwrap_or_default();
+        f.render_widget(List::new(set_tree_items(tree, area.width, &app.config, &app.theme)), area);
+      }
+      _ => {
+        app.child_items.update_offset(app.child_items.items.len(), area.height);
+        let list = &app.child_items;
+        let (items, mut state) = window(&list.items, list.offset, list.state.selected(), area.height);
+        let child_items = set_items(items, Some(area.width), None, &app.config, &app.theme);
+        let child_items = List::new(child_items).highlight_style(app.theme.highlight);
+        f.render_stateful_widget(child_items, area, &mut state);
+      }
+    }
+  }
+
+  // status-------------------------------------------------------
+  render_status(f, app, chunks[2]);
+}
+
+/// The keys in a box at the center, over the blocks
+fn render_help<B: Backend>(f: &mut Frame<B>, app: &App) {
+  let rows = help::rows(&app.keymap);
+  let key_width = rows.iter().map(|(keys, _)| keys.width()).max().unwrap_or(0);
+  let lines = rows
+    .into_iter()
+    .map(|(keys, description)| {
+      let padding = " ".repeat(key_width - keys.width() + 2);
+      Spans::from(vec![Span::styled(keys + &padding, Style::default().fg(Color::Yellow)), Span::raw(description)])
+    })
+    .collect::<Vec<_>>();
+  let width = lines.iter().map(Spans::width).max().unwrap_or(0) as u16 + 2;
+  let area = centered(f.size(), width, lines.len() as u16 + 2);
+  let block = Block::default()
+    .borders(Borders::ALL)
+    .border_style(Style::default().fg(Color::Gray))
+    .title(Span::styled("Keys (any key to close)", app.theme.header));
+  f.render_widget(Clear, area);
+  f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// A rectangle of at most `width` and `height` in the middle of `area`
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+  let width = width.min(area.width);
+  let height = height.min(area.height);
+  Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height)
+}
+
+fn render_status<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+  if let Some(input) = &app.input {
+    let prompt = input.kind.prompt();
+    let cursor_x = area.x + (prompt.width() + input.text.width()) as u16;
+    let mut text = vec![Span::styled(prompt, Style::default().fg(Color::Yellow)), Span::raw(input.text.as_str())];
+    if let Some(error) = &input.error {
+      text.push(Span::styled(format!("  {}", error), Style::default().fg(Color::Red)));
+    }
+    let text = Spans::from(text);
+    f.render_widget(Paragraph::new(text), area);
+    if !input.kind.is_confirm() {
+      f.set_cursor(cursor_x, area.y);
+    }
+    return;
+  }
+  let mut spans = vec![];
+  if let Some(filter) = &app.filter {
+    spans.push(Span::styled(format!("filter: {}  ", filter.text), Style::default().fg(Color::Yellow)));
+  }
+  if let Some(path) = app.get_selected_path() {
+    spans.push(Span::styled(app.status.get(&path).to_string(), Style::default().fg(Color::Gray)));
+    let size = match app.dir_sizes.get(&path) {
+      Some(DirSize::Walking(total)) => Some(format!("  counting... {} files", total.files)),
+      Some(DirSize::Done { total, .. }) => {
+        Some(format!("  total {} in {} files", format_size(total.bytes, app.config.size_unit()), total.files))
+      }
+      None => None,
+    };
+    if let Some(size) = size {
+      spans.push(Span::styled(size, Style::default().fg(Color::Cyan)));
+    }
+  }
+  f.render_widget(Paragraph::new(Spans::from(spans)), area);
+  let position = app.position().map(|(i, len)| format!("{}/{}", i, len));
+  let right = [app.disk_space.clone(), position].into_iter().flatten().collect::<Vec<_>>().join("  ");
+  if !right.is_empty() {
+    let right = Paragraph::new(Span::styled(right, Style::default().fg(Color::Gray)));
+    f.render_widget(right.alignment(Alignment::Right), area);
+  }
+}
+
+/// Two pixels per cell with the upper half block
+fn image_lines(thumbnail: &Thumbnail, width: u16, height: u16) -> Vec<Spans<'static>> {
+  thumbnail
+    .to_cells(width, height)
+    .into_iter()
+    .map(|row| {
+      Spans::from(
+        row
+          .into_iter()
+          .map(|([r, g, b], [br, bg, bb])| {
+            Span::styled("▀", Style::default().fg(Color::Rgb(r, g, b)).bg(Color::Rgb(br, bg, bb)))
+          })
+          .collect::<Vec<_>>(),
+      )
+    })
+    .collect()
+}
+
+/// The working directory as shown in the header, leaving room for the read-only mark
+pub fn header_path(app: &App, width: u16) -> String {
+  let width = if app.read_only { width.saturating_sub(READ_ONLY_MARK.width() as u16) } else { width };
+  truncate_path(&app.generate_wd_str(), width as usize)
 }
 
-fn set_items(items: &[ItemInfo], config: Config) -> Vec<ListItem> {
+/// Replace the middle components with `…`, keeping the root and as many trailing components as fit
+fn truncate_path(path: &str, width: usize) -> String {
+  if path.width() <= width {
+    return path.into();
+  }
+  let sep = std::path::MAIN_SEPARATOR;
+  let (head, rest) = match path.find(sep) {
+    Some(i) => path.split_at(i + sep.len_utf8()),
+    None => ("", path),
+  };
+  let mut tail = String::new();
+  for component in rest.rsplit(sep) {
+    let candidate = if tail.is_empty() { component.to_string() } else { format!("{}{}{}", component, sep, tail) };
+    if head.width() + 2 + candidate.width() > width {
+      break;
+    }
+    tail = candidate;
+  }
+  if tail.is_empty() {
+    // Not even the last component fits, keep its end
+    return format!("…{}", truncate::tail(path, width.saturating_sub(1)));
+  }
+  format!("{}…{}{}", head, sep, tail)
+}
+
+/// The entries drawn in `height` rows from `offset` and the selection among them, so that only those are styled
+fn window(items: &[ItemInfo], offset: usize, selected: Option<usize>, height: u16) -> (&[ItemInfo], ListState) {
+  let start = offset.min(items.len());
+  let end = (start + height as usize).min(items.len());
+  let mut state = ListState::default();
+  state.select(selected.and_then(|i| i.checked_sub(start)).filter(|i| start + i < end));
+  (&items[start..end], state)
+}
+
+/// Names are truncated to `width` columns, with `base` they are the paths under it
+fn set_items<'a>(
+  items: &'a [ItemInfo],
+  width: Option<u16>,
+  base: Option<&Path>,
+  config: &Config,
+  theme: &Theme,
+) -> Vec<ListItem<'a>> {
+  let show_index = config.is_show_index(items);
+  items
+    .iter()
+    .filter_map(|item| Some(ListItem::new(item_span(item, show_index, width, base, config, theme)?)))
+    .collect()
+}
+
+/// File contents after their line numbers right-aligned in `width` columns, descriptions such as `<binary file>` have none
+fn set_numbered_items<'a>(items: &'a [ItemInfo], width: usize, config: &Config, theme: &Theme) -> Vec<ListItem<'a>> {
   items
     .iter()
     .filter_map(|item| {
-      let style = match item.item {
-        Item::Content(_) | Item::None | Item::Path(ItemPath::File(_)) => Style::default().fg(Color::Gray),
-        Item::Path(ItemPath::Dir(_)) => Style::default().fg(Color::Blue),
-        Item::Search(_) => Style::default().fg(Color::Green),
-        Item::Path(ItemPath::Symlink(ItemSymlink::Dir(_))) => Style::default().fg(Color::Cyan),
-        Item::Path(ItemPath::Symlink(ItemSymlink::File(_))) => Style::default().fg(Color::LightCyan),
-        Item::Path(ItemPath::Unknown(_)) => Style::default().fg(Color::Red),
-      };
-
-      let mut text = if let Item::Search(text) = &item.item {
-        text.into()
-      } else if let Item::Content(text) = &item.item {
-        text.into()
-      } else {
-        item.generate_filename()?
-      };
-
-      if config.is_show_index(items) {
-        text = format!("{} {}", item.index.unwrap_or(0) + 1, text);
+      let mut spans = item_span(item, false, None, None, config, theme)?;
+      if let Some(i) = item.index {
+        spans.0.insert(0, Span::styled(format!("{:>width$} ", i + 1), Style::default().fg(Color::DarkGray)));
       }
+      Some(ListItem::new(spans))
+    })
+    .collect()
+}
 
-      Some(ListItem::new(Span::styled(text, style)))
+/// Entries after the branches, without the index
+fn set_tree_items<'a>(tree: &'a [TreeEntry], width: u16, config: &Config, theme: &Theme) -> Vec<ListItem<'a>> {
+  tree
+    .iter()
+    .filter_map(|entry| {
+      let width = width.saturating_sub(entry.prefix.width() as u16);
+      let mut spans = item_span(&entry.item, false, Some(width), None, config, theme)?;
+      spans.0.insert(0, Span::raw(entry.prefix.as_str()));
+      Some(ListItem::new(spans))
     })
     .collect()
 }
+
+/// Names, not file contents, are truncated to `width` columns with the index and the icon
+fn item_span<'a>(
+  item: &'a ItemInfo,
+  show_index: bool,
+  width: Option<u16>,
+  base: Option<&Path>,
+  config: &Config,
+  theme: &Theme,
+) -> Option<Spans<'a>> {
+  let style = match &item.item {
+    Item::Path(ItemPath::File(path)) => match theme.files.as_ref().and_then(|files| files.style(path)) {
+      Some(style) => theme.file.patch(style),
+      None => theme.file,
+    },
+    Item::Content(_) | Item::Image(_) | Item::None => theme.file,
+    Item::Path(ItemPath::Dir(_)) | Item::Parent => theme.dir,
+    Item::Search(_) => Style::default().fg(Color::Green),
+    Item::Path(ItemPath::Symlink(ItemSymlink::Dir(_))) => theme.symlink_dir,
+    Item::Path(ItemPath::Symlink(ItemSymlink::File(_))) => theme.symlink_file,
+    Item::Path(ItemPath::Symlink(ItemSymlink::Broken(_))) => {
+      Style::default().fg(Color::LightRed).add_modifier(Modifier::CROSSED_OUT)
+    }
+    Item::Path(ItemPath::Unknown(_)) => Style::default().fg(Color::Red),
+  };
+
+  let mut prefix = String::new();
+  if show_index && !item.is_parent() {
+    prefix = format!("{} ", item.index.unwrap_or(0) + 1);
+  }
+  if let Some(icon) = icon(&item.item).filter(|_| config.is_icons()) {
+    prefix = format!("{}{} ", prefix, icon);
+  }
+  let width = width.map(|width| (width as usize).saturating_sub(prefix.width()));
+  let truncate = |text: String, truncation: Truncation| match width {
+    Some(width) => truncation.apply(&text, width).into_owned(),
+    None => text,
+  };
+
+  let text = if let Item::Search(text) = &item.item {
+    text.into()
+  } else if let Item::Content(text) = &item.item {
+    text.into()
+  } else if let Item::Image(thumbnail) = &item.item {
+    thumbnail.description.clone()
+  } else if item.is_parent() {
+    "..".into()
+  } else if let Some(target) = item.read_link() {
+    // The name of the link stays visible
+    truncate(format!("{} -> {}", item.generate_name(base)?, config.display_path(&target)), Truncation::End)
+  } else {
+    truncate(item.generate_name(base)?, config.truncation())
+  };
+  let text = prefix + &text;
+
+  if matches!(item.item, Item::Content(_)) && config.is_preview_ansi() && text.contains('\x1b') {
+    return Some(Spans::from(ansi::spans(&text, style)));
+  }
+  Some(Spans::from(Span::styled(text, style)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[cfg(unix)]
+  #[test]
+  fn test_truncate_path() {
+    assert_eq!(truncate_path("/usr/local/bin", 20), "/usr/local/bin");
+    assert_eq!(truncate_path("/usr/local/share/doc", 12), "/…/share/doc");
+    assert_eq!(truncate_path("~/projects/rust/app", 10), "~/…/app");
+    assert_eq!(truncate_path("/usr/a_very_long_name", 8), "…ng_name");
+    // Two columns each
+    assert_eq!(truncate_path("/home/ユーザー/書類", 14), "/…/書類");
+    assert_eq!(truncate_path("/home/ユーザー/書類", 19), "/home/ユーザー/書類");
+    assert_eq!(truncate_path("/写真アルバム", 6), "…バム");
+  }
+
+  #[test]
+  fn test_window() {
+    let items = (0..10).map(|i| ItemInfo { item: Item::Content(i.to_string()), index: Some(i) }).collect::<Vec<_>>();
+    let (visible, state) = window(&items, 4, Some(6), 3);
+    assert_eq!(visible.iter().map(|item| item.index.unwrap()).collect::<Vec<_>>(), [4, 5, 6]);
+    assert_eq!(state.selected(), Some(2));
+    let (visible, state) = window(&items, 8, Some(2), 5);
+    assert_eq!(visible.len(), 2);
+    assert_eq!(state.selected(), None);
+    assert!(window(&items, 20, None, 5).0.is_empty());
+  }
+
+  #[test]
+  fn test_centered() {
+    assert_eq!(centered(Rect::new(0, 0, 80, 24), 20, 10), Rect::new(30, 7, 20, 10));
+    assert_eq!(centered(Rect::new(2, 1, 10, 5), 20, 10), Rect::new(2, 1, 10, 5));
+  }
+
+  #[test]
+  fn test_set_numbered_items() {
+    let items = [
+      ItemInfo { item: Item::Content("fn main() {".into()), index: Some(8) },
+      ItemInfo { item: Item::Content("}".into()), index: Some(9) },
+      ItemInfo { item: Item::Content("<binary file, 1 B>".into()), index: None },
+    ];
+    let (config, theme) = (Config::default(), Theme::default());
+    let lines = set_numbered_items(&items, 2, &config, &theme);
+    let numbered = |number: &str, i: usize| {
+      let mut spans = item_span(&items[i], false, None, None, &config, &theme).unwrap();
+      spans.0.insert(0, Span::styled(number.to_string(), Style::default().fg(Color::DarkGray)));
+      ListItem::new(spans)
+    };
+    assert_eq!(
+      lines,
+      [
+        numbered(" 9 ", 0),
+        numbered("10 ", 1),
+        ListItem::new(item_span(&items[2], false, None, None, &config, &theme).unwrap())
+      ]
+    );
+  }
+}
@@ -0,0 +1,71 @@
+use std::{
+  collections::HashSet,
+  fs,
+  path::{Path, PathBuf},
+};
+
+use crate::util::canonicalize;
+
+/// Canonical paths of the directories entered, so that no directory is entered twice through symbolic links
+#[derive(Debug, Default)]
+pub struct Visited(HashSet<PathBuf>);
+
+impl Visited {
+  /// `false` if `dir` was entered before or cannot be resolved, e.g. a link leading back to a directory above
+  pub fn insert(&mut self, dir: &Path) -> bool {
+    canonicalize(dir).is_ok_and(|dir| self.0.insert(dir))
+  }
+}
+
+/// Call `visit` with every entry under `dir` that is not a directory, skipping the entries that cannot be read
+///
+/// Symbolic links are visited as they are unless `follow_symlinks`, with which those to directories are entered once
+pub fn walk(dir: &Path, follow_symlinks: bool, mut visit: impl FnMut(&Path, &fs::Metadata)) {
+  let mut visited = Visited::default();
+  if follow_symlinks {
+    visited.insert(dir);
+  }
+  let mut dirs = vec![dir.to_path_buf()];
+  while let Some(dir) = dirs.pop() {
+    let entries = if let Ok(entries) = fs::read_dir(&dir) { entries } else { continue };
+    for entry in entries.flatten() {
+      let path = entry.path();
+      let metadata = if follow_symlinks { fs::metadata(&path) } else { entry.metadata() };
+      let metadata = if let Ok(metadata) = metadata.or_else(|_| entry.metadata()) { metadata } else { continue };
+      if !metadata.is_dir() {
+        visit(&path, &metadata);
+      } else if !follow_symlinks || visited.insert(&path) {
+        dirs.push(path);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::util::TestDir;
+
+  #[cfg(unix)]
+  #[test]
+  fn test_walk() {
+    let dir = TestDir::new("test_walk");
+    fs::create_dir_all(dir.join("a")).unwrap();
+    fs::write(dir.join("a/x"), "").unwrap();
+    let _ = std::os::unix::fs::symlink(&dir, dir.join("a/loop"));
+    let _ = std::os::unix::fs::symlink("missing", dir.join("broken"));
+    let names = |follow_symlinks| {
+      let mut names = vec![];
+      walk(&dir, follow_symlinks, |path, _| names.push(path.strip_prefix(&dir).unwrap().to_path_buf()));
+      names.sort();
+      names
+    };
+    assert_eq!(names(false), [PathBuf::from("a/loop"), "a/x".into(), "broken".into()]);
+    assert_eq!(names(true), [PathBuf::from("a/x"), "broken".into()]);
+
+    let mut visited = Visited::default();
+    assert!(visited.insert(&dir));
+    assert!(!visited.insert(&dir.join("a/loop")));
+    assert!(!visited.insert(&dir.join("missing")));
+  }
+}
@@ -0,0 +1,98 @@
+use std::{
+  fmt,
+  path::{Path, PathBuf},
+  sync::mpsc::{self, Receiver},
+  time::{Duration, Instant},
+};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Events within this time of each other are handled as one change
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watches the working directory so that it is read again when its entries change
+pub struct DirWatcher {
+  /// `None` when watching is not supported
+  watcher: Option<RecommendedWatcher>,
+  receiver: Receiver<notify::Result<notify::Event>>,
+  path: Option<PathBuf>,
+  /// When the last burst of events is over
+  due: Option<Instant>,
+}
+
+impl fmt::Debug for DirWatcher {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("DirWatcher").field("path", &self.path).field("due", &self.due).finish()
+  }
+}
+
+impl Default for DirWatcher {
+  fn default() -> Self {
+    let (sender, receiver) = mpsc::channel();
+    // The receiver is gone when the app has finished
+    let watcher = notify::recommended_watcher(move |event| {
+      let _ = sender.send(event);
+    });
+    DirWatcher { watcher: watcher.ok(), receiver, path: None, due: None }
+  }
+}
+
+impl DirWatcher {
+  /// Stop watching the previous directory, directories that cannot be watched are not refreshed
+  pub fn watch(&mut self, path: &Path) {
+    if self.path.as_deref() == Some(path) {
+      return;
+    }
+    let watcher = if let Some(watcher) = &mut self.watcher { watcher } else { return };
+    if let Some(previous) = self.path.take() {
+      let _ = watcher.unwatch(&previous);
+    }
+    if watcher.watch(path, RecursiveMode::NonRecursive).is_ok() {
+      self.path = Some(path.into());
+    }
+    // Events of the previous directory
+    while self.receiver.try_recv().is_ok() {}
+    self.due = None;
+  }
+  /// True once when the events have settled
+  pub fn changed(&mut self) -> bool {
+    while let Ok(event) = self.receiver.try_recv() {
+      // Reading the directory only touches the access time
+      if event.is_ok_and(|event| !event.kind.is_access()) {
+        self.due = Some(Instant::now() + DEBOUNCE);
+      }
+    }
+    if self.due.is_some_and(|due| due <= Instant::now()) {
+      self.due = None;
+      return true;
+    }
+    false
+  }
+  /// Time left until the pending change is handled
+  pub fn wait(&self) -> Option<Duration> {
+    self.due.map(|due| due.saturating_duration_since(Instant::now()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{fs, thread};
+
+  use super::*;
+  use crate::util::TestDir;
+
+  #[test]
+  fn test_changed() {
+    let dir = TestDir::new("test_watcher");
+    let mut watcher = DirWatcher::default();
+    watcher.watch(&dir);
+    assert!(!watcher.changed());
+    fs::write(dir.join("a"), "").unwrap();
+    let started = Instant::now();
+    while !watcher.changed() && started.elapsed() < Duration::from_secs(5) {
+      thread::sleep(Duration::from_millis(10));
+    }
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(!watcher.changed());
+  }
+}
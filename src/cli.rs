@@ -1,49 +1,68 @@
-use clap::{arg, command, Parser};
+use clap::Parser;
 
 use crate::error::PrintError;
 use crate::shell::Shell;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about = "Tools for easy cd\nThe `cd` functionality can also be used as-is", long_about = None)]
-#[command(override_usage = "ed\n
- -------------------------------------------------------------
-|   Key         |    Description                              |
-|---------------|---------------------------------------------|
-| ↑ k           | Move up                                     |
-| ↓ j           | Move down                                   |
-| ← h           | Move parent directory                       |
-| → l           | Move Child directory                        |
-| Home          | Move to top                                 |
-| End           | Move to bottom                              |
-| PageUp        | Skip a little and move up                   |
-| PageDown      | Skip a little and move down                 |
-| Enter c ;     | Change directory to current directory       |
-| Esc Ctrl+c q  | Exit and return to original directory       |
-| Insert Ctrl+s | Search mode switch (Char key will not work) |
-| Backspace     | Delete one character from the search string |
-| Delete        | Delete all search strings                   |
-| V             | Open vscode                                 |
-| p             | Print the selected filepath                 |
- -------------------------------------------------------------")]
+#[command(override_usage = crate::app::usage())]
 pub struct Cli {
   #[arg(short, hide(true))]
   temp_path: Option<String>,
+  #[arg(value_name = "PATH", help = "Start in this directory, or in the directory of this file with it selected")]
+  path: Option<std::path::PathBuf>,
   #[arg(long, value_enum, value_name = "SHELL", help = "Configure shell")]
   init: Option<Shell>,
-  #[arg(long, help = "Show all environment variables")]
+  #[arg(long, help = "Show all environment variables, including those set in the config file")]
   env: bool,
+  #[arg(long, help = "Check the config file and the environment variables, exit with an error if invalid")]
+  check_config: bool,
+  #[arg(
+    long,
+    value_name = "NAME",
+    help = "Start with the entry of this name selected, the first entry if there is none"
+  )]
+  select: Option<String>,
+  #[arg(long, help = "Start in the current directory even if _ED_RESTORE_LAST_DIR is set")]
+  no_restore: bool,
+  #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = ".", help = "Print the entries of the directory and exit")]
+  list: Option<std::path::PathBuf>,
+  #[arg(long, help = "Print the result as JSON, or the entries with --list")]
+  json: bool,
+  #[arg(long, help = "Print the chosen path instead of changing to it")]
+  print_only: bool,
+  #[arg(
+    long,
+    value_name = "PATH",
+    help = "Write the printed path or the JSON to this file instead of stdout, as does the descriptor in ED_OUT_FD"
+  )]
+  out_file: Option<std::path::PathBuf>,
+  #[arg(long, help = "Disable creating, renaming, deleting and pasting")]
+  read_only: bool,
+  #[arg(
+    long,
+    conflicts_with_all = ["path", "select", "list"],
+    help = "Choose from the paths read from stdin, one per line, instead of the current directory"
+  )]
+  stdin: bool,
+  #[arg(long, value_enum, value_name = "TYPE", help = "Start showing only directories or this type of files")]
+  only: Option<crate::Only>,
+  #[arg(short, long, action = clap::ArgAction::Count, help = "Write a log, -v for navigation and errors, -vv for details")]
+  verbose: u8,
 }
 
 impl Cli {
   pub fn match_options(&self) {
     self.match_init();
     self.match_env();
+    self.match_check_config();
+    self.match_list();
   }
 
   fn match_init(&self) {
     if let Some(shell) = &self.init {
       if let Err(e) = crate::init(shell) {
-        e.eprintln();
+        e.exit();
       }
       std::process::exit(exitcode::OK);
     }
@@ -53,17 +72,78 @@ impl Cli {
     if self.env {
       match crate::Config::new() {
         Ok(c) => c.show_all(),
-        Err(e) => e.eprintln(),
+        Err(e) => e.exit(),
       };
       std::process::exit(exitcode::OK);
     }
   }
 
+  fn match_check_config(&self) {
+    if self.check_config {
+      if let Err(e) = crate::Config::new() {
+        e.exit();
+      }
+      match crate::Config::file_path().filter(|path| path.is_file()) {
+        Some(path) => println!("{} is valid", path.display()),
+        None => println!("No config file, the environment variables are valid"),
+      }
+      std::process::exit(exitcode::OK);
+    }
+  }
+
+  fn match_list(&self) {
+    if let Some(path) = &self.list {
+      if let Err(e) = crate::list(path, self.json) {
+        e.exit();
+      }
+      std::process::exit(exitcode::OK);
+    }
+  }
+
+  pub fn start_path(&self) -> Option<&std::path::Path> {
+    self.path.as_deref()
+  }
+
+  pub fn select(&self) -> Option<crate::InitialSelection> {
+    self.select.clone().map(crate::InitialSelection::Name)
+  }
+
+  pub fn is_no_restore(&self) -> bool {
+    self.no_restore
+  }
+
+  pub fn is_json(&self) -> bool {
+    self.json
+  }
+
+  pub fn is_print_only(&self) -> bool {
+    self.print_only
+  }
+
+  pub fn out_file(&self) -> Option<&std::path::Path> {
+    self.out_file.as_deref()
+  }
+
+  pub fn is_read_only(&self) -> bool {
+    self.read_only
+  }
+
+  pub fn is_stdin(&self) -> bool {
+    self.stdin
+  }
+
+  pub fn only(&self) -> Option<crate::Only> {
+    self.only
+  }
+
+  pub fn verbosity(&self) -> u8 {
+    self.verbose
+  }
+
   pub fn prepare_cd(&self, cd_path: &std::path::Path) {
     if let Some(temp_path) = self.temp_path.as_ref() {
       if let Err(e) = crate::pipe_shell(cd_path, temp_path) {
-        e.eprintln();
-        std::process::exit(exitcode::OK);
+        e.exit();
       }
     }
   }
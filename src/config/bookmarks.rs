@@ -0,0 +1,56 @@
+use std::{
+  collections::BTreeMap,
+  fs,
+  path::{Path, PathBuf},
+};
+
+use super::Log;
+
+#[derive(Debug, Default)]
+pub struct Bookmarks(BTreeMap<char, PathBuf>);
+
+impl Bookmarks {
+  pub fn output_path() -> PathBuf {
+    Log::output_path().with_file_name("bookmarks.json")
+  }
+
+  /// Missing or broken files are treated as no bookmarks
+  pub fn load() -> Self {
+    let bookmarks = fs::read_to_string(Self::output_path()).ok().and_then(|s| serde_json::from_str(&s).ok());
+    Bookmarks(bookmarks.unwrap_or_default())
+  }
+
+  pub fn save(&self) -> anyhow::Result<()> {
+    let output_path = Self::output_path();
+    fs::create_dir_all(output_path.parent().unwrap())?;
+    fs::write(output_path, serde_json::to_string_pretty(&self.0)?)?;
+    Ok(())
+  }
+
+  pub fn get(&self, key: char) -> Option<&PathBuf> {
+    self.0.get(&key)
+  }
+
+  pub fn insert(&mut self, key: char, path: &Path) {
+    self.0.insert(key, path.into());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_output_path() {
+    assert_eq!(Bookmarks::output_path(), home::home_dir().unwrap().join(".easychangedirectory").join("bookmarks.json"));
+  }
+
+  #[test]
+  fn test_insert() {
+    let mut bookmarks = Bookmarks::default();
+    bookmarks.insert('a', Path::new("/tmp"));
+    bookmarks.insert('a', Path::new("/usr"));
+    assert_eq!(bookmarks.get('a'), Some(&PathBuf::from("/usr")));
+    assert_eq!(bookmarks.get('b'), None);
+  }
+}
@@ -0,0 +1,37 @@
+use std::{fs, path::PathBuf};
+
+use super::Log;
+
+/// Column ratios set by dragging the borders, kept with `_ED_SAVE_COLUMN_RATIOS`
+pub struct SavedColumnRatios;
+
+impl SavedColumnRatios {
+  pub fn output_path() -> PathBuf {
+    Log::output_path().with_file_name("column_ratios")
+  }
+
+  /// In the format of `_ED_COLUMN_RATIOS`
+  pub fn read() -> Option<String> {
+    fs::read_to_string(Self::output_path()).ok().map(|ratios| ratios.trim().to_string())
+  }
+
+  pub fn write(ratios: &str) -> anyhow::Result<()> {
+    let output_path = Self::output_path();
+    fs::create_dir_all(output_path.parent().unwrap())?;
+    fs::write(output_path, ratios)?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_output_path() {
+    assert_eq!(
+      SavedColumnRatios::output_path(),
+      home::home_dir().unwrap().join(".easychangedirectory").join("column_ratios")
+    );
+  }
+}
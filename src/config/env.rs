@@ -1,24 +1,125 @@
+use std::{
+  collections::HashMap,
+  env, fs,
+  path::{Path, PathBuf},
+  time::Duration,
+};
+
+use anyhow::{anyhow, bail};
+use directories::ProjectDirs;
+use log::{debug, info, warn};
 use serde::Deserialize;
 
-use crate::app::{Item, ItemInfo};
+use crate::{
+  action::EnterAction,
+  app::{
+    CaseMode, InitialSelection, Item, ItemInfo, PreviewMode, PreviewTrigger, SortMode, SymlinkNavigation, Truncation,
+    DEFAULT_COMPACT_WIDTH, DEFAULT_MAX_PREVIEW_BYTES, DEFAULT_PREVIEW_DELAY, DEFAULT_PREVIEW_LINES,
+  },
+  util::{abbreviate_home, SizeUnit},
+};
 
-#[derive(Deserialize, Debug, Clone, Copy)]
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
   _ed_pwd: Option<u8>,
   _ed_set_bg: Option<u8>,
   _ed_show_index: Option<u8>,
   _ed_view_file_contents: Option<u8>,
   _ed_log: Option<u8>,
+  _ed_sort: Option<SortMode>,
+  _ed_dirs_first: Option<u8>,
+  _ed_restore_last_dir: Option<u8>,
+  _ed_preview_lines: Option<usize>,
+  _ed_max_preview_bytes: Option<u64>,
+  _ed_trash_dir: Option<PathBuf>,
+  _ed_respect_gitignore: Option<u8>,
+  _ed_keymap: Option<String>,
+  _ed_theme: Option<String>,
+  _ed_icons: Option<u8>,
+  _ed_preview_delay: Option<u64>,
+  _ed_sticky_filter: Option<u8>,
+  _ed_case: Option<CaseMode>,
+  _ed_size_unit: Option<SizeUnit>,
+  _ed_no_wrap: Option<u8>,
+  _ed_read_only: Option<u8>,
+  _ed_column_ratios: Option<String>,
+  _ed_hide_grandparent: Option<u8>,
+  _ed_preview_mode: Option<PreviewMode>,
+  _ed_file_colors: Option<u8>,
+  _ed_dirs_only: Option<u8>,
+  _ed_canonicalize_output: Option<u8>,
+  _ed_preview_commands: Option<String>,
+  _ed_preview_ansi: Option<u8>,
+  _ed_initial_selection: Option<InitialSelection>,
+  _ed_save_column_ratios: Option<u8>,
+  _ed_preview_line_numbers: Option<u8>,
+  _ed_truncate: Option<Truncation>,
+  _ed_confirm_quit: Option<u8>,
+  _ed_date_format: Option<String>,
+  _ed_quit_on_open: Option<u8>,
+  _ed_no_tilde: Option<u8>,
+  _ed_use_trash: Option<u8>,
+  _ed_disk_space: Option<u8>,
+  _ed_compact_width: Option<u16>,
+  _ed_symlink_navigation: Option<SymlinkNavigation>,
+  _ed_follow_symlinks: Option<u8>,
+  _ed_enter_action: Option<EnterAction>,
+  _ed_show_parent_entry: Option<u8>,
+  _ed_preview_trigger: Option<PreviewTrigger>,
 }
 
+/// Overrides where the config file is looked up
+const CONFIG_VAR: &str = "ED_CONFIG";
+const RESOLUTION_ORDER: &str = "The config file is `$ED_CONFIG` if set, otherwise `config.toml` in \
+  `$XDG_CONFIG_HOME/easychangedirectory` (`~/.config/easychangedirectory`) on Linux, \
+  `~/Library/Application Support/easychangedirectory` on macOS or `%APPDATA%\\easychangedirectory\\config` on Windows";
+
 impl Config {
+  /// The config file with the environment variables taking precedence
   pub fn new() -> anyhow::Result<Self> {
-    Ok(envy::from_env::<Self>()?)
+    let mut vars = match Self::file_path() {
+      Some(path) if path.is_file() || env::var_os(CONFIG_VAR).is_some() => {
+        info!("Reading the config file {}", path.display());
+        Self::read_file(&path)
+          .map_err(|e| anyhow!("Failed to read the config file {}: {}\n{}", path.display(), e, RESOLUTION_ORDER))?
+      }
+      path => {
+        debug!("No config file at {}", path.map(|path| path.display().to_string()).unwrap_or_default());
+        HashMap::new()
+      }
+    };
+    for (key, value) in known_vars(env::vars()) {
+      debug!("{} = {} from the environment", key, value);
+      vars.insert(key, value);
+    }
+    envy::from_iter(vars).map_err(|e| anyhow!("Invalid environment variable: {}", e))
+  }
+
+  /// `$ED_CONFIG`, or `config.toml` in the config directory of the platform
+  pub fn file_path() -> Option<PathBuf> {
+    match env::var_os(CONFIG_VAR) {
+      Some(path) => Some(path.into()),
+      None => ProjectDirs::from("", "", "easychangedirectory").map(|dirs| dirs.config_dir().join("config.toml")),
+    }
+  }
+
+  /// Keys are the environment variables without `_ED_` in lowercase, e.g. `sort = "size"`
+  fn read_file(path: &Path) -> anyhow::Result<HashMap<String, String>> {
+    parse_file(&fs::read_to_string(path)?)
   }
 
   pub fn is_pwd(&self) -> bool {
     self._ed_pwd.eq(&Some(1))
   }
+  pub fn is_no_tilde(&self) -> bool {
+    self._ed_no_tilde.eq(&Some(1))
+  }
+  /// `path` as shown to the user, with the home directory abbreviated to `~` unless `_ED_NO_TILDE` is set
+  pub fn display_path(&self, path: &Path) -> String {
+    let home = if self.is_no_tilde() { None } else { home::home_dir() };
+    abbreviate_home(path, home.as_deref())
+  }
   pub fn is_show_index(&self, items: &[ItemInfo]) -> bool {
     self._ed_show_index.eq(&Some(1)) && !items.is_empty() && !matches!(items[0].item, Item::Search(_))
   }
@@ -31,19 +132,221 @@ impl Config {
   pub fn is_log(&self) -> bool {
     self._ed_log.eq(&Some(1))
   }
+  pub fn sort_mode(&self) -> SortMode {
+    self._ed_sort.unwrap_or_default()
+  }
+  pub fn is_dirs_first(&self) -> bool {
+    self._ed_dirs_first.eq(&Some(1))
+  }
+  pub fn is_restore_last_dir(&self) -> bool {
+    self._ed_restore_last_dir.eq(&Some(1))
+  }
+  pub fn preview_lines(&self) -> usize {
+    self._ed_preview_lines.unwrap_or(DEFAULT_PREVIEW_LINES)
+  }
+  pub fn max_preview_bytes(&self) -> u64 {
+    self._ed_max_preview_bytes.unwrap_or(DEFAULT_MAX_PREVIEW_BYTES)
+  }
+  pub fn trash_dir(&self) -> Option<&Path> {
+    self._ed_trash_dir.as_deref()
+  }
+  /// On unless the value is `0`
+  pub fn is_use_trash(&self) -> bool {
+    !self._ed_use_trash.eq(&Some(0))
+  }
+  pub fn is_respect_gitignore(&self) -> bool {
+    self._ed_respect_gitignore.eq(&Some(1))
+  }
+  pub fn keymap(&self) -> Option<&str> {
+    self._ed_keymap.as_deref()
+  }
+  pub fn theme(&self) -> Option<&str> {
+    self._ed_theme.as_deref()
+  }
+  pub fn is_icons(&self) -> bool {
+    self._ed_icons.eq(&Some(1))
+  }
+  pub fn preview_trigger(&self) -> PreviewTrigger {
+    self._ed_preview_trigger.unwrap_or_default()
+  }
+  pub fn preview_delay(&self) -> Duration {
+    Duration::from_millis(self._ed_preview_delay.unwrap_or(DEFAULT_PREVIEW_DELAY))
+  }
+  pub fn is_sticky_filter(&self) -> bool {
+    self._ed_sticky_filter.eq(&Some(1))
+  }
+  pub fn case_mode(&self) -> CaseMode {
+    self._ed_case.unwrap_or_default()
+  }
+  pub fn size_unit(&self) -> SizeUnit {
+    self._ed_size_unit.unwrap_or_default()
+  }
+  pub fn is_disk_space(&self) -> bool {
+    self._ed_disk_space.eq(&Some(1))
+  }
+  pub fn is_no_wrap(&self) -> bool {
+    self._ed_no_wrap.eq(&Some(1))
+  }
+  pub fn is_read_only(&self) -> bool {
+    self._ed_read_only.eq(&Some(1))
+  }
+  pub fn column_ratios(&self) -> Option<&str> {
+    self._ed_column_ratios.as_deref()
+  }
+  pub fn is_save_column_ratios(&self) -> bool {
+    self._ed_save_column_ratios.eq(&Some(1))
+  }
+  pub fn is_hide_grandparent(&self) -> bool {
+    self._ed_hide_grandparent.eq(&Some(1))
+  }
+  pub fn preview_mode(&self) -> PreviewMode {
+    self._ed_preview_mode.unwrap_or_default()
+  }
+  pub fn is_file_colors(&self) -> bool {
+    self._ed_file_colors.eq(&Some(1))
+  }
+  pub fn compact_width(&self) -> u16 {
+    self._ed_compact_width.unwrap_or(DEFAULT_COMPACT_WIDTH)
+  }
+  pub fn is_dirs_only(&self) -> bool {
+    self._ed_dirs_only.eq(&Some(1))
+  }
+  pub fn is_canonicalize_output(&self) -> bool {
+    self._ed_canonicalize_output.eq(&Some(1))
+  }
+  pub fn preview_commands(&self) -> Option<&str> {
+    self._ed_preview_commands.as_deref()
+  }
+  pub fn date_format(&self) -> Option<&str> {
+    self._ed_date_format.as_deref()
+  }
+  pub fn is_quit_on_open(&self) -> bool {
+    self._ed_quit_on_open.eq(&Some(1))
+  }
+  pub fn is_confirm_quit(&self) -> bool {
+    self._ed_confirm_quit.eq(&Some(1))
+  }
+  pub fn truncation(&self) -> Truncation {
+    self._ed_truncate.unwrap_or_default()
+  }
+  pub fn symlink_navigation(&self) -> SymlinkNavigation {
+    self._ed_symlink_navigation.unwrap_or_default()
+  }
+  pub fn enter_action(&self) -> EnterAction {
+    self._ed_enter_action.unwrap_or_default()
+  }
+  pub fn is_show_parent_entry(&self) -> bool {
+    self._ed_show_parent_entry.eq(&Some(1))
+  }
+  pub fn is_follow_symlinks(&self) -> bool {
+    self._ed_follow_symlinks.eq(&Some(1))
+  }
+  pub fn is_preview_line_numbers(&self) -> bool {
+    self._ed_preview_line_numbers.eq(&Some(1))
+  }
+  pub fn is_preview_ansi(&self) -> bool {
+    self._ed_preview_ansi.eq(&Some(1))
+  }
+  pub fn initial_selection(&self) -> InitialSelection {
+    self._ed_initial_selection.clone().unwrap_or_default()
+  }
 
   pub fn show_all(&self) {
-    println!("_ED_PWD = {}", self._ed_pwd.map(|u| u.to_string()).unwrap_or_else(|| "".to_string()));
-    println!("_ED_SET_BG = {}", self._ed_set_bg.map(|u| u.to_string()).unwrap_or_else(|| "".to_string()));
-    println!("_ED_SHOW_INDEX = {}", self._ed_show_index.map(|u| u.to_string()).unwrap_or_else(|| "".to_string()));
-    println!(
-      "_ED_VIEW_FILE_CONTENTS = {}",
-      self._ed_view_file_contents.map(|u| u.to_string()).unwrap_or_else(|| "".to_string())
-    );
-    println!("_ED_LOG = {}", self._ed_log.map(|u| u.to_string()).unwrap_or_else(|| "".to_string()));
+    println!("_ED_PWD = {}", self._ed_pwd.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_SET_BG = {}", self._ed_set_bg.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_SHOW_INDEX = {}", self._ed_show_index.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_VIEW_FILE_CONTENTS = {}", self._ed_view_file_contents.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_LOG = {}", self._ed_log.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_SORT = {}", self._ed_sort.map(|s| s.name()).unwrap_or_default());
+    println!("_ED_DIRS_FIRST = {}", self._ed_dirs_first.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_RESTORE_LAST_DIR = {}", self._ed_restore_last_dir.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_PREVIEW_LINES = {}", self._ed_preview_lines.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_MAX_PREVIEW_BYTES = {}", self._ed_max_preview_bytes.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_TRASH_DIR = {}", self._ed_trash_dir.as_ref().map(|p| p.display().to_string()).unwrap_or_default());
+    println!("_ED_USE_TRASH = {}", self._ed_use_trash.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_RESPECT_GITIGNORE = {}", self._ed_respect_gitignore.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_KEYMAP = {}", self._ed_keymap.as_deref().unwrap_or_default());
+    println!("_ED_THEME = {}", self._ed_theme.as_deref().unwrap_or_default());
+    println!("_ED_ICONS = {}", self._ed_icons.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_PREVIEW_DELAY = {}", self._ed_preview_delay.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_PREVIEW_TRIGGER = {}", self._ed_preview_trigger.map(|p| p.name()).unwrap_or_default());
+    println!("_ED_STICKY_FILTER = {}", self._ed_sticky_filter.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_CASE = {}", self._ed_case.map(|c| c.name()).unwrap_or_default());
+    println!("_ED_SIZE_UNIT = {}", self._ed_size_unit.map(|u| u.name()).unwrap_or_default());
+    println!("_ED_NO_WRAP = {}", self._ed_no_wrap.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_READ_ONLY = {}", self._ed_read_only.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_COLUMN_RATIOS = {}", self._ed_column_ratios.as_deref().unwrap_or_default());
+    println!("_ED_SAVE_COLUMN_RATIOS = {}", self._ed_save_column_ratios.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_COMPACT_WIDTH = {}", self._ed_compact_width.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_HIDE_GRANDPARENT = {}", self._ed_hide_grandparent.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_PREVIEW_MODE = {}", self._ed_preview_mode.map(|m| m.name()).unwrap_or_default());
+    println!("_ED_FILE_COLORS = {}", self._ed_file_colors.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_DIRS_ONLY = {}", self._ed_dirs_only.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_CANONICALIZE_OUTPUT = {}", self._ed_canonicalize_output.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_PREVIEW_COMMANDS = {}", self._ed_preview_commands.as_deref().unwrap_or_default());
+    println!("_ED_PREVIEW_LINE_NUMBERS = {}", self._ed_preview_line_numbers.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_PREVIEW_ANSI = {}", self._ed_preview_ansi.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_DISK_SPACE = {}", self._ed_disk_space.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_DATE_FORMAT = {}", self._ed_date_format.as_deref().unwrap_or_default());
+    println!("_ED_QUIT_ON_OPEN = {}", self._ed_quit_on_open.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_NO_TILDE = {}", self._ed_no_tilde.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_CONFIRM_QUIT = {}", self._ed_confirm_quit.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_SYMLINK_NAVIGATION = {}", self._ed_symlink_navigation.map(|s| s.name()).unwrap_or_default());
+    println!("_ED_FOLLOW_SYMLINKS = {}", self._ed_follow_symlinks.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_ENTER_ACTION = {}", self._ed_enter_action.map(|e| e.name()).unwrap_or_default());
+    println!("_ED_SHOW_PARENT_ENTRY = {}", self._ed_show_parent_entry.map(|u| u.to_string()).unwrap_or_default());
+    println!("_ED_TRUNCATE = {}", self._ed_truncate.map(|t| t.name()).unwrap_or_default());
+    println!("_ED_INITIAL_SELECTION = {}", self._ed_initial_selection.as_ref().map(|s| s.name()).unwrap_or_default());
   }
 }
 
+/// The values as the environment variables would be, `true` is `1`, each checked on its own to tell the key and line
+fn parse_file(text: &str) -> anyhow::Result<HashMap<String, String>> {
+  let table = text.parse::<toml::Table>()?;
+  table
+    .into_iter()
+    .map(|(key, value)| {
+      let at = || line_of(text, &key).map(|line| format!(" at line {}", line)).unwrap_or_default();
+      let value = match value {
+        toml::Value::String(s) => s,
+        toml::Value::Integer(i) => i.to_string(),
+        toml::Value::Boolean(b) => u8::from(b).to_string(),
+        value => {
+          bail!("Invalid `{}`{}: expected a string, an integer or a boolean, got {}", key, at(), value.type_str())
+        }
+      };
+      let var = format!("_ED_{}", key.to_uppercase());
+      match envy::from_iter::<_, Config>([(var.clone(), value.clone())]) {
+        Ok(_) => Ok((var, value)),
+        Err(e) if e.to_string().starts_with("unknown field") => bail!("Unknown key `{}`{}", key, at()),
+        Err(e) => bail!("Invalid `{}`{}: {}", key, at(), e),
+      }
+    })
+    .collect()
+}
+
+/// The `_ED_*` variables that are config fields, warning of the others so that a stale one does not stop every run
+fn known_vars(vars: impl Iterator<Item = (String, String)>) -> Vec<(String, String)> {
+  vars
+    .filter(|(key, _)| key.starts_with("_ED_"))
+    .filter(|(key, value)| match envy::from_iter::<_, Config>([(key.clone(), value.clone())]) {
+      Err(e) if e.to_string().starts_with("unknown field") => {
+        warn!("Ignoring the unknown environment variable {}", key);
+        false
+      }
+      _ => true,
+    })
+    .collect()
+}
+
+/// 1-based line where `key` is assigned
+fn line_of(text: &str, key: &str) -> Option<usize> {
+  text
+    .lines()
+    .position(|line| line.trim_start().strip_prefix(key).is_some_and(|rest| rest.trim_start().starts_with('=')))
+    .map(|i| i + 1)
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -56,11 +359,121 @@ mod tests {
       _ed_show_index: Some(1),
       _ed_view_file_contents: Some(1),
       _ed_log: Some(1),
+      _ed_sort: Some(SortMode::Size),
+      _ed_dirs_first: Some(1),
+      _ed_restore_last_dir: Some(1),
+      _ed_preview_lines: Some(10),
+      _ed_max_preview_bytes: Some(2048),
+      _ed_trash_dir: Some(PathBuf::from("/tmp/trash")),
+      _ed_respect_gitignore: Some(1),
+      _ed_keymap: Some("move_down=n".into()),
+      _ed_theme: Some("dir=green".into()),
+      _ed_icons: Some(1),
+      _ed_preview_delay: Some(0),
+      _ed_sticky_filter: Some(1),
+      _ed_case: Some(CaseMode::Sensitive),
+      _ed_size_unit: Some(SizeUnit::Decimal),
+      _ed_no_wrap: Some(1),
+      _ed_read_only: Some(1),
+      _ed_column_ratios: Some("1,1,4,2".into()),
+      _ed_hide_grandparent: Some(1),
+      _ed_preview_mode: Some(PreviewMode::Tree),
+      _ed_file_colors: Some(1),
+      _ed_dirs_only: Some(1),
+      _ed_canonicalize_output: Some(1),
+      _ed_preview_commands: Some("rs=bat".into()),
+      _ed_preview_ansi: Some(1),
+      _ed_initial_selection: Some(InitialSelection::Last),
+      _ed_save_column_ratios: Some(1),
+      _ed_preview_line_numbers: Some(1),
+      _ed_truncate: Some(Truncation::End),
+      _ed_confirm_quit: Some(1),
+      _ed_date_format: Some("relative".into()),
+      _ed_quit_on_open: Some(1),
+      _ed_no_tilde: Some(1),
+      _ed_use_trash: Some(0),
+      _ed_disk_space: Some(1),
+      _ed_compact_width: Some(0),
+      _ed_symlink_navigation: Some(SymlinkNavigation::Physical),
+      _ed_follow_symlinks: Some(1),
+      _ed_enter_action: Some(EnterAction::ChangeOrPrintForFiles),
+      _ed_show_parent_entry: Some(1),
+      _ed_preview_trigger: Some(PreviewTrigger::Manual),
     };
     assert!(config.is_pwd());
     assert!(config.is_show_index(&[ItemInfo::default()]));
     assert!(config.is_view_file_contents());
     assert!(config.is_set_bg());
     assert!(config.is_log());
+    assert_eq!(config.sort_mode(), SortMode::Size);
+    assert!(config.is_dirs_first());
+    assert!(config.is_restore_last_dir());
+    assert_eq!(config.preview_lines(), 10);
+    assert_eq!(config.max_preview_bytes(), 2048);
+    assert_eq!(config.trash_dir(), Some(Path::new("/tmp/trash")));
+    assert!(config.is_respect_gitignore());
+    assert_eq!(config.keymap(), Some("move_down=n"));
+    assert_eq!(config.theme(), Some("dir=green"));
+    assert!(config.is_icons());
+    assert_eq!(config.preview_delay(), Duration::ZERO);
+    assert!(config.is_sticky_filter());
+    assert_eq!(config.case_mode(), CaseMode::Sensitive);
+    assert_eq!(config.size_unit(), SizeUnit::Decimal);
+    assert!(config.is_no_wrap());
+    assert!(config.is_read_only());
+    assert_eq!(config.column_ratios(), Some("1,1,4,2"));
+    assert!(config.is_hide_grandparent());
+    assert_eq!(config.preview_mode(), PreviewMode::Tree);
+    assert!(config.is_file_colors());
+    assert!(config.is_dirs_only());
+    assert!(config.is_canonicalize_output());
+    assert_eq!(config.preview_commands(), Some("rs=bat"));
+    assert!(config.is_preview_ansi());
+    assert_eq!(config.initial_selection(), InitialSelection::Last);
+    assert!(config.is_save_column_ratios());
+    assert!(config.is_preview_line_numbers());
+    assert_eq!(config.truncation(), Truncation::End);
+    assert!(config.is_confirm_quit());
+    assert_eq!(config.date_format(), Some("relative"));
+    assert!(config.is_quit_on_open());
+    assert!(config.is_no_tilde());
+    assert!(!config.is_use_trash());
+    assert!(Config::default().is_use_trash());
+    assert!(config.is_disk_space());
+    assert_eq!(config.compact_width(), 0);
+    assert_eq!(config.symlink_navigation(), SymlinkNavigation::Physical);
+    assert!(config.is_follow_symlinks());
+    assert_eq!(config.enter_action(), EnterAction::ChangeOrPrintForFiles);
+    assert!(config.is_show_parent_entry());
+    assert_eq!(config.preview_trigger(), PreviewTrigger::Manual);
+    assert_eq!(config.display_path(&home::home_dir().unwrap()), home::home_dir().unwrap().to_string_lossy());
+  }
+
+  #[test]
+  fn test_parse_file() {
+    let vars = parse_file("sort = \"size\"\ndirs_first = true\npreview_lines = 10\nicons = false\n").unwrap();
+    let config = envy::from_iter::<_, Config>(vars).unwrap();
+    assert_eq!(config.sort_mode(), SortMode::Size);
+    assert!(config.is_dirs_first());
+    assert_eq!(config.preview_lines(), 10);
+    assert!(!config.is_icons());
+
+    assert!(parse_file("sort = ").is_err());
+    let error = |text| parse_file(text).unwrap_err().to_string();
+    assert_eq!(error("icons = 1\nsrot = \"size\""), "Unknown key `srot` at line 2");
+    assert!(error("\nsort = \"big\"").starts_with("Invalid `sort` at line 2: "));
+    assert!(error("preview_lines = \"ten\"").starts_with("Invalid `preview_lines` at line 1: "));
+    assert_eq!(
+      error("column_ratios = [1, 1, 4, 2]"),
+      "Invalid `column_ratios` at line 1: expected a string, an integer or a boolean, got array"
+    );
+  }
+
+  #[test]
+  fn test_known_vars() {
+    let vars = [("_ED_SORT", "size"), ("_ED_FOO", "1"), ("_ED_PREVIEW_LINES", "ten"), ("PATH", "/bin")];
+    let vars = known_vars(vars.into_iter().map(|(key, value)| (key.to_string(), value.to_string())));
+    assert_eq!(vars, [("_ED_SORT".to_string(), "size".to_string()), ("_ED_PREVIEW_LINES".into(), "ten".into())]);
+    assert!(envy::from_iter::<_, Config>(vars).is_err());
   }
 }
@@ -0,0 +1,37 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use super::Log;
+
+pub struct LastDir;
+
+impl LastDir {
+  pub fn output_path() -> PathBuf {
+    Log::output_path().with_file_name("last_dir")
+  }
+
+  /// The saved directory, if it still exists
+  pub fn read() -> Option<PathBuf> {
+    let path = PathBuf::from(fs::read_to_string(Self::output_path()).ok()?);
+    path.is_dir().then_some(path)
+  }
+
+  pub fn write(path: &Path) -> anyhow::Result<()> {
+    let output_path = Self::output_path();
+    fs::create_dir_all(output_path.parent().unwrap())?;
+    fs::write(output_path, path.to_string_lossy().as_bytes())?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_output_path() {
+    assert_eq!(LastDir::output_path(), home::home_dir().unwrap().join(".easychangedirectory").join("last_dir"));
+  }
+}
@@ -1,8 +1,8 @@
-use std::{fs::File, path::PathBuf};
+use std::{env, fs, fs::File, path::PathBuf};
 
-use ::log::info;
+use ::log::{debug, LevelFilter};
 use crossterm::event::KeyEvent;
-use simplelog::{CombinedLogger, Config, LevelFilter, WriteLogger};
+use simplelog::{ConfigBuilder, WriteLogger};
 
 use crate::app::App;
 
@@ -13,26 +13,53 @@ impl Log {
     home::home_dir().unwrap().join(format!(".{}", env!("CARGO_PKG_NAME"))).join("ed.log")
   }
 
-  pub fn init() {
-    let path = Self::output_path();
-    let parent = path.parent().unwrap();
-    if !parent.exists() {
-      std::fs::create_dir(parent).unwrap();
+  /// `-v` is info, `-vv` debug and more trace, without them the level in `RUST_LOG`
+  pub fn level(verbose: u8) -> LevelFilter {
+    match verbose {
+      0 => env::var("RUST_LOG").ok().and_then(|value| parse_level(&value)).unwrap_or(LevelFilter::Off),
+      1 => LevelFilter::Info,
+      2 => LevelFilter::Debug,
+      _ => LevelFilter::Trace,
+    }
+  }
+
+  /// Nothing is written when `level` is off or the log is already set up, only the records of this crate are written
+  pub fn init(level: LevelFilter) -> anyhow::Result<()> {
+    if level == LevelFilter::Off || Self::is_enabled() {
+      return Ok(());
     }
-    CombinedLogger::init(vec![WriteLogger::new(LevelFilter::Info, Config::default(), File::create(path).unwrap())])
-      .unwrap();
+    let path = Self::output_path();
+    fs::create_dir_all(path.parent().unwrap())?;
+    let config = ConfigBuilder::new().add_filter_allow_str(env!("CARGO_CRATE_NAME")).build();
+    WriteLogger::init(level, config, File::create(path)?)?;
+    Ok(())
+  }
+
+  pub fn is_enabled() -> bool {
+    ::log::max_level() != LevelFilter::Off
   }
 
   pub fn write(app: &App, key: &KeyEvent) {
-    info!("--------------------------------");
-    info!("path: {:?}", app.wd);
-    info!("selected: {:?}", app.items.state.selected());
-    info!("key: {:?}", key.code);
-    info!("mode: {:?}", app.mode);
-    info!("search: {:?}", app.search.text);
+    debug!("--------------------------------");
+    debug!("path: {:?}", app.wd);
+    debug!("selected: {:?}", app.items.state.selected());
+    debug!("key: {:?}", key.code);
+    debug!("mode: {:?}", app.mode);
+    debug!("search: {:?}", app.search.text);
+    debug!("jump: {:?}", app.jump);
   }
 }
 
+/// A level such as `debug`, or the level of this crate in directives such as `warn,easychangedirectory=debug`
+fn parse_level(value: &str) -> Option<LevelFilter> {
+  value.split(',').map(str::trim).fold(None, |level, directive| match directive.split_once('=') {
+    Some((target, target_level)) if target.trim() == env!("CARGO_CRATE_NAME") => target_level.trim().parse().ok(),
+    Some(_) => level,
+    // A level for this crate wins over the default
+    None => level.or_else(|| directive.parse().ok()),
+  })
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -41,4 +68,20 @@ mod tests {
   fn test_output_path() {
     assert_eq!(Log::output_path(), home::home_dir().unwrap().join(".easychangedirectory").join("ed.log"));
   }
+
+  #[test]
+  fn test_level() {
+    assert_eq!(Log::level(1), LevelFilter::Info);
+    assert_eq!(Log::level(2), LevelFilter::Debug);
+    assert_eq!(Log::level(5), LevelFilter::Trace);
+  }
+
+  #[test]
+  fn test_parse_level() {
+    assert_eq!(parse_level("DEBUG"), Some(LevelFilter::Debug));
+    assert_eq!(parse_level("warn,easychangedirectory=trace"), Some(LevelFilter::Trace));
+    assert_eq!(parse_level("easychangedirectory=info,warn"), Some(LevelFilter::Info));
+    assert_eq!(parse_level("notify=debug"), None);
+    assert_eq!(parse_level("loud"), None);
+  }
 }
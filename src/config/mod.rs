@@ -1,5 +1,11 @@
+mod bookmarks;
+mod column_ratios;
 mod env;
+mod last_dir;
 mod log;
 
+pub use self::bookmarks::Bookmarks;
+pub use self::column_ratios::SavedColumnRatios;
 pub use self::env::Config;
+pub use self::last_dir::LastDir;
 pub use self::log::Log;
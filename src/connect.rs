@@ -1,4 +1,14 @@
-use std::{fs::File, io::Write, path::Path};
+use std::{
+  env,
+  fs::{File, OpenOptions},
+  io::Write,
+  path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+/// A file descriptor taking the place of stdout for what is printed, e.g. `3` with `3>&1 >/dev/tty`
+pub const OUT_FD_VAR: &str = "ED_OUT_FD";
 
 pub fn pipe_shell(path: &Path, temp_path: &str) -> anyhow::Result<()> {
   let mut f = File::create(temp_path)?;
@@ -6,3 +16,73 @@ pub fn pipe_shell(path: &Path, temp_path: &str) -> anyhow::Result<()> {
 
   Ok(())
 }
+
+/// Where the path printed with `p` or `--print-only` and the JSON go, so that they do not mix with the terminal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Output {
+  Stdout,
+  /// Given with `--out-file`, or `/dev/fd/N` for `ED_OUT_FD`
+  File(PathBuf),
+}
+
+impl Output {
+  /// `out_file` takes precedence over `ED_OUT_FD`, stdout without either
+  pub fn new(out_file: Option<&Path>) -> anyhow::Result<Self> {
+    match (out_file, env::var(OUT_FD_VAR)) {
+      (Some(path), _) => Ok(Output::File(path.into())),
+      (None, Ok(fd)) => Self::from_fd(&fd),
+      (None, Err(_)) => Ok(Output::Stdout),
+    }
+  }
+  #[cfg(unix)]
+  fn from_fd(fd: &str) -> anyhow::Result<Self> {
+    let fd = fd.trim().parse::<u32>().map_err(|_| anyhow::anyhow!("Invalid {}: {}", OUT_FD_VAR, fd))?;
+    Ok(Output::File(PathBuf::from(format!("/dev/fd/{}", fd))))
+  }
+  #[cfg(not(unix))]
+  fn from_fd(_: &str) -> anyhow::Result<Self> {
+    anyhow::bail!("{} is not supported on this platform, use --out-file", OUT_FD_VAR)
+  }
+  /// `text` followed by a newline, replacing what the file had
+  pub fn write(&self, text: &str) -> anyhow::Result<()> {
+    match self {
+      Output::Stdout => println!("{}", text),
+      Output::File(path) => {
+        let mut file = OpenOptions::new()
+          .write(true)
+          .create(true)
+          .truncate(true)
+          .open(path)
+          .with_context(|| format!("Failed to open {}", path.display()))?;
+        writeln!(file, "{}", text)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+
+  use super::*;
+  use crate::util::TestDir;
+
+  #[test]
+  fn test_output() {
+    let dir = TestDir::new("test_output");
+    let path = dir.join("output");
+    let output = Output::new(Some(&path)).unwrap();
+    assert_eq!(output, Output::File(path.clone()));
+    output.write("/tmp/longer").unwrap();
+    output.write("/tmp").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "/tmp\n");
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_from_fd() {
+    assert_eq!(Output::from_fd("3").unwrap(), Output::File("/dev/fd/3".into()));
+    assert!(Output::from_fd("three").is_err());
+  }
+}
@@ -1,5 +1,13 @@
+/// Exit code when an error is printed, see also `Action::exit_code`
+pub const EXIT_ERROR: i32 = 2;
+
 pub trait PrintError {
   fn eprintln(&self);
+  /// Print the error and exit with `EXIT_ERROR`
+  fn exit(&self) -> ! {
+    self.eprintln();
+    std::process::exit(EXIT_ERROR)
+  }
 }
 
 impl PrintError for anyhow::Error {
@@ -6,10 +6,30 @@ use serde_json::json;
 use crate::shell::Shell;
 
 pub fn init(shell: &Shell) -> anyhow::Result<()> {
-  let shellscript = Handlebars::new()
-    .render_template(shell.get_template(), &json!({ "temp_path": temp_dir().join("_easychangedirectory.txt") }))?;
-
-  println!("{}", shellscript);
+  println!("{}", render(shell)?);
 
   Ok(())
 }
+
+/// The function `ed` for `shell`, which runs the app with its arguments and changes to the chosen directory
+fn render(shell: &Shell) -> anyhow::Result<String> {
+  Ok(
+    Handlebars::new()
+      .render_template(shell.get_template(), &json!({ "temp_path": temp_dir().join("_easychangedirectory.txt") }))?,
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_render() {
+    let temp_path = temp_dir().join("_easychangedirectory.txt");
+    for shell in [Shell::Bash, Shell::Fish, Shell::Powershell, Shell::Zsh] {
+      let script = render(&shell).unwrap();
+      assert!(script.contains(&temp_path.display().to_string()));
+      assert!(!script.contains("{{"));
+    }
+  }
+}
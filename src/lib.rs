@@ -1,4 +1,14 @@
-//! This crate is a command line tool, not a library
+//! A command line tool for choosing a directory, whose picker can also be embedded
+//!
+//! ```no_run
+//! use easychangedirectory::{pick_directory, Action, Config};
+//!
+//! let config = Config::new()?;
+//! if let Action::Change(path) = pick_directory(None, &config)? {
+//!   println!("{}", path.display());
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
 
 mod action;
 mod app;
@@ -7,11 +17,15 @@ mod config;
 mod connect;
 pub mod error;
 mod init;
+mod list;
 mod shell;
+mod util;
 
-pub use crate::app::app;
+pub use crate::action::Action;
+pub use crate::app::{app, pick_directory, pick_directory_in, InitialSelection, Only};
 pub use crate::cli::cli;
 pub use crate::config::Config;
 pub use crate::config::Log;
-pub use crate::connect::pipe_shell;
+pub use crate::connect::{pipe_shell, Output};
 pub use crate::init::init;
+pub use crate::list::list;
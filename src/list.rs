@@ -0,0 +1,49 @@
+use std::{fs, path::Path};
+
+use anyhow::bail;
+use serde::Serialize;
+
+use crate::{
+  app::{read_items, Item, ItemPath, ReadOptions},
+  Config,
+};
+
+#[derive(Serialize)]
+struct Entry {
+  path: String,
+  #[serde(rename = "type")]
+  kind: &'static str,
+  /// `None` for anything other than files
+  size: Option<u64>,
+}
+
+/// Print the entries of `path` in the same order as the app
+pub fn list(path: &Path, json: bool) -> anyhow::Result<()> {
+  if !path.is_dir() {
+    bail!("Not a directory: {}", path.display());
+  }
+  let options = ReadOptions::from_config(&Config::new()?);
+  let items = read_items(path, &options)?;
+  let paths = items.iter().filter_map(|item| Some((item.get_path()?, &item.item)));
+
+  if json {
+    let entries = paths
+      .map(|(path, item)| Entry {
+        kind: match item {
+          Item::Path(ItemPath::Dir(_)) => "dir",
+          Item::Path(ItemPath::File(_)) => "file",
+          Item::Path(ItemPath::Symlink(_)) => "symlink",
+          _ => "unknown",
+        },
+        size: fs::metadata(&path).ok().filter(|metadata| metadata.is_file()).map(|metadata| metadata.len()),
+        path: path.display().to_string(),
+      })
+      .collect::<Vec<_>>();
+    println!("{}", serde_json::to_string(&entries)?);
+  } else {
+    for (path, _) in paths {
+      println!("{}", path.file_name().unwrap_or_default().to_string_lossy());
+    }
+  }
+  Ok(())
+}
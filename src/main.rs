@@ -1,3 +1,5 @@
+use std::process;
+
 use easychangedirectory as ed;
 
 use ed::error::PrintError;
@@ -7,24 +9,55 @@ fn main() {
 
   cli.match_options();
 
-  let action = match ed::app() {
+  if let Err(e) = ed::Log::init(ed::Log::level(cli.verbosity())) {
+    e.eprintln();
+  }
+
+  // Checked before the terminal is taken over
+  let output = match ed::Output::new(cli.out_file()) {
+    Ok(output) => output,
+    Err(e) => e.exit(),
+  };
+
+  let action = match ed::app(
+    cli.start_path(),
+    cli.select(),
+    cli.is_no_restore(),
+    cli.is_read_only(),
+    cli.is_stdin(),
+    cli.only(),
+  ) {
+    Ok(action) if cli.is_print_only() => action.print_only(),
     Ok(action) => action,
-    Err(e) => {
-      e.eprintln();
-      return;
-    }
+    Err(e) => e.exit(),
   };
 
-  let action_path = action.execute();
+  if cli.is_json() {
+    if let Err(e) = action.to_json().and_then(|json| output.write(&json)) {
+      e.exit();
+    }
+    cli.prepare_cd(&action.cd_path());
+    process::exit(action.exit_code());
+  }
+
+  let action_path = match action.execute(&output) {
+    Ok(action_path) => action_path,
+    Err(e) => e.exit(),
+  };
 
   cli.prepare_cd(&action_path);
 
-  if let Ok(config) = ed::Config::new() {
-    if config.is_pwd() {
-      println!("Now: {}", action_path.display());
-    }
-    if config.is_log() {
-      println!("Log output location: {}", ed::Log::output_path().display());
+  match ed::Config::new() {
+    Ok(config) => {
+      if config.is_pwd() && !cli.is_print_only() {
+        println!("Now: {}", config.display_path(&action_path));
+      }
+      if ed::Log::is_enabled() {
+        println!("Log output location: {}", config.display_path(&ed::Log::output_path()));
+      }
     }
+    Err(e) => e.eprintln(),
   }
+
+  process::exit(action.exit_code());
 }
@@ -24,18 +24,22 @@ pub const BASH: &str = r#"
 # eval "$(easychangedirectory --init bash)"
 
 function ed() {
-  if [[ "$#" -eq 0 ]]; then
-    temp_path="{{ temp_path }}.$$"
-    easychangedirectory -t "${temp_path}"
-    cd_path=`cat "${temp_path}"`
-    cd "${cd_path}" || return
-  elif [[ "$#" -eq 1 ]] && [[ "$1" =~ ^-+[a-zA-Z]+ ]]; then
-    easychangedirectory "$1"
-  elif [[ "$#" -eq 1 ]]; then
+  if [[ "$#" -eq 1 ]] && [[ ! "$1" =~ ^- ]]; then
     cd "$1" || return
-  else
-    echo 'Too many arguments'
+    return
+  fi
+  local temp_path="{{ temp_path }}.$$"
+  rm -f "${temp_path}"
+  easychangedirectory -t "${temp_path}" "$@"
+  local code=$?
+  # Not written when it exits before choosing, e.g. `--env` or an error
+  if [[ -s "${temp_path}" ]]; then
+    local cd_path
+    cd_path=$(cat "${temp_path}")
+    rm -f "${temp_path}"
+    cd "${cd_path}" || return
   fi
+  return "${code}"
 }
 "#;
 
@@ -44,19 +48,21 @@ pub const FISH: &str = r#"
 # easychangedirectory --init fish | source
 
 function ed
-  set arg_cnt (count $argv)
-  if test "$arg_cnt" -eq 0
-    set temp_path "{{ temp_path }}.$fish_pid"
-    easychangedirectory -t "$temp_path"
-    set cd_path (cat "$temp_path")
-    cd "$cd_path"
-  else if string match -r '^x\-+[a-zA-Z]+' "x$argv[1]" &> /dev/null
-    easychangedirectory "$argv[1]"
-  else if test "$arg_cnt" -eq 1
+  if test (count $argv) -eq 1; and not string match -q -- '-*' "$argv[1]"
     cd "$argv[1]"
-  else
-    echo 'Too many arguments'
+    return
+  end
+  set -l temp_path "{{ temp_path }}.$fish_pid"
+  rm -f "$temp_path"
+  easychangedirectory -t "$temp_path" $argv
+  set -l code $status
+  # Not written when it exits before choosing, e.g. `--env` or an error
+  if test -s "$temp_path"
+    set -l cd_path (cat "$temp_path")
+    rm -f "$temp_path"
+    cd "$cd_path"
   end
+  return $code
 end
 "#;
 
@@ -65,17 +71,18 @@ pub const POWERSHELL: &str = r#"
 # Invoke-Expression (& { (easychangedirectory --init powershell | Out-String) } )
 
 function ed {
-  if ($args.Length -eq 0) {
-    $temp_path = "{{ temp_path }}.$pid"
-    easychangedirectory -t $temp_path
-    $cd_path = (cat $temp_path)
-    cd $cd_path
-  } elseif ($args.Length -eq 1 -and $args[0] -match '^-+[a-zA-Z]+') {
-    easychangedirectory $args[0]
-  } elseif ($args.Length -eq 1) {
+  if ($args.Length -eq 1 -and $args[0] -notmatch '^-') {
     cd $args[0]
-  } else {
-    echo 'Too many arguments'
+    return
+  }
+  $temp_path = "{{ temp_path }}.$pid"
+  Remove-Item -ErrorAction Ignore $temp_path
+  easychangedirectory -t $temp_path @args
+  # Not written when it exits before choosing, e.g. `--env` or an error
+  if ((Test-Path $temp_path) -and (Get-Item $temp_path).Length -gt 0) {
+    $cd_path = Get-Content -Raw $temp_path
+    Remove-Item $temp_path
+    cd $cd_path
   }
 }
 "#;
@@ -85,18 +92,22 @@ pub const ZSH: &str = r#"
 # eval "$(easychangedirectory --init zsh)"
 
 function ed() {
-  if [[ "$#" -eq 0 ]]; then
-    temp_path="{{ temp_path }}.$$"
-    easychangedirectory -t "${temp_path}"
-    cd_path=`cat ${temp_path}`
-    cd "${cd_path}" || return
-  elif [[ "$#" -eq 1 ]] && [[ "$1" =~ ^-+[a-zA-Z]+ ]]; then
-    easychangedirectory "$1"
-  elif [[ "$#" -eq 1 ]]; then
+  if [[ "$#" -eq 1 ]] && [[ ! "$1" =~ ^- ]]; then
     cd "$1" || return
-  else
-    echo 'Too many arguments'
+    return
+  fi
+  local temp_path="{{ temp_path }}.$$"
+  rm -f "${temp_path}"
+  easychangedirectory -t "${temp_path}" "$@"
+  local code=$?
+  # Not written when it exits before choosing, e.g. `--env` or an error
+  if [[ -s "${temp_path}" ]]; then
+    local cd_path
+    cd_path=$(cat "${temp_path}")
+    rm -f "${temp_path}"
+    cd "${cd_path}" || return
   fi
+  return "${code}"
 }
 "#;
 
@@ -0,0 +1,301 @@
+use std::{
+  fs, io,
+  path::{Path, PathBuf},
+  time::SystemTime,
+};
+
+use anyhow::bail;
+use chrono::{
+  format::{Item, StrftimeItems},
+  DateTime, Local,
+};
+use serde::Deserialize;
+
+const BINARY_UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+const DECIMAL_UNITS: [&str; 7] = ["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+
+/// Steps of the units `format_size` uses
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeUnit {
+  /// 1024 bytes, `KiB`
+  #[default]
+  Binary,
+  /// 1000 bytes, `kB`
+  Decimal,
+}
+
+impl SizeUnit {
+  pub fn name(&self) -> &'static str {
+    match self {
+      SizeUnit::Binary => "binary",
+      SizeUnit::Decimal => "decimal",
+    }
+  }
+  fn base(&self) -> f64 {
+    match self {
+      SizeUnit::Binary => 1024.0,
+      SizeUnit::Decimal => 1000.0,
+    }
+  }
+  fn units(&self) -> &'static [&'static str; 7] {
+    match self {
+      SizeUnit::Binary => &BINARY_UNITS,
+      SizeUnit::Decimal => &DECIMAL_UNITS,
+    }
+  }
+}
+
+/// e.g. `512 B`, `1.5 KiB`, with one decimal from the second unit
+pub fn format_size(bytes: u64, unit: SizeUnit) -> String {
+  let (base, units) = (unit.base(), unit.units());
+  let mut value = bytes as f64;
+  let mut i = 0;
+  while value >= base && i < units.len() - 1 {
+    value /= base;
+    i += 1;
+  }
+  if i == 0 {
+    return format!("{} {}", bytes, units[0]);
+  }
+  // Would be shown as `1024.0 KiB`
+  if (value * 10.0).round() / 10.0 >= base && i < units.len() - 1 {
+    value /= base;
+    i += 1;
+  }
+  format!("{:.1} {}", value, units[i])
+}
+
+/// Modified times are shown like `2024-05-01 12:34` by default
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+const ISO8601_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+/// How modified times are shown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateFormat {
+  /// strftime-style specifiers
+  Custom(String),
+  /// e.g. `3 days ago`
+  Relative,
+}
+
+impl Default for DateFormat {
+  fn default() -> Self {
+    DateFormat::Custom(DEFAULT_DATE_FORMAT.into())
+  }
+}
+
+impl DateFormat {
+  /// `relative`, `iso8601` or strftime-style specifiers such as `%d/%m/%Y`
+  pub fn new(spec: Option<&str>) -> anyhow::Result<Self> {
+    Ok(match spec.map(str::trim).unwrap_or_default() {
+      "" => DateFormat::default(),
+      "relative" => DateFormat::Relative,
+      "iso8601" => DateFormat::Custom(ISO8601_DATE_FORMAT.into()),
+      format if StrftimeItems::new(format).any(|item| item == Item::Error) => bail!("Unknown specifier: {}", format),
+      format => DateFormat::Custom(format.into()),
+    })
+  }
+  pub fn format(&self, time: SystemTime) -> String {
+    let time = DateTime::<Local>::from(time);
+    match self {
+      DateFormat::Custom(format) => time.format(format).to_string(),
+      DateFormat::Relative => format_elapsed((Local::now() - time).num_seconds()),
+    }
+  }
+}
+
+/// e.g. `just now`, `1 minute ago`, `3 days ago`, times in the future are `just now` too
+fn format_elapsed(seconds: i64) -> String {
+  const UNITS: [(i64, &str); 6] = [
+    (365 * 24 * 60 * 60, "year"),
+    (30 * 24 * 60 * 60, "month"),
+    (7 * 24 * 60 * 60, "week"),
+    (24 * 60 * 60, "day"),
+    (60 * 60, "hour"),
+    (60, "minute"),
+  ];
+  match UNITS.iter().find(|(unit, _)| seconds >= *unit) {
+    Some((unit, name)) => {
+      let count = seconds / unit;
+      format!("{} {}{} ago", count, name, if count == 1 { "" } else { "s" })
+    }
+    None => "just now".into(),
+  }
+}
+
+/// `path` with `home` at its start replaced by `~`, other paths in full
+pub fn abbreviate_home(path: &Path, home: Option<&Path>) -> String {
+  match home.and_then(|home| path.strip_prefix(home).ok()) {
+    Some(rest) if rest.as_os_str().is_empty() => "~".into(),
+    Some(rest) => format!("~{}{}", std::path::MAIN_SEPARATOR, rest.display()),
+    None => path.to_string_lossy().into(),
+  }
+}
+
+/// `fs::canonicalize` without the `\\?\` prefix Windows adds, so that paths compare equal to those read from directories
+pub fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+  fs::canonicalize(path).map(|path| strip_verbatim(&path))
+}
+
+/// `\\?\C:\dir` becomes `C:\dir` and `\\?\UNC\server\share` becomes `\\server\share`, other paths are kept
+pub fn strip_verbatim(path: &Path) -> PathBuf {
+  #[cfg(windows)]
+  {
+    use std::path::{Component, Prefix};
+
+    let rest = || path.components().skip(1).collect::<PathBuf>();
+    if let Some(Component::Prefix(prefix)) = path.components().next() {
+      match prefix.kind() {
+        Prefix::VerbatimDisk(disk) => return PathBuf::from(format!("{}:", disk as char)).join(rest()),
+        Prefix::VerbatimUNC(server, share) => {
+          let root = format!(r"\\{}\{}", server.to_string_lossy(), share.to_string_lossy());
+          return PathBuf::from(root).join(rest());
+        }
+        _ => {}
+      }
+    }
+  }
+  path.to_path_buf()
+}
+
+/// The roots of the drives that exist, e.g. `C:\`, shown above a drive root
+#[cfg(windows)]
+pub fn drive_roots() -> Vec<PathBuf> {
+  (b'A'..=b'Z').map(|letter| PathBuf::from(format!(r"{}:\", letter as char))).filter(|root| root.exists()).collect()
+}
+
+/// Only `vars`, whatever the config file and the environment of the test run
+#[cfg(test)]
+pub fn config(vars: &[(&str, &str)]) -> crate::Config {
+  envy::from_iter(vars.iter().map(|(key, value)| (key.to_string(), value.to_string()))).unwrap()
+}
+
+/// A directory for a test under the temporary directory, unique to the process and removed on drop even if the test fails
+#[cfg(test)]
+pub struct TestDir(PathBuf);
+
+#[cfg(test)]
+impl TestDir {
+  pub fn new(name: &str) -> Self {
+    let dir = std::env::temp_dir().join(format!("_easychangedirectory_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    TestDir(dir)
+  }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TestDir {
+  type Target = Path;
+
+  fn deref(&self) -> &Path {
+    &self.0
+  }
+}
+
+#[cfg(test)]
+impl AsRef<Path> for TestDir {
+  fn as_ref(&self) -> &Path {
+    &self.0
+  }
+}
+
+#[cfg(test)]
+impl Drop for TestDir {
+  fn drop(&mut self) {
+    let _ = fs::remove_dir_all(&self.0);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_test_dir() {
+    let dir = TestDir::new("test_test_dir");
+    fs::write(dir.join("a"), "").unwrap();
+    let path = dir.to_path_buf();
+    assert!(path.ends_with(format!("_easychangedirectory_test_test_dir_{}", std::process::id())));
+    drop(dir);
+    assert!(!path.exists());
+  }
+
+  #[test]
+  fn test_format_size() {
+    assert_eq!(format_size(0, SizeUnit::Binary), "0 B");
+    assert_eq!(format_size(1023, SizeUnit::Binary), "1023 B");
+    assert_eq!(format_size(1024, SizeUnit::Binary), "1.0 KiB");
+    assert_eq!(format_size(1536, SizeUnit::Binary), "1.5 KiB");
+    assert_eq!(format_size(1024 * 1024 - 1, SizeUnit::Binary), "1.0 MiB");
+    assert_eq!(format_size(5 * 1024 * 1024 * 1024, SizeUnit::Binary), "5.0 GiB");
+    assert_eq!(format_size(u64::MAX, SizeUnit::Binary), "16.0 EiB");
+
+    assert_eq!(format_size(999, SizeUnit::Decimal), "999 B");
+    assert_eq!(format_size(1000, SizeUnit::Decimal), "1.0 kB");
+    assert_eq!(format_size(1024, SizeUnit::Decimal), "1.0 kB");
+    assert_eq!(format_size(2_500_000, SizeUnit::Decimal), "2.5 MB");
+    assert_eq!(format_size(u64::MAX, SizeUnit::Decimal), "18.4 EB");
+  }
+
+  #[test]
+  fn test_date_format() {
+    assert_eq!(DateFormat::new(None).unwrap(), DateFormat::default());
+    assert_eq!(DateFormat::new(Some(" relative ")).unwrap(), DateFormat::Relative);
+    assert_eq!(DateFormat::new(Some("iso8601")).unwrap(), DateFormat::Custom(ISO8601_DATE_FORMAT.into()));
+    assert!(DateFormat::new(Some("%Q")).is_err());
+
+    let time = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(86400 * 365);
+    let local = DateTime::<Local>::from(time);
+    assert_eq!(DateFormat::new(Some("%d/%m/%Y")).unwrap().format(time), local.format("%d/%m/%Y").to_string());
+    assert_eq!(DateFormat::Relative.format(SystemTime::now()), "just now");
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_abbreviate_home() {
+    let home = Some(Path::new("/home/user"));
+    assert_eq!(abbreviate_home(Path::new("/home/user/src/app"), home), "~/src/app");
+    assert_eq!(abbreviate_home(Path::new("/home/user"), home), "~");
+    assert_eq!(abbreviate_home(Path::new("/home/user2"), home), "/home/user2");
+    assert_eq!(abbreviate_home(Path::new("/usr/share"), home), "/usr/share");
+    assert_eq!(abbreviate_home(Path::new("/home/user/src"), None), "/home/user/src");
+  }
+
+  #[test]
+  fn test_format_elapsed() {
+    assert_eq!(format_elapsed(-5), "just now");
+    assert_eq!(format_elapsed(59), "just now");
+    assert_eq!(format_elapsed(60), "1 minute ago");
+    assert_eq!(format_elapsed(3 * 24 * 60 * 60 + 5), "3 days ago");
+    assert_eq!(format_elapsed(400 * 24 * 60 * 60), "1 year ago");
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_strip_verbatim() {
+    assert_eq!(strip_verbatim(Path::new("/usr/lib")), Path::new("/usr/lib"));
+    assert_eq!(canonicalize(Path::new("/usr/../usr")).unwrap(), Path::new("/usr"));
+  }
+
+  #[cfg(windows)]
+  #[test]
+  fn test_strip_verbatim() {
+    assert_eq!(strip_verbatim(Path::new(r"\\?\C:\Users\me")), Path::new(r"C:\Users\me"));
+    assert_eq!(strip_verbatim(Path::new(r"\\?\C:\")), Path::new(r"C:\"));
+    assert_eq!(strip_verbatim(Path::new(r"\\?\UNC\server\share\dir")), Path::new(r"\\server\share\dir"));
+    assert_eq!(strip_verbatim(Path::new(r"\\server\share")), Path::new(r"\\server\share"));
+    assert_eq!(strip_verbatim(Path::new(r"C:\Users")), Path::new(r"C:\Users"));
+  }
+
+  #[cfg(windows)]
+  #[test]
+  fn test_roots_have_no_parent() {
+    // Moving up stops at these instead of unwrapping the parent
+    assert_eq!(Path::new(r"C:\").parent(), None);
+    assert_eq!(Path::new(r"\\server\share\").parent(), None);
+    assert_eq!(Path::new(r"\\server\share\dir").parent(), Some(Path::new(r"\\server\share\")));
+    assert!(drive_roots().iter().all(|root| root.parent().is_none()));
+  }
+}